@@ -1,24 +1,26 @@
-use crate::{fdo_magic::builtin::LoadedDatabase, read_bytes, MIME};
+use crate::{read_bytes, TextClassifier, MIME};
 use fnv::FnvHashMap;
-use std::{path::Path, sync::Arc};
+use std::fs::{self, FileType, Metadata};
+use std::path::Path;
 
-pub(crate) struct BaseType {
-  ldb: Arc<LoadedDatabase>,
+pub struct BaseType {
+  /// Decides `text/plain`
+  text: TextClassifier,
 }
 
 impl BaseType {
-  pub fn new(ldb: Arc<LoadedDatabase>) -> Self {
-    Self { ldb }
+  pub const fn new(text: TextClassifier) -> Self {
+    Self { text }
   }
 }
 
 impl crate::Checker for BaseType {
   fn from_u8(&self, file: &[u8], mimetype: &str) -> bool {
-    from_u8(file, mimetype)
+    from_u8(self.text, file, mimetype)
   }
 
   fn from_filepath(&self, filepath: &Path, mimetype: &str) -> bool {
-    from_filepath(filepath, mimetype)
+    from_filepath(self.text, filepath, mimetype)
   }
 
   fn get_supported(&self) -> Vec<MIME> {
@@ -34,51 +36,78 @@ impl crate::Checker for BaseType {
   }
 }
 
-/// If there are any null bytes, return False. Otherwise return True.
-fn is_text_plain_from_u8(b: &[u8]) -> bool {
-  bytecount::count(b, 0) == 0
-}
-
 // TODO: Hoist the main logic here somewhere else. This'll get redundant fast!
-fn is_text_plain_from_filepath(filepath: &Path) -> bool {
+fn is_text_plain_from_filepath(text: TextClassifier, filepath: &Path) -> bool {
   let b = match read_bytes(filepath, 512) {
     Ok(x) => x,
     Err(_) => return false,
   };
-  is_text_plain_from_u8(b.as_slice())
+  text.classify(b.as_slice()).is_text()
 }
 
 #[allow(unused_variables)]
-pub fn from_u8(b: &[u8], mimetype: &str) -> bool {
+pub fn from_u8(text: TextClassifier, b: &[u8], mimetype: &str) -> bool {
   if mimetype == "application/octet-stream" || mimetype == "all/allfiles" {
     // Both of these are the case if we have a bytestream at all
     return true;
   }
   if mimetype == "text/plain" {
-    is_text_plain_from_u8(b)
+    text.classify(b).is_text()
+  } else if mimetype == "application/x-zerosize" {
+    b.is_empty()
   } else {
     // ...how did we get bytes for this?
     false
   }
 }
 
-pub fn from_filepath(filepath: &Path, mimetype: &str) -> bool {
-  use std::fs;
-
+/// Symbolic links are not followed, the database resolves them if it follows links.
+pub fn from_filepath(text: TextClassifier, filepath: &Path, mimetype: &str) -> bool {
   // Being bad with error handling here,
   // but if you can't open it it's probably not a file.
-  let meta = match fs::metadata(filepath) {
-    Ok(x) => x,
-    Err(_) => {
-      return false;
-    },
+  let Ok(meta) = fs::symlink_metadata(filepath) else {
+    return false;
   };
 
   match mimetype {
     "all/all" => true,
     "all/allfiles" | "application/octet-stream" => meta.is_file(),
+    "application/x-zerosize" => meta.is_file() && meta.len() == 0,
     "inode/directory" => meta.is_dir(),
-    "text/plain" => is_text_plain_from_filepath(filepath),
+    "inode/mount-point" => meta.is_dir() && is_mount_point(filepath, &meta),
+    "inode/symlink" => meta.file_type().is_symlink(),
+    "text/plain" => is_text_plain_from_filepath(text, filepath),
+    _ => is_special_file(meta.file_type(), mimetype),
+  }
+}
+
+/// A directory is a mount point if its parent is on another device or it is the root
+#[cfg(unix)]
+fn is_mount_point(dir: &Path, meta: &Metadata) -> bool {
+  use std::os::unix::fs::MetadataExt;
+  fs::symlink_metadata(dir.join(".."))
+    .is_ok_and(|parent| parent.dev() != meta.dev() || parent.ino() == meta.ino())
+}
+
+#[cfg(not(unix))]
+const fn is_mount_point(_dir: &Path, _meta: &Metadata) -> bool {
+  false
+}
+
+/// Devices, FIFOs and sockets
+#[cfg(unix)]
+fn is_special_file(file_type: FileType, mimetype: &str) -> bool {
+  use std::os::unix::fs::FileTypeExt;
+  match mimetype {
+    "inode/blockdevice" => file_type.is_block_device(),
+    "inode/chardevice" => file_type.is_char_device(),
+    "inode/fifo" => file_type.is_fifo(),
+    "inode/socket" => file_type.is_socket(),
     _ => false,
   }
 }
+
+#[cfg(not(unix))]
+const fn is_special_file(_file_type: FileType, _mimetype: &str) -> bool {
+  false
+}
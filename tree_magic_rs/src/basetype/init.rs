@@ -10,8 +10,15 @@ pub fn get_subclasses() -> Vec<(MIME<'static>, MIME<'static>)> {
   vec![
     ("all/all", "all/allfiles"),
     ("all/all", "inode/directory"),
+    ("inode/directory", "inode/mount-point"),
+    ("all/all", "inode/symlink"),
+    ("all/all", "inode/blockdevice"),
+    ("all/all", "inode/chardevice"),
+    ("all/all", "inode/fifo"),
+    ("all/all", "inode/socket"),
     ("all/allfiles", "application/octet-stream"),
     ("application/octet-stream", "text/plain"),
+    ("application/octet-stream", "application/x-zerosize"),
   ]
 }
 
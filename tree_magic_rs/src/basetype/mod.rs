@@ -1,10 +1,17 @@
 //! Handles "base types" such as inode/* and text/plain
-const TYPES: [&str; 5] = [
+const TYPES: [&str; 12] = [
   "all/all",
   "all/allfiles",
   "inode/directory",
+  "inode/mount-point",
+  "inode/symlink",
+  "inode/blockdevice",
+  "inode/chardevice",
+  "inode/fifo",
+  "inode/socket",
   "text/plain",
   "application/octet-stream",
+  "application/x-zerosize",
 ];
 
 pub mod check;
@@ -0,0 +1,644 @@
+//! Detects the MIME types of files and inspects the MIME database.
+//!
+//! Exits with 1 if a file does not match, a file name does not fit the contents
+//! or the database has lint issues, and with 2 if a file could not be read.
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::{
+  io::{self, BufWriter, Read, Write},
+  path::{Path, PathBuf},
+  process::ExitCode,
+};
+use tree_magic_rs::db::{
+  lint_db, Alias, DbProvider, GlobDatabase, GlobRule, LoadResult, MagicRule, OwnedBuildableDb,
+  SharedMimeDbProviderError, SharedMimeDbProviderExt, Subclass, XmlNamespaceDatabase,
+};
+use tree_magic_rs::{MimeDatabase, VerdictKind};
+
+/// How many bytes are read for detection, like `MimeDatabase::from_filepath` does
+const SNIFF_LEN: u64 = 2048;
+
+const MISMATCH: u8 = 1;
+const ERROR: u8 = 2;
+
+/// Detects the MIME types of files and inspects the MIME database.
+#[derive(Parser)]
+#[command(name = "tmagic", version)]
+struct Cli {
+  /// Loads the database from these shared MIME info directories instead of the well known ones
+  #[arg(long = "mime-dir", value_name = "DIR", global = true)]
+  mime_dirs: Vec<PathBuf>,
+  /// The output format
+  #[arg(long, value_enum, default_value_t = Format::Line, global = true)]
+  format: Format,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+  /// One line per result
+  Line,
+  /// A JSON document
+  Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum By {
+  /// The contents of the files
+  Content,
+  /// The file names, without reading the files
+  Name,
+  /// Both, checking that the name fits the contents
+  Both,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Prints the types of files
+  Detect {
+    /// What to detect the types from
+    #[arg(long, value_enum, default_value_t = By::Both)]
+    by: By,
+    /// The files
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+  },
+  /// Checks whether files match the magic rules of a type
+  Match {
+    /// The type
+    mime_type: String,
+    /// The files
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+  },
+  /// Shows how the type of a file was found
+  Explain {
+    /// The file
+    file: PathBuf,
+  },
+  /// Inspects the database
+  #[command(subcommand)]
+  Db(DbCommand),
+  /// Prints the supertypes and subtypes of a type as trees
+  Tree {
+    /// The type
+    mime_type: String,
+  },
+}
+
+#[derive(Subcommand)]
+enum DbCommand {
+  /// Counts the entries loaded from each directory
+  Stats,
+  /// Prints all entries
+  Dump,
+  /// Checks the entries for mistakes
+  Lint,
+}
+
+/// The entries loaded from one directory.
+#[derive(Serialize)]
+struct Source {
+  name: String,
+  #[serde(flatten)]
+  load_result: LoadResult,
+  globs_num: usize,
+}
+
+#[derive(Serialize)]
+struct FileType<'a> {
+  path: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  content_type: Option<&'a str>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  name_type: Option<&'a str>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  verdict: Option<VerdictKind>,
+}
+
+#[derive(Serialize)]
+struct FileMatch {
+  path: String,
+  matches: bool,
+}
+
+#[derive(Serialize)]
+struct Explanation<'a> {
+  path: String,
+  content_type: &'a str,
+  magic_path: Vec<&'a str>,
+  globs: Vec<&'a GlobRule>,
+  name_type: Option<&'a str>,
+  verdict: VerdictKind,
+  supertypes: Vec<&'a str>,
+}
+
+#[derive(Serialize)]
+struct TypeTree<'a> {
+  mime_type: &'a str,
+  parents: Vec<Self>,
+  children: Vec<Self>,
+}
+
+/// Which way a [`TypeTree`] goes
+#[derive(Clone, Copy)]
+enum Relation {
+  Parents,
+  Children,
+}
+
+#[derive(Serialize)]
+struct Dump<'a> {
+  #[serde(flatten)]
+  provider: &'a OwnedBuildableDb,
+  globs: &'a [GlobRule],
+}
+
+fn main() -> ExitCode {
+  let cli = Cli::parse();
+  let mut out = BufWriter::new(io::stdout().lock());
+  match run(cli, &mut out).and_then(|status| out.flush().map(|()| status)) {
+    Ok(status) => ExitCode::from(status),
+    // The reader is gone, like `head` after enough lines
+    Err(err) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+    Err(err) => {
+      eprintln!("tmagic: {err}");
+      ExitCode::from(ERROR)
+    },
+  }
+}
+
+fn run(cli: Cli, out: &mut dyn Write) -> io::Result<u8> {
+  let (provider, globs, xml_namespaces, sources) = load_database(&cli.mime_dirs);
+  let format = cli.format;
+  match cli.command {
+    Command::Db(DbCommand::Stats) => stats(out, format, &sources),
+    Command::Db(DbCommand::Dump) => dump(out, format, &provider, &globs),
+    Command::Db(DbCommand::Lint) => lint(out, format, &provider, &globs),
+    command => {
+      let db = MimeDatabase::from_provider(&provider)
+        .with_globs(globs)
+        .with_xml_namespaces(xml_namespaces);
+      match command {
+        Command::Detect { by, files } => detect(out, format, &db, by, &files),
+        Command::Match { mime_type, files } => match_files(out, format, &db, &mime_type, &files),
+        Command::Explain { file } => explain(out, format, &db, &file),
+        Command::Tree { mime_type } => tree(out, format, &db, &mime_type),
+        Command::Db(_) => unreachable!(),
+      }
+    },
+  }
+}
+
+/// Loads the magic rules, aliases, subclasses, globs and XML root elements
+/// of the shared MIME info directories.
+///
+/// Unreadable or malformed files are reported and skipped.
+fn load_database(
+  mime_dirs: &[PathBuf],
+) -> (
+  OwnedBuildableDb,
+  GlobDatabase,
+  XmlNamespaceDatabase,
+  Vec<Source>,
+) {
+  let mut dirs = mime_dirs.to_vec();
+  if dirs.is_empty() {
+    dirs.push(PathBuf::from("/usr/share/mime"));
+    dirs.push(PathBuf::from("/usr/local/share/mime"));
+    if let Some(home) = std::env::var_os("HOME") {
+      dirs.push(Path::new(&home).join(".local/share/mime"));
+    }
+  }
+  let mut provider = OwnedBuildableDb::new();
+  let mut globs = GlobDatabase::new();
+  let mut xml_namespaces = XmlNamespaceDatabase::new();
+  let mut sources = Vec::new();
+  for dir in dirs {
+    let load_result = match provider.load_from_xdg_shared_magic_dir(&dir) {
+      Ok(load_result) => load_result,
+      Err((load_result, errs)) => {
+        errs.iter().for_each(warn_provider_error);
+        load_result
+      },
+    };
+    let globs_file = dir.join("globs2");
+    let globs_num = match globs.load_globs2_file(&globs_file) {
+      Ok(num) => num,
+      Err(_) if !globs_file.exists() => 0,
+      Err(err) => {
+        eprintln!("tmagic: {}: {}", globs_file.display(), error_chain(&err));
+        0
+      },
+    };
+    let xml_namespaces_file = dir.join("XMLnamespaces");
+    if xml_namespaces_file.exists() {
+      if let Err(err) = xml_namespaces.load_xml_namespaces_file(&xml_namespaces_file) {
+        eprintln!(
+          "tmagic: {}: {}",
+          xml_namespaces_file.display(),
+          error_chain(&err)
+        );
+      }
+    }
+    sources.push(Source {
+      name: dir.display().to_string(),
+      load_result,
+      globs_num,
+    });
+  }
+  #[cfg(feature = "bundled")]
+  if mime_dirs.is_empty() && provider.magic_rules_mut().is_empty() {
+    provider = OwnedBuildableDb::from_provider(&tree_magic_rs::db::BUNDLED_DB);
+    sources.push(Source {
+      name: "bundled".to_string(),
+      load_result: LoadResult {
+        magic_rules_num: provider.magic_rules_mut().len(),
+        aliases_num: provider.aliases_mut().len(),
+        subclasses_num: provider.subclasses_mut().len(),
+      },
+      globs_num: 0,
+    });
+  }
+  (provider, globs, xml_namespaces, sources)
+}
+
+fn warn_provider_error(err: &SharedMimeDbProviderError) {
+  let (SharedMimeDbProviderError::IoError { file_path, .. }
+  | SharedMimeDbProviderError::MagicRuleParseError { file_path, .. }
+  | SharedMimeDbProviderError::LineParseError { file_path, .. }) = err;
+  eprintln!("tmagic: {}: {}", file_path.display(), error_chain(err));
+}
+
+/// Formats an error with its sources.
+fn error_chain(err: &dyn std::error::Error) -> String {
+  let mut message = err.to_string();
+  let mut source = err.source();
+  while let Some(err) = source {
+    message.push_str(": ");
+    message.push_str(&err.to_string());
+    source = err.source();
+  }
+  message
+}
+
+fn write_json<T: Serialize + ?Sized>(out: &mut dyn Write, value: &T) -> io::Result<()> {
+  serde_json::to_writer_pretty(&mut *out, value)?;
+  writeln!(out)
+}
+
+/// Reads the first bytes of a file, enough for detection.
+fn read_head(path: &Path) -> io::Result<Vec<u8>> {
+  let mut bytes = Vec::new();
+  std::fs::File::open(path)?
+    .take(SNIFF_LEN)
+    .read_to_end(&mut bytes)?;
+  Ok(bytes)
+}
+
+fn file_name(path: &Path) -> String {
+  path
+    .file_name()
+    .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
+
+fn detect(
+  out: &mut dyn Write,
+  format: Format,
+  db: &MimeDatabase,
+  by: By,
+  files: &[PathBuf],
+) -> io::Result<u8> {
+  let mut status = 0;
+  let mut results = Vec::new();
+  for path in files {
+    let result = match by {
+      By::Content => db.from_filepath(path).map(|content_type| FileType {
+        path: path.display().to_string(),
+        content_type: Some(content_type),
+        name_type: None,
+        verdict: None,
+      }),
+      By::Name => Some(FileType {
+        path: path.display().to_string(),
+        content_type: None,
+        name_type: db.from_filename(&file_name(path)),
+        verdict: None,
+      }),
+      By::Both => read_head(path).ok().map(|bytes| {
+        let verdict = db.validate(path, &bytes);
+        FileType {
+          path: path.display().to_string(),
+          content_type: Some(verdict.content_type),
+          name_type: verdict.name_type,
+          verdict: Some(verdict.kind),
+        }
+      }),
+    };
+    let Some(result) = result else {
+      eprintln!("tmagic: {}: cannot be read", path.display());
+      status = ERROR;
+      continue;
+    };
+    if result.verdict == Some(VerdictKind::Mismatched) {
+      status = status.max(MISMATCH);
+    }
+    if format == Format::Line {
+      let type_or_unknown = |mime_type: Option<&str>| mime_type.unwrap_or("unknown").to_string();
+      let line = match by {
+        By::Content => type_or_unknown(result.content_type),
+        By::Name => type_or_unknown(result.name_type),
+        By::Both => format!(
+          "{} (name: {}, {})",
+          type_or_unknown(result.content_type),
+          type_or_unknown(result.name_type),
+          result.verdict.map_or("", verdict_name)
+        ),
+      };
+      writeln!(out, "{}: {line}", result.path)?;
+    }
+    results.push(result);
+  }
+  if format == Format::Json {
+    write_json(out, &results)?;
+  }
+  Ok(status)
+}
+
+const fn verdict_name(kind: VerdictKind) -> &'static str {
+  match kind {
+    VerdictKind::Consistent => "consistent",
+    VerdictKind::Compatible => "compatible",
+    VerdictKind::Mismatched => "mismatched",
+    VerdictKind::Undecidable => "undecidable",
+  }
+}
+
+fn match_files(
+  out: &mut dyn Write,
+  format: Format,
+  db: &MimeDatabase,
+  mime_type: &str,
+  files: &[PathBuf],
+) -> io::Result<u8> {
+  let mut status = 0;
+  let mut results = Vec::new();
+  for path in files {
+    if !path.exists() {
+      eprintln!("tmagic: {}: cannot be read", path.display());
+      status = ERROR;
+      continue;
+    }
+    let matches = db.match_filepath(mime_type, path);
+    if !matches {
+      status = status.max(MISMATCH);
+    }
+    if format == Format::Line {
+      let not = if matches { "" } else { "not " };
+      writeln!(out, "{}: {not}{mime_type}", path.display())?;
+    }
+    results.push(FileMatch {
+      path: path.display().to_string(),
+      matches,
+    });
+  }
+  if format == Format::Json {
+    write_json(out, &results)?;
+  }
+  Ok(status)
+}
+
+/// All supertypes of a type, nearest first.
+fn supertypes<'a>(db: &'a MimeDatabase, mime_type: &str) -> Vec<&'a str> {
+  let mut supertypes = db.parents(mime_type);
+  let mut i = 0;
+  while let Some(&supertype) = supertypes.get(i) {
+    for parent in db.parents(supertype) {
+      if parent != mime_type && !supertypes.contains(&parent) {
+        supertypes.push(parent);
+      }
+    }
+    i += 1;
+  }
+  supertypes
+}
+
+fn explain(out: &mut dyn Write, format: Format, db: &MimeDatabase, path: &Path) -> io::Result<u8> {
+  let bytes = match read_head(path) {
+    Ok(bytes) => bytes,
+    Err(err) => {
+      eprintln!("tmagic: {}: {err}", path.display());
+      return Ok(ERROR);
+    },
+  };
+  let verdict = db.validate(path, &bytes);
+  let explanation = Explanation {
+    path: path.display().to_string(),
+    content_type: verdict.content_type,
+    magic_path: db.path_from_u8(&bytes),
+    globs: db.globs().match_filename(&file_name(path)),
+    name_type: verdict.name_type,
+    verdict: verdict.kind,
+    supertypes: supertypes(db, verdict.content_type),
+  };
+  match format {
+    Format::Line => {
+      writeln!(out, "{}: {}", explanation.path, explanation.content_type)?;
+      writeln!(out, "  magic: {}", explanation.magic_path.join(" > "))?;
+      for glob in &explanation.globs {
+        writeln!(
+          out,
+          "  glob: {} {} (weight {})",
+          glob.pattern, glob.mime_type, glob.weight
+        )?;
+      }
+      writeln!(out, "  name: {}", verdict_name(explanation.verdict))?;
+      writeln!(out, "  supertypes: {}", explanation.supertypes.join(", "))?;
+    },
+    Format::Json => write_json(out, &explanation)?,
+  }
+  Ok(u8::from(explanation.verdict == VerdictKind::Mismatched))
+}
+
+/// The supertypes or subtypes of a type, each with theirs.
+fn type_tree<'a>(
+  db: &'a MimeDatabase,
+  mime_type: &'a str,
+  relation: Relation,
+  seen: &mut Vec<&'a str>,
+) -> Vec<TypeTree<'a>> {
+  seen.push(mime_type);
+  let related = match relation {
+    Relation::Parents => db.parents(mime_type),
+    Relation::Children => db.children(mime_type),
+  };
+  let mut trees = Vec::new();
+  // Cycles in the subclasses are cut
+  for mime_type in related {
+    if seen.contains(&mime_type) {
+      continue;
+    }
+    let next = type_tree(db, mime_type, relation, seen);
+    trees.push(match relation {
+      Relation::Parents => TypeTree {
+        mime_type,
+        parents: next,
+        children: Vec::new(),
+      },
+      Relation::Children => TypeTree {
+        mime_type,
+        parents: Vec::new(),
+        children: next,
+      },
+    });
+  }
+  seen.pop();
+  trees
+}
+
+fn write_type_trees(out: &mut dyn Write, trees: &[TypeTree], depth: usize) -> io::Result<()> {
+  for tree in trees {
+    writeln!(out, "{:indent$}{}", "", tree.mime_type, indent = depth * 2)?;
+    write_type_trees(out, &tree.parents, depth + 1)?;
+    write_type_trees(out, &tree.children, depth + 1)?;
+  }
+  Ok(())
+}
+
+fn tree(out: &mut dyn Write, format: Format, db: &MimeDatabase, mime_type: &str) -> io::Result<u8> {
+  let tree = TypeTree {
+    mime_type,
+    parents: type_tree(db, mime_type, Relation::Parents, &mut Vec::new()),
+    children: type_tree(db, mime_type, Relation::Children, &mut Vec::new()),
+  };
+  match format {
+    Format::Line => {
+      writeln!(out, "{mime_type}")?;
+      for (title, trees) in [("supertypes", &tree.parents), ("subtypes", &tree.children)] {
+        if !trees.is_empty() {
+          writeln!(out, "  {title}:")?;
+          write_type_trees(out, trees, 2)?;
+        }
+      }
+    },
+    Format::Json => write_json(out, &tree)?,
+  }
+  Ok(0)
+}
+
+fn stats(out: &mut dyn Write, format: Format, sources: &[Source]) -> io::Result<u8> {
+  let total_globs: usize = sources.iter().map(|source| source.globs_num).sum();
+  let total = Source {
+    name: "total".to_string(),
+    load_result: sources.iter().map(|source| source.load_result).sum(),
+    globs_num: total_globs,
+  };
+  match format {
+    Format::Line => {
+      for source in sources.iter().chain([&total]) {
+        let LoadResult {
+          magic_rules_num,
+          aliases_num,
+          subclasses_num,
+        } = source.load_result;
+        writeln!(
+          out,
+          "{}: {magic_rules_num} magic rules, {aliases_num} aliases, \
+           {subclasses_num} subclasses, {} globs",
+          source.name, source.globs_num
+        )?;
+      }
+    },
+    Format::Json => {
+      #[derive(Serialize)]
+      struct Stats<'a> {
+        sources: &'a [Source],
+        total: Source,
+      }
+      write_json(out, &Stats { sources, total })?;
+    },
+  }
+  Ok(0)
+}
+
+fn dump(
+  out: &mut dyn Write,
+  format: Format,
+  provider: &OwnedBuildableDb,
+  globs: &GlobDatabase,
+) -> io::Result<u8> {
+  if format == Format::Json {
+    write_json(
+      out,
+      &Dump {
+        provider,
+        globs: globs.globs(),
+      },
+    )?;
+    return Ok(0);
+  }
+  for rule in provider.iter_magic_rules() {
+    write!(
+      out,
+      "magic {} {} {}>{}={}",
+      rule.priority(),
+      rule.mime_type(),
+      rule.indent_level(),
+      rule.start_off(),
+      rule.val().escape_ascii()
+    )?;
+    if !rule.mask().is_empty() {
+      write!(out, "&{}", rule.mask().escape_ascii())?;
+    }
+    if rule.word_len() != 1 {
+      write!(out, "~{}", rule.word_len())?;
+    }
+    if rule.region_len() != 0 {
+      write!(out, "+{}", rule.region_len())?;
+    }
+    writeln!(out)?;
+  }
+  for alias in provider.iter_aliases() {
+    writeln!(out, "alias {} {}", alias.alias(), alias.name())?;
+  }
+  for subclass in provider.iter_subclasses() {
+    writeln!(
+      out,
+      "subclass {} {}",
+      subclass.mime_type(),
+      subclass.parent()
+    )?;
+  }
+  for glob in globs.globs() {
+    let case_sensitive = if glob.case_sensitive {
+      " case-sensitive"
+    } else {
+      ""
+    };
+    writeln!(
+      out,
+      "glob {} {} {}{case_sensitive}",
+      glob.weight, glob.mime_type, glob.pattern
+    )?;
+  }
+  Ok(0)
+}
+
+fn lint(
+  out: &mut dyn Write,
+  format: Format,
+  provider: &OwnedBuildableDb,
+  globs: &GlobDatabase,
+) -> io::Result<u8> {
+  let issues = lint_db(provider, globs);
+  match format {
+    Format::Line => {
+      for issue in &issues {
+        writeln!(out, "{issue}")?;
+      }
+    },
+    Format::Json => write_json(out, &issues)?,
+  }
+  Ok(u8::from(!issues.is_empty()))
+}
@@ -0,0 +1,18 @@
+//! Compiles `MIME_DIR/packages/*.xml` into a shared MIME info database,
+//! like the `update-mime-database` tool of shared-mime-info.
+use std::{path::PathBuf, process::ExitCode};
+
+fn main() -> ExitCode {
+  let mut args = std::env::args_os().skip(1);
+  let (Some(mime_dir), None) = (args.next(), args.next()) else {
+    eprintln!("usage: update-mime-database-rs MIME_DIR");
+    return ExitCode::FAILURE;
+  };
+  match tree_magic_rs::db::update_mime_database(&PathBuf::from(mime_dir)) {
+    Ok(_) => ExitCode::SUCCESS,
+    Err(err) => {
+      eprintln!("update-mime-database-rs: {err}");
+      ExitCode::FAILURE
+    },
+  }
+}
@@ -0,0 +1,262 @@
+//! A cache of detection results keyed by file identity.
+use crate::{MimeDatabase, MIME};
+use lru::LruCache;
+use std::{
+  fs::{self, File, Metadata},
+  io::{self, BufRead, BufReader, BufWriter, Write},
+  num::NonZeroUsize,
+  path::Path,
+  sync::{Mutex, MutexGuard, PoisonError},
+  time::UNIX_EPOCH,
+};
+use thiserror::Error;
+
+const STORE_HEADER: &str = "tree_magic_rs detection cache 1";
+
+/// The errors that can occur while loading or saving a [`DetectionCache`].
+#[derive(Error, Debug)]
+pub enum DetectionCacheError {
+  /// The store could not be read or written
+  #[error("IO error")]
+  IoError(#[from] io::Error),
+  /// The store was not written by this version of the crate or is corrupted
+  #[error("Malformed detection cache at line {line}")]
+  Malformed {
+    /// The line number, starting at 1
+    line: usize,
+  },
+}
+
+/// Identifies a version of a file without opening it.
+///
+/// Platforms without inode numbers use a hash of the path instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct FileKey {
+  device: u64,
+  inode: u64,
+  size: u64,
+  /// Modification time in nanoseconds since the epoch
+  mtime: i128,
+}
+impl FileKey {
+  fn new(path: &Path, metadata: &Metadata) -> io::Result<Self> {
+    #[cfg(unix)]
+    let (device, inode) = {
+      use std::os::unix::fs::MetadataExt;
+      let _ = path;
+      (metadata.dev(), metadata.ino())
+    };
+    #[cfg(not(unix))]
+    let (device, inode) = {
+      use std::hash::{Hash, Hasher};
+      let mut hasher = fnv::FnvHasher::default();
+      path.hash(&mut hasher);
+      (0, hasher.finish())
+    };
+    let mtime = match metadata.modified()?.duration_since(UNIX_EPOCH) {
+      Ok(after) => i128::try_from(after.as_nanos()),
+      Err(before) => i128::try_from(before.duration().as_nanos()).map(|nanos| -nanos),
+    }
+    .unwrap_or(i128::MAX);
+    Ok(Self {
+      device,
+      inode,
+      size: metadata.len(),
+      mtime,
+    })
+  }
+
+  fn parse(line: &str) -> Option<(Self, &str)> {
+    let mut fields = line.splitn(5, ' ');
+    let key = Self {
+      device: fields.next()?.parse().ok()?,
+      inode: fields.next()?.parse().ok()?,
+      size: fields.next()?.parse().ok()?,
+      mtime: fields.next()?.parse().ok()?,
+    };
+    Some((key, fields.next()?))
+  }
+}
+
+struct CacheState {
+  /// The fingerprint of the database that detected the entries
+  fingerprint: Option<u64>,
+  entries: LruCache<FileKey, String>,
+}
+
+/// Remembers the types detected by [`MimeDatabase::from_filepath`],
+/// so files that did not change since are not opened again.
+///
+/// Files are identified by device, inode, size and modification time,
+/// of the links themselves if the database does not
+/// [follow them](MimeDatabase::with_follow_symlinks).
+/// The least recently used entries are dropped once the capacity is reached,
+/// and all entries are dropped when the cache is used with a database
+/// that has a different [`fingerprint`](MimeDatabase::fingerprint).
+///
+/// The cache can be shared between threads and stored on disk with [`save`](Self::save).
+///
+/// # Examples
+/// ```rust
+/// use std::{num::NonZeroUsize, path::Path};
+/// use tree_magic_rs::{db::OwnedBuildableDb, DetectionCache, MimeDatabase};
+///
+/// let db = MimeDatabase::from_provider(&OwnedBuildableDb::new());
+/// let cache = DetectionCache::new(NonZeroUsize::new(10_000).unwrap());
+/// let mime_type = cache.from_filepath(&db, Path::new("Cargo.toml"));
+/// assert_eq!(mime_type, db.from_filepath(Path::new("Cargo.toml")));
+/// assert_eq!(cache.len(), 1);
+/// ```
+pub struct DetectionCache {
+  state: Mutex<CacheState>,
+}
+impl DetectionCache {
+  /// Creates an empty cache that holds up to `capacity` entries.
+  #[must_use]
+  pub fn new(capacity: NonZeroUsize) -> Self {
+    Self {
+      state: Mutex::new(CacheState {
+        fingerprint: None,
+        entries: LruCache::new(capacity),
+      }),
+    }
+  }
+
+  /// Loads a cache written by [`save`](Self::save).
+  ///
+  /// A missing file yields an empty cache.
+  /// If the store holds more than `capacity` entries, the least recently used are dropped.
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be read or is malformed.
+  pub fn load(path: &Path, capacity: NonZeroUsize) -> Result<Self, DetectionCacheError> {
+    let cache = Self::new(capacity);
+    let file = match File::open(path) {
+      Ok(file) => file,
+      Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(cache),
+      Err(err) => return Err(err.into()),
+    };
+    let mut lines = BufReader::new(file).lines();
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let fingerprint = header
+      .strip_prefix(STORE_HEADER)
+      .and_then(|fingerprint| fingerprint.strip_prefix(' '))
+      .and_then(|fingerprint| u64::from_str_radix(fingerprint, 16).ok())
+      .ok_or(DetectionCacheError::Malformed { line: 1 })?;
+    {
+      let mut state = cache.lock();
+      state.fingerprint = Some(fingerprint);
+      // Entries are stored least recently used first
+      for (index, line) in lines.enumerate() {
+        let line = line?;
+        let (key, mime_type) =
+          FileKey::parse(&line).ok_or(DetectionCacheError::Malformed { line: index + 2 })?;
+        state.entries.put(key, mime_type.to_string());
+      }
+    }
+    Ok(cache)
+  }
+
+  /// Writes the cache to a file, to be read with [`load`](Self::load).
+  ///
+  /// The file is written to a temporary file first and then renamed.
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be written.
+  pub fn save(&self, path: &Path) -> Result<(), DetectionCacheError> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = Path::new(&tmp_path);
+    let result = File::create(tmp_path)
+      .and_then(|file| self.write(BufWriter::new(file)))
+      .and_then(|()| fs::rename(tmp_path, path));
+    if result.is_err() {
+      let _ = fs::remove_file(tmp_path);
+    }
+    Ok(result?)
+  }
+
+  fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+    let state = self.lock();
+    let fingerprint = state.fingerprint.unwrap_or_default();
+    writeln!(writer, "{STORE_HEADER} {fingerprint:016x}")?;
+    for (key, mime_type) in state.entries.iter().rev() {
+      writeln!(
+        writer,
+        "{} {} {} {} {mime_type}",
+        key.device, key.inode, key.size, key.mtime
+      )?;
+    }
+    drop(state);
+    writer.flush()
+  }
+
+  /// Gets the type of a file like [`MimeDatabase::from_filepath`],
+  /// without opening it if it was detected before and did not change since.
+  ///
+  /// Returns `None` if the file cannot be accessed.
+  pub fn from_filepath<'d>(&self, db: &'d MimeDatabase, path: &Path) -> Option<MIME<'d>> {
+    // Links that are not followed are detected as links, not as their targets
+    let metadata = if db.follow_symlinks() {
+      fs::metadata(path)
+    } else {
+      fs::symlink_metadata(path)
+    }
+    .ok()?;
+    let key = FileKey::new(path, &metadata).ok()?;
+    {
+      let mut state = self.lock();
+      if state.fingerprint != Some(db.fingerprint()) {
+        state.entries.clear();
+        state.fingerprint = Some(db.fingerprint());
+      }
+      if let Some(mime_type) = state
+        .entries
+        .get(&key)
+        .and_then(|mime_type| db.graph_type(mime_type))
+      {
+        return Some(mime_type);
+      }
+    }
+    // Detect without holding the lock, so other threads are not blocked
+    let mime_type = db.from_filepath(path)?;
+    let mut state = self.lock();
+    if state.fingerprint == Some(db.fingerprint()) {
+      state.entries.put(key, mime_type.to_string());
+    }
+    drop(state);
+    Some(mime_type)
+  }
+
+  /// The number of cached files
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.lock().entries.len()
+  }
+
+  /// Whether no files are cached
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Drops all entries.
+  pub fn clear(&self) {
+    self.lock().entries.clear();
+  }
+
+  fn lock(&self) -> MutexGuard<'_, CacheState> {
+    // The state is consistent after every operation, a panic cannot break it
+    self.state.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+}
+impl std::fmt::Debug for DetectionCache {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let state = self.lock();
+    f.debug_struct("DetectionCache")
+      .field("fingerprint", &state.fingerprint)
+      .field("len", &state.entries.len())
+      .field("cap", &state.entries.cap())
+      .finish()
+  }
+}
@@ -0,0 +1,146 @@
+//! Compiles a database into Rust code from a build script.
+//!
+//! The generated file defines a `static` [`StaticDbProvider`](crate::db::StaticDbProvider),
+//! so the database is available without any files or parsing at runtime.
+//!
+//! # Examples
+//! In `build.rs`:
+//! ```rust,no_run
+//! use std::path::{Path, PathBuf};
+//!
+//! let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+//! tree_magic_rs::codegen::generate_from_dir(
+//!   Path::new("mime"),
+//!   "EMBEDDED_DB",
+//!   &out_dir.join("embedded_db.rs"),
+//! )
+//! .unwrap();
+//! println!("cargo:rerun-if-changed=mime");
+//! ```
+//! In the crate:
+//! ```rust,ignore
+//! include!(concat!(env!("OUT_DIR"), "/embedded_db.rs"));
+//!
+//! let db = tree_magic_rs::MimeDatabase::from_provider(&EMBEDDED_DB);
+//! ```
+use crate::db::{
+  Alias, DbProvider, LoadResult, MagicRule, OwnedBuildableDb, SharedMimeDbProviderError,
+  SharedMimeDbProviderExt, Subclass,
+};
+use std::{
+  fs::File,
+  io::{self, BufWriter, Write},
+  path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// The errors that can occur while generating code.
+#[derive(Error, Debug)]
+pub enum CodegenError {
+  /// The output file could not be written
+  #[error("IO error")]
+  IoError(#[from] io::Error),
+  /// The directory contains no `magic` file
+  #[error("No magic file in {dir:?}")]
+  NotFound {
+    /// The directory that was searched
+    dir: PathBuf,
+  },
+  /// The database files could not be loaded
+  #[error("Error loading the database")]
+  LoadError(Vec<SharedMimeDbProviderError>),
+}
+
+/// Writes `bytes` as a byte string literal.
+fn write_byte_str(out: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+  out.write_all(b"b\"")?;
+  for &b in bytes {
+    match b {
+      b'"' | b'\\' => write!(out, "\\{}", b as char)?,
+      b' '..=b'~' => out.write_all(&[b])?,
+      _ => write!(out, "\\x{b:02x}")?,
+    }
+  }
+  out.write_all(b"\"")
+}
+
+/// Writes a `static` named `name` holding all entries of `provider`.
+///
+/// The generated code refers to this crate as `::tree_magic_rs`.
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn generate<'a, P, W>(provider: &'a P, name: &str, mut out: W) -> io::Result<()>
+where
+  P: DbProvider<'a> + ?Sized,
+  W: Write,
+{
+  writeln!(out, "// @generated by tree_magic_rs::codegen")?;
+  writeln!(
+    out,
+    "pub static {name}: ::tree_magic_rs::db::StaticDbProvider<'static> ="
+  )?;
+  writeln!(out, "  ::tree_magic_rs::db::StaticDbProvider::new(")?;
+  writeln!(out, "    &[")?;
+  for rule in provider.iter_magic_rules() {
+    write!(
+      out,
+      "      ::tree_magic_rs::db::BorrowedMagicRule::new({}, {:?}, {}, {}, ",
+      rule.priority(),
+      rule.mime_type(),
+      rule.indent_level(),
+      rule.start_off()
+    )?;
+    write_byte_str(&mut out, rule.val())?;
+    write!(out, ", ")?;
+    write_byte_str(&mut out, rule.mask())?;
+    writeln!(out, ", {}, {}),", rule.word_len(), rule.region_len())?;
+  }
+  writeln!(out, "    ],")?;
+  writeln!(out, "    &[")?;
+  for alias in provider.iter_aliases() {
+    writeln!(
+      out,
+      "      ::tree_magic_rs::db::BorrowedAlias::new({:?}, {:?}),",
+      alias.alias(),
+      alias.name()
+    )?;
+  }
+  writeln!(out, "    ],")?;
+  writeln!(out, "    &[")?;
+  for subclass in provider.iter_subclasses() {
+    writeln!(
+      out,
+      "      ::tree_magic_rs::db::BorrowedSubclass::new({:?}, {:?}),",
+      subclass.mime_type(),
+      subclass.parent()
+    )?;
+  }
+  writeln!(out, "    ],")?;
+  writeln!(out, "  );")?;
+  out.flush()
+}
+
+/// Loads the `magic`, `aliases` and `subclasses` files in `dir`
+/// and writes them to `out_file` as a `static` named `name`.
+///
+/// # Errors
+/// Returns an error if `dir` has no `magic` file, a file is malformed
+/// or `out_file` cannot be written.
+pub fn generate_from_dir(
+  dir: &Path,
+  name: &str,
+  out_file: &Path,
+) -> Result<LoadResult, CodegenError> {
+  if !dir.join("magic").is_file() {
+    return Err(CodegenError::NotFound {
+      dir: dir.to_owned(),
+    });
+  }
+  let mut db = OwnedBuildableDb::new();
+  let load_result = db
+    .load_from_xdg_shared_magic_dir(dir)
+    .map_err(|(_, errs)| CodegenError::LoadError(errs))?;
+  generate(&db, name, BufWriter::new(File::create(out_file)?))?;
+  Ok(load_result)
+}
@@ -1,17 +1,17 @@
-use std::marker::PhantomData;
-
 use crate::db::{Alias, MagicRule, Subclass};
 
 use super::BuildeableDbProvider;
 
-#[derive(Clone, Copy)]
+/// An [`Alias`] that borrows its strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BorrowedAlias<'a> {
   alias: &'a str,
   name: &'a str,
 }
 impl<'a> BorrowedAlias<'a> {
+  /// Creates an alias that maps `alias` to `name`.
   #[must_use]
-  pub fn new(alias: &'a str, name: &'a str) -> Self {
+  pub const fn new(alias: &'a str, name: &'a str) -> Self {
     Self { alias, name }
   }
 }
@@ -24,7 +24,8 @@ impl<'a> Alias for BorrowedAlias<'a> {
   }
 }
 
-#[derive(Clone, Copy)]
+/// A [`MagicRule`] that borrows its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BorrowedMagicRule<'a> {
   priority: u32,
   mime_type: &'a str,
@@ -36,8 +37,10 @@ pub struct BorrowedMagicRule<'a> {
   region_len: u32,
 }
 impl<'a> BorrowedMagicRule<'a> {
+  /// Creates a rule; an empty `mask` means no mask.
   #[must_use]
-  pub fn new(
+  #[allow(clippy::too_many_arguments)]
+  pub const fn new(
     priority: u32,
     mime_type: &'a str,
     indent_level: u32,
@@ -93,22 +96,28 @@ impl<'a> MagicRule for BorrowedMagicRule<'a> {
   }
 }
 
-// TODO: !
-#[derive(Clone, Copy)]
+/// A [`Subclass`] that borrows its strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BorrowedSubclass<'a> {
-  _data: PhantomData<&'a ()>,
+  mime_type: &'a str,
+  parent: &'a str,
 }
 impl<'a> BorrowedSubclass<'a> {
+  /// Declares `mime_type` as a subclass of `parent`.
   #[must_use]
-  pub fn new() -> Self {
-    Self { _data: PhantomData }
+  pub const fn new(mime_type: &'a str, parent: &'a str) -> Self {
+    Self { mime_type, parent }
   }
 }
 impl<'a> Subclass for BorrowedSubclass<'a> {
-  fn str(&self) -> &str {
-    todo!()
+  fn mime_type(&self) -> &str {
+    self.mime_type
+  }
+  fn parent(&self) -> &str {
+    self.parent
   }
 }
 
+/// A database that borrows all of its entries.
 pub type BorrowedBuildableDb<'a> =
   BuildeableDbProvider<BorrowedMagicRule<'a>, BorrowedAlias<'a>, BorrowedSubclass<'a>>;
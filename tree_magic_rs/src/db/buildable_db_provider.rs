@@ -1,5 +1,10 @@
 use super::DbProvider;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+/// A database whose entries can be edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BuildeableDbProvider<MagicRule, Alias, Subclass>
 where
   MagicRule: crate::db::MagicRule + Clone,
@@ -16,6 +21,7 @@ where
   Alias: crate::db::Alias + Clone,
   Subclass: crate::db::Subclass + Clone,
 {
+  /// Creates an empty database.
   #[must_use]
   pub fn new() -> Self {
     Self {
@@ -24,18 +30,22 @@ where
       subclasses: Vec::new(),
     }
   }
+  /// The magic rules, for adding or removing rules.
   #[must_use]
   pub fn magic_rules_mut(&mut self) -> &mut Vec<MagicRule> {
     &mut self.magic_rules
   }
+  /// The aliases, for adding or removing aliases.
   #[must_use]
   pub fn aliases_mut(&mut self) -> &mut Vec<Alias> {
     &mut self.aliases
   }
+  /// The subclasses, for adding or removing subclasses.
   #[must_use]
   pub fn subclasses_mut(&mut self) -> &mut Vec<Subclass> {
     &mut self.subclasses
   }
+  /// Removes all entries.
   pub fn clear(&mut self) {
     self.magic_rules.clear();
     self.aliases.clear();
@@ -0,0 +1,160 @@
+//! A small rule set covering the most common formats.
+//!
+//! The rules are written from the format specifications and are MIT licensed
+//! like the rest of this crate, so they can be embedded in any binary.
+//! MIME type names follow the shared MIME info database.
+use crate::db::{BorrowedAlias, BorrowedMagicRule, BorrowedSubclass, StaticDbProvider};
+
+const fn rule(
+  priority: u32,
+  mime_type: &'static str,
+  indent_level: u32,
+  start_off: u32,
+  val: &'static [u8],
+) -> BorrowedMagicRule<'static> {
+  BorrowedMagicRule::new(priority, mime_type, indent_level, start_off, val, &[], 1, 0)
+}
+
+const MAGIC_RULES: &[BorrowedMagicRule<'static>] = &[
+  // Images
+  rule(50, "image/png", 0, 0, b"\x89PNG\r\n\x1a\n"),
+  rule(50, "image/jpeg", 0, 0, b"\xff\xd8\xff"),
+  rule(50, "image/gif", 0, 0, b"GIF87a"),
+  rule(50, "image/gif", 0, 0, b"GIF89a"),
+  // "BM" followed by a known DIB header size
+  rule(50, "image/bmp", 0, 0, b"BM"),
+  rule(50, "image/bmp", 1, 14, b"\x0c\0\0\0"),
+  rule(50, "image/bmp", 1, 14, b"\x28\0\0\0"),
+  rule(50, "image/bmp", 1, 14, b"\x40\0\0\0"),
+  rule(50, "image/bmp", 1, 14, b"\x6c\0\0\0"),
+  rule(50, "image/bmp", 1, 14, b"\x7c\0\0\0"),
+  rule(50, "image/tiff", 0, 0, b"II*\0"),
+  rule(50, "image/tiff", 0, 0, b"MM\0*"),
+  rule(50, "image/webp", 0, 0, b"RIFF"),
+  rule(50, "image/webp", 1, 8, b"WEBP"),
+  rule(50, "image/avif", 0, 4, b"ftypavif"),
+  rule(50, "image/heif", 0, 4, b"ftypheic"),
+  rule(50, "image/heif", 0, 4, b"ftypmif1"),
+  rule(50, "image/vnd.adobe.photoshop", 0, 0, b"8BPS"),
+  // Documents
+  rule(50, "application/pdf", 0, 0, b"%PDF-"),
+  rule(50, "application/postscript", 0, 0, b"%!PS-Adobe-"),
+  rule(50, "application/rtf", 0, 0, b"{\\rtf"),
+  rule(40, "application/xml", 0, 0, b"<?xml"),
+  rule(
+    50,
+    "application/x-ole-storage",
+    0,
+    0,
+    b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1",
+  ),
+  rule(50, "application/vnd.sqlite3", 0, 0, b"SQLite format 3\0"),
+  // Archives and compression
+  rule(50, "application/zip", 0, 0, b"PK\x03\x04"),
+  rule(50, "application/zip", 0, 0, b"PK\x05\x06"),
+  rule(50, "application/gzip", 0, 0, b"\x1f\x8b"),
+  rule(50, "application/x-bzip", 0, 0, b"BZh"),
+  rule(50, "application/x-xz", 0, 0, b"\xfd7zXZ\0"),
+  rule(50, "application/zstd", 0, 0, b"\x28\xb5\x2f\xfd"),
+  rule(50, "application/x-lz4", 0, 0, b"\x04\x22\x4d\x18"),
+  rule(
+    50,
+    "application/x-7z-compressed",
+    0,
+    0,
+    b"7z\xbc\xaf\x27\x1c",
+  ),
+  rule(50, "application/vnd.rar", 0, 0, b"Rar!\x1a\x07"),
+  rule(50, "application/x-tar", 0, 257, b"ustar"),
+  rule(50, "application/x-archive", 0, 0, b"!<arch>\n"),
+  rule(
+    60,
+    "application/vnd.debian.binary-package",
+    0,
+    0,
+    b"!<arch>\ndebian",
+  ),
+  rule(50, "application/x-rpm", 0, 0, b"\xed\xab\xee\xdb"),
+  rule(50, "application/x-iso9660-image", 0, 32769, b"CD001"),
+  // Executables
+  rule(50, "application/x-executable", 0, 0, b"\x7fELF"),
+  rule(40, "application/x-ms-dos-executable", 0, 0, b"MZ"),
+  rule(50, "application/x-mach-binary", 0, 0, b"\xfe\xed\xfa\xce"),
+  rule(50, "application/x-mach-binary", 0, 0, b"\xfe\xed\xfa\xcf"),
+  rule(50, "application/x-mach-binary", 0, 0, b"\xce\xfa\xed\xfe"),
+  rule(50, "application/x-mach-binary", 0, 0, b"\xcf\xfa\xed\xfe"),
+  rule(50, "application/wasm", 0, 0, b"\0asm"),
+  // Audio
+  rule(50, "audio/mpeg", 0, 0, b"ID3"),
+  rule(50, "audio/mpeg", 0, 0, b"\xff\xfb"),
+  rule(50, "audio/mpeg", 0, 0, b"\xff\xf3"),
+  rule(50, "audio/mpeg", 0, 0, b"\xff\xf2"),
+  rule(50, "audio/flac", 0, 0, b"fLaC"),
+  rule(50, "audio/x-wav", 0, 0, b"RIFF"),
+  rule(50, "audio/x-wav", 1, 8, b"WAVE"),
+  rule(50, "audio/x-aiff", 0, 0, b"FORM"),
+  rule(50, "audio/x-aiff", 1, 8, b"AIFF"),
+  rule(50, "audio/midi", 0, 0, b"MThd"),
+  rule(50, "audio/mp4", 0, 4, b"ftypM4A "),
+  // Ogg pages, the codec is named in the first packet
+  rule(40, "application/ogg", 0, 0, b"OggS"),
+  rule(50, "audio/x-vorbis+ogg", 0, 0, b"OggS"),
+  rule(50, "audio/x-vorbis+ogg", 1, 28, b"\x01vorbis"),
+  rule(50, "audio/x-opus+ogg", 0, 0, b"OggS"),
+  rule(50, "audio/x-opus+ogg", 1, 28, b"OpusHead"),
+  rule(50, "audio/x-flac+ogg", 0, 0, b"OggS"),
+  rule(50, "audio/x-flac+ogg", 1, 28, b"\x7fFLAC"),
+  rule(50, "video/x-theora+ogg", 0, 0, b"OggS"),
+  rule(50, "video/x-theora+ogg", 1, 28, b"\x80theora"),
+  // Video
+  rule(50, "video/mp4", 0, 4, b"ftypisom"),
+  rule(50, "video/mp4", 0, 4, b"ftypiso2"),
+  rule(50, "video/mp4", 0, 4, b"ftypmp41"),
+  rule(50, "video/mp4", 0, 4, b"ftypmp42"),
+  rule(50, "video/quicktime", 0, 4, b"ftypqt  "),
+  rule(50, "video/x-matroska", 0, 0, b"\x1a\x45\xdf\xa3"),
+  rule(50, "video/x-msvideo", 0, 0, b"RIFF"),
+  rule(50, "video/x-msvideo", 1, 8, b"AVI "),
+  // Fonts
+  rule(50, "font/woff", 0, 0, b"wOFF"),
+  rule(50, "font/woff2", 0, 0, b"wOF2"),
+  rule(50, "font/otf", 0, 0, b"OTTO"),
+];
+
+const ALIASES: &[BorrowedAlias<'static>] = &[
+  BorrowedAlias::new("application/x-gzip", "application/gzip"),
+  BorrowedAlias::new("application/x-zip-compressed", "application/zip"),
+  BorrowedAlias::new("application/x-bzip2", "application/x-bzip"),
+  BorrowedAlias::new("application/x-rar", "application/vnd.rar"),
+  BorrowedAlias::new("application/x-sqlite3", "application/vnd.sqlite3"),
+  BorrowedAlias::new("application/x-pdf", "application/pdf"),
+  BorrowedAlias::new("application/x-ogg", "application/ogg"),
+  BorrowedAlias::new("text/xml", "application/xml"),
+  BorrowedAlias::new("audio/wav", "audio/x-wav"),
+  BorrowedAlias::new("audio/vnd.wave", "audio/x-wav"),
+  BorrowedAlias::new("audio/x-flac", "audio/flac"),
+  BorrowedAlias::new("audio/mp3", "audio/mpeg"),
+  BorrowedAlias::new("audio/vorbis", "audio/x-vorbis+ogg"),
+  BorrowedAlias::new("image/pjpeg", "image/jpeg"),
+];
+
+const SUBCLASSES: &[BorrowedSubclass<'static>] = &[
+  BorrowedSubclass::new("application/xml", "text/plain"),
+  BorrowedSubclass::new("application/rtf", "text/plain"),
+  BorrowedSubclass::new("application/postscript", "text/plain"),
+  BorrowedSubclass::new(
+    "application/vnd.debian.binary-package",
+    "application/x-archive",
+  ),
+  BorrowedSubclass::new("audio/x-vorbis+ogg", "application/ogg"),
+  BorrowedSubclass::new("audio/x-opus+ogg", "application/ogg"),
+  BorrowedSubclass::new("audio/x-flac+ogg", "application/ogg"),
+  BorrowedSubclass::new("video/x-theora+ogg", "application/ogg"),
+];
+
+/// The bundled rule set.
+///
+/// [`MimeDatabase::new`](crate::MimeDatabase::new) falls back to it
+/// if no shared MIME info database is installed.
+pub static BUNDLED_DB: StaticDbProvider<'static> =
+  StaticDbProvider::new(MAGIC_RULES, ALIASES, SUBCLASSES);
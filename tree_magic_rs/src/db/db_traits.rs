@@ -2,26 +2,37 @@
 use std::{collections::VecDeque, path::PathBuf};
 use thiserror::Error;
 
+/// A single line of a magic section.
+///
 /// https://specifications.freedesktop.org/shared-mime-info-spec/shared-mime-info-spec-0.21.html#s2_layout
 pub trait MagicRule {
+  /// The priority of the section this rule belongs to
   #[must_use]
   fn priority(&self) -> u32;
+  /// The MIME type of the section this rule belongs to
   #[must_use]
   fn mime_type(&self) -> &str;
+  /// Nesting level; rules with a higher level must match after their parent
   #[must_use]
   fn indent_level(&self) -> u32;
+  /// The offset into the file to start matching at
   #[must_use]
   fn start_off(&self) -> u32;
+  /// The bytes to match
   #[must_use]
   fn val(&self) -> &[u8];
+  /// The mask applied before comparing, empty if there is none
   #[must_use]
   fn mask(&self) -> &[u8];
+  /// The word size of the value for byte swapping
   #[must_use]
   fn word_len(&self) -> u32;
+  /// How many offsets after `start_off` the value may also start at
   #[must_use]
   fn region_len(&self) -> u32;
 }
 
+/// A line of the `aliases` file.
 pub trait Alias {
   /// The name to be mapped
   #[must_use]
@@ -31,26 +42,33 @@ pub trait Alias {
   fn name(&self) -> &str;
 }
 
+/// A line of the `subclasses` file.
 pub trait Subclass {
-  //FIXME: was: String No idea what this should be
+  /// The subclass
   #[must_use]
-  fn str(&self) -> &str;
+  fn mime_type(&self) -> &str;
+  /// The MIME type it is a subclass of
+  #[must_use]
+  fn parent(&self) -> &str;
 }
 
 // TODO: Create fast impls of MagicRule and Alias and Subclass and a FastDbProvider
 
 /// Database providers should implement this trait.
 pub trait DbProvider<'a> {
+  /// The magic rule type
   type MagicRule: MagicRule + ?Sized;
   /// All magic rules
   #[must_use]
   fn iter_magic_rules(&'a self) -> Box<dyn Iterator<Item = &Self::MagicRule> + 'a>;
+  /// The alias type
   type Alias: Alias + ?Sized;
   /// All aliases
   // fn aliases<'a>(&'a self) -> impl Iterator<Item = impl Alias + 'a>; NOT OBJECT SAFE!
   // fn foreach_alias<C: FnMut(impl Alias)>(&self, f: C);
   #[must_use]
   fn iter_aliases(&'a self) -> Box<dyn Iterator<Item = &Self::Alias> + 'a>;
+  /// The subclass type
   type Subclass: Subclass + ?Sized;
   /// Alls subclass files
   #[must_use]
@@ -0,0 +1,303 @@
+use super::parse_globs::{
+  parse_globs2, parse_mime_types, parse_nginx_types, GlobRule, GlobsParseError,
+  EXTENSION_MAP_WEIGHT,
+};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The errors that can occur while loading glob files.
+#[derive(Error, Debug)]
+pub enum GlobDatabaseError {
+  /// A file could not be read
+  #[error("IO error")]
+  IoError {
+    /// The file that could not be read
+    file_path: PathBuf,
+    /// The underlying error
+    source: std::io::Error,
+  },
+  /// A file is malformed
+  #[error("Error parsing glob file")]
+  GlobsParseError {
+    /// The malformed file
+    file_path: PathBuf,
+    /// The underlying error
+    source: GlobsParseError,
+  },
+}
+
+/// Matches a name against a case-sensitive glob pattern, like the patterns of [`GlobRule`]s.
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+  fnmatch(
+    &pattern.chars().collect::<Vec<_>>(),
+    &name.chars().collect::<Vec<_>>(),
+  )
+}
+
+/// Matches a name against a pattern with `*`, `?` and `[...]` wildcards.
+fn fnmatch(pattern: &[char], name: &[char]) -> bool {
+  let (mut p, mut n) = (0, 0);
+  // The pattern position after the last `*` and the name position it matched up to
+  let mut star = None;
+  while n < name.len() {
+    let step = match pattern.get(p) {
+      Some('*') => {
+        star = Some((p + 1, n));
+        p += 1;
+        continue;
+      },
+      Some('?') => Some(1),
+      Some('[') => match match_bracket(&pattern[p..], name[n]) {
+        Some((matched, len)) => matched.then_some(len),
+        // An unclosed bracket is a literal
+        None => (name[n] == '[').then_some(1),
+      },
+      Some(&c) => (c == name[n]).then_some(1),
+      None => None,
+    };
+    if let Some(len) = step {
+      p += len;
+      n += 1;
+    } else if let Some((star_p, star_n)) = star {
+      p = star_p;
+      n = star_n + 1;
+      star = Some((star_p, n));
+    } else {
+      return false;
+    }
+  }
+  pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches a character against a `[...]` set at the start of `pattern`.
+///
+/// Returns whether it matched and the length of the set, or `None` if it is not closed.
+fn match_bracket(pattern: &[char], c: char) -> Option<(bool, usize)> {
+  let mut i = 1;
+  let negate = matches!(pattern.get(i), Some('!' | '^'));
+  if negate {
+    i += 1;
+  }
+  let mut matched = false;
+  let mut first = true;
+  loop {
+    match *pattern.get(i)? {
+      ']' if !first => return Some((matched != negate, i + 1)),
+      lo => {
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&hi| hi != ']') {
+          matched |= (lo..=pattern[i + 2]).contains(&c);
+          i += 3;
+        } else {
+          matched |= lo == c;
+          i += 1;
+        }
+      },
+    }
+    first = false;
+  }
+}
+
+fn has_wildcards(pattern: &str) -> bool {
+  pattern.contains(['*', '?', '['])
+}
+
+/// File name globs of the shared MIME info database and other extension maps.
+///
+/// Matching follows the shared MIME info specification:
+/// the highest weight wins, then case-sensitive globs, then literal names over wildcards,
+/// then the longest pattern.
+#[derive(Debug, Clone, Default)]
+pub struct GlobDatabase {
+  globs: Vec<GlobRule>,
+}
+impl GlobDatabase {
+  /// Creates an empty glob database.
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { globs: Vec::new() }
+  }
+
+  /// The loaded globs, in loading order.
+  #[must_use]
+  pub fn globs(&self) -> &[GlobRule] {
+    &self.globs
+  }
+
+  /// Adds globs and returns how many were added.
+  ///
+  /// A `__NOGLOBS__` pattern removes the globs loaded before for its MIME type.
+  pub fn add_globs(&mut self, globs: impl IntoIterator<Item = GlobRule>) -> usize {
+    let mut added = 0;
+    for glob in globs {
+      if glob.pattern == "__NOGLOBS__" {
+        self.globs.retain(|g| g.mime_type != glob.mime_type);
+      } else {
+        self.globs.push(glob);
+        added += 1;
+      }
+    }
+    added
+  }
+
+  fn load_file(
+    &mut self,
+    file: &Path,
+    parse: impl FnOnce(&str) -> Result<Vec<GlobRule>, GlobsParseError>,
+  ) -> Result<usize, GlobDatabaseError> {
+    let text = std::fs::read_to_string(file).map_err(|source| GlobDatabaseError::IoError {
+      file_path: file.to_path_buf(),
+      source,
+    })?;
+    let globs = parse(&text).map_err(|source| GlobDatabaseError::GlobsParseError {
+      file_path: file.to_path_buf(),
+      source,
+    })?;
+    Ok(self.add_globs(globs))
+  }
+
+  /// Loads a shared MIME info `globs2` file.
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be read or is malformed.
+  pub fn load_globs2_file(&mut self, file: &Path) -> Result<usize, GlobDatabaseError> {
+    self.load_file(file, parse_globs2)
+  }
+
+  /// Loads the `globs2` files of the well known shared MIME info directories,
+  /// with the user directory loaded last so that it overrides the system ones.
+  ///
+  /// Missing files are skipped.
+  ///
+  /// # Errors
+  /// Returns the number of loaded globs and the errors of unreadable or malformed files.
+  pub fn load_xdg_globs_default(&mut self) -> Result<usize, (usize, Vec<GlobDatabaseError>)> {
+    let mut dirs = vec![
+      PathBuf::from("/usr/share/mime"),
+      PathBuf::from("/usr/local/share/mime"),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+      dirs.push(Path::new(&home).join(".local/share/mime"));
+    }
+    let mut errs = Vec::new();
+    let mut loaded = 0;
+    for dir in dirs {
+      let file = dir.join("globs2");
+      if !file.exists() {
+        continue;
+      }
+      match self.load_globs2_file(&file) {
+        Ok(num) => loaded += num,
+        Err(err) => errs.push(err),
+      }
+    }
+    if !errs.is_empty() {
+      return Err((loaded, errs));
+    }
+    Ok(loaded)
+  }
+
+  /// Loads an Apache `mime.types` file with [`EXTENSION_MAP_WEIGHT`].
+  ///
+  /// # Errors
+  /// Returns the first malformed line.
+  pub fn load_mime_types_str(&mut self, text: &str) -> Result<usize, GlobsParseError> {
+    Ok(self.add_globs(parse_mime_types(text, EXTENSION_MAP_WEIGHT)?))
+  }
+
+  /// Loads an Apache `mime.types` file with [`EXTENSION_MAP_WEIGHT`].
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be read or is malformed.
+  pub fn load_mime_types_file(&mut self, file: &Path) -> Result<usize, GlobDatabaseError> {
+    self.load_file(file, |text| parse_mime_types(text, EXTENSION_MAP_WEIGHT))
+  }
+
+  /// Loads an nginx `types` block with [`EXTENSION_MAP_WEIGHT`].
+  ///
+  /// # Errors
+  /// Returns the first malformed line.
+  pub fn load_nginx_types_str(&mut self, text: &str) -> Result<usize, GlobsParseError> {
+    Ok(self.add_globs(parse_nginx_types(text, EXTENSION_MAP_WEIGHT)?))
+  }
+
+  /// Loads an nginx `types` block with [`EXTENSION_MAP_WEIGHT`].
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be read or is malformed.
+  pub fn load_nginx_types_file(&mut self, file: &Path) -> Result<usize, GlobDatabaseError> {
+    self.load_file(file, |text| parse_nginx_types(text, EXTENSION_MAP_WEIGHT))
+  }
+
+  /// Returns the globs matching a file name, best match first
+  /// and only the best glob of each MIME type.
+  ///
+  /// Leading directories are ignored.
+  #[must_use]
+  pub fn match_filename(&self, file_name: &str) -> Vec<&GlobRule> {
+    let file_name = file_name.rsplit('/').next().unwrap_or(file_name);
+    let name: Vec<char> = file_name.chars().collect();
+    let folded: Vec<char> = file_name.to_lowercase().chars().collect();
+    let mut matches: Vec<&GlobRule> = self
+      .globs
+      .iter()
+      .filter(|glob| {
+        if glob.case_sensitive {
+          fnmatch(&glob.pattern.chars().collect::<Vec<_>>(), &name)
+        } else {
+          fnmatch(
+            &glob.pattern.to_lowercase().chars().collect::<Vec<_>>(),
+            &folded,
+          )
+        }
+      })
+      .collect();
+    matches.sort_by_key(|glob| {
+      (
+        std::cmp::Reverse(glob.weight),
+        !glob.case_sensitive,
+        has_wildcards(&glob.pattern),
+        std::cmp::Reverse(glob.pattern.len()),
+      )
+    });
+    let mut seen = Vec::<&str>::new();
+    matches.retain(|glob| {
+      let new = !seen.contains(&glob.mime_type.as_str());
+      seen.push(&glob.mime_type);
+      new
+    });
+    matches
+  }
+
+  /// Returns the MIME type of the best glob matching a file name.
+  #[must_use]
+  pub fn from_filename(&self, file_name: &str) -> Option<&str> {
+    self
+      .match_filename(file_name)
+      .first()
+      .map(|glob| glob.mime_type.as_str())
+  }
+
+  /// Returns the extensions of the `*.ext` globs of a MIME type, highest weight first.
+  ///
+  /// MIME types are compared case-insensitively.
+  #[must_use]
+  pub fn extensions_for(&self, mime_type: &str) -> Vec<&str> {
+    let mut extensions: Vec<(u32, &str)> = self
+      .globs
+      .iter()
+      .filter(|glob| glob.mime_type.eq_ignore_ascii_case(mime_type))
+      .filter_map(|glob| {
+        let extension = glob.pattern.strip_prefix("*.")?;
+        (!extension.is_empty() && !has_wildcards(extension)).then_some((glob.weight, extension))
+      })
+      .collect();
+    extensions.sort_by_key(|(weight, _)| std::cmp::Reverse(*weight));
+    let mut out = Vec::<&str>::new();
+    for (_, extension) in extensions {
+      if !out.iter().any(|e| e.eq_ignore_ascii_case(extension)) {
+        out.push(extension);
+      }
+    }
+    out
+  }
+}
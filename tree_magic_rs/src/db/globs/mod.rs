@@ -0,0 +1,9 @@
+mod parse_globs;
+pub use parse_globs::{
+  parse_globs2, parse_mime_types, parse_nginx_types, GlobRule, GlobsParseError,
+  EXTENSION_MAP_WEIGHT,
+};
+pub(in crate::db) use parse_globs::is_mime_type;
+
+mod glob_db;
+pub use glob_db::{glob_matches, GlobDatabase, GlobDatabaseError};
@@ -0,0 +1,172 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The weight of globs from extension maps like `mime.types`.
+///
+/// It is below the lowest weight of the shared MIME info database,
+/// so these globs only answer for names it does not know.
+pub const EXTENSION_MAP_WEIGHT: u32 = 5;
+
+/// The errors that can occur while parsing a glob source.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GlobsParseError {
+  /// A line is malformed
+  #[error("Error parsing line {line}")]
+  LineParseError {
+    /// The line number, starting at 1
+    line: usize,
+  },
+}
+
+/// A file name pattern and the MIME type it maps to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GlobRule {
+  /// The weight, higher weights win
+  pub weight: u32,
+  /// The MIME type
+  pub mime_type: String,
+  /// The pattern, with `*`, `?` and `[...]` wildcards
+  pub pattern: String,
+  /// Whether the pattern is matched case-sensitively
+  pub case_sensitive: bool,
+}
+impl GlobRule {
+  fn extension(weight: u32, mime_type: &str, extension: &str) -> Self {
+    Self {
+      weight,
+      mime_type: mime_type.to_string(),
+      pattern: format!("*.{extension}"),
+      case_sensitive: false,
+    }
+  }
+}
+
+pub(in crate::db) fn is_mime_type(word: &str) -> bool {
+  word
+    .split_once('/')
+    .is_some_and(|(media, sub)| !media.is_empty() && !sub.is_empty() && !sub.contains('/'))
+}
+
+/// Parses a shared MIME info `globs2` file of `weight:type:pattern[:flags]` lines.
+///
+/// # Errors
+/// Returns the first malformed line.
+pub fn parse_globs2(text: &str) -> Result<Vec<GlobRule>, GlobsParseError> {
+  let mut globs = Vec::new();
+  for (i, line) in text.lines().enumerate() {
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let error = || GlobsParseError::LineParseError { line: i + 1 };
+    let mut fields = line.split(':');
+    let (Some(weight), Some(mime_type), Some(pattern)) =
+      (fields.next(), fields.next(), fields.next())
+    else {
+      return Err(error());
+    };
+    let weight = weight.parse().map_err(|_| error())?;
+    if !is_mime_type(mime_type) || pattern.is_empty() {
+      return Err(error());
+    }
+    let case_sensitive = fields
+      .next()
+      .is_some_and(|flags| flags.split(',').any(|flag| flag == "cs"));
+    globs.push(GlobRule {
+      weight,
+      mime_type: mime_type.to_string(),
+      pattern: pattern.to_string(),
+      case_sensitive,
+    });
+  }
+  Ok(globs)
+}
+
+/// Parses an Apache `mime.types` file of `type ext...` lines into `*.ext` globs.
+///
+/// Types without extensions are skipped.
+///
+/// # Errors
+/// Returns the first line that does not start with a MIME type.
+pub fn parse_mime_types(text: &str, weight: u32) -> Result<Vec<GlobRule>, GlobsParseError> {
+  let mut globs = Vec::new();
+  for (i, line) in text.lines().enumerate() {
+    let line = line.split_once('#').map_or(line, |(line, _)| line);
+    let mut words = line.split_whitespace();
+    let Some(mime_type) = words.next() else {
+      continue;
+    };
+    if !is_mime_type(mime_type) {
+      return Err(GlobsParseError::LineParseError { line: i + 1 });
+    }
+    globs.extend(words.map(|extension| GlobRule::extension(weight, mime_type, extension)));
+  }
+  Ok(globs)
+}
+
+/// Splits an nginx configuration into words, `{`, `}` and `;`, with their line numbers.
+fn nginx_tokens(text: &str) -> Vec<(usize, &str)> {
+  let mut tokens = Vec::new();
+  for (i, line) in text.lines().enumerate() {
+    let line = line.split_once('#').map_or(line, |(line, _)| line);
+    let mut rest = line;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+      rest = &rest[start..];
+      let len = match rest.find(|c: char| c.is_whitespace() || matches!(c, '{' | '}' | ';')) {
+        Some(0) => 1,
+        Some(len) => len,
+        None => rest.len(),
+      };
+      let token = rest[..len].trim_matches(['"', '\'']);
+      tokens.push((i + 1, token));
+      rest = &rest[len..];
+    }
+  }
+  tokens
+}
+
+/// Parses an nginx `types { type ext...; }` block into `*.ext` globs.
+///
+/// The `types` block around the entries is optional, so files meant to be
+/// included into a block are read as well.
+///
+/// # Errors
+/// Returns the line of the first malformed entry or an unclosed block.
+pub fn parse_nginx_types(text: &str, weight: u32) -> Result<Vec<GlobRule>, GlobsParseError> {
+  let tokens = nginx_tokens(text);
+  let mut globs = Vec::new();
+  let mut in_block = false;
+  let mut pos = 0;
+  while let Some(&(line, token)) = tokens.get(pos) {
+    let error = GlobsParseError::LineParseError { line };
+    pos += 1;
+    match token {
+      "types" if !in_block && tokens.get(pos).is_some_and(|(_, t)| *t == "{") => {
+        in_block = true;
+        pos += 1;
+      },
+      "}" if in_block => in_block = false,
+      mime_type if is_mime_type(mime_type) => loop {
+        match tokens.get(pos) {
+          Some((_, ";")) => {
+            pos += 1;
+            break;
+          },
+          Some((_, "{" | "}")) | None => return Err(error),
+          Some((_, extension)) => {
+            globs.push(GlobRule::extension(weight, mime_type, extension));
+            pos += 1;
+          },
+        }
+      },
+      _ => return Err(error),
+    }
+  }
+  if in_block {
+    return Err(GlobsParseError::LineParseError {
+      line: text.lines().count(),
+    });
+  }
+  Ok(globs)
+}
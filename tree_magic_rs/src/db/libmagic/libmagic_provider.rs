@@ -0,0 +1,42 @@
+use super::{import_libmagic, SkippedLibmagicLine};
+use crate::db::{BuildeableDbProvider, LoadResult, OwnedAlias, OwnedMagicRule, OwnedSubclass};
+use std::path::Path;
+
+/// Loads magic rules from libmagic text magic files.
+pub trait LibmagicProviderExt {
+  /// Loads the translatable entries of a libmagic file, see [`import_libmagic`].
+  ///
+  /// Returns the number of loaded entries and the lines that could not be translated.
+  fn load_libmagic_str(&mut self, source: &str) -> (LoadResult, Vec<SkippedLibmagicLine>);
+  /// Loads the translatable entries of a libmagic file from the disk.
+  ///
+  /// Invalid UTF-8 is replaced, as only the annotations have to be text.
+  ///
+  /// # Errors
+  /// Returns an error if the file could not be read.
+  fn load_libmagic_file(
+    &mut self,
+    file_path: &Path,
+  ) -> std::io::Result<(LoadResult, Vec<SkippedLibmagicLine>)>;
+}
+impl LibmagicProviderExt for BuildeableDbProvider<OwnedMagicRule, OwnedAlias, OwnedSubclass> {
+  fn load_libmagic_str(&mut self, source: &str) -> (LoadResult, Vec<SkippedLibmagicLine>) {
+    let import = import_libmagic(source);
+    let load_result = LoadResult {
+      magic_rules_num: import.magic_rules.len(),
+      aliases_num: 0,
+      subclasses_num: import.subclasses.len(),
+    };
+    self.magic_rules_mut().extend(import.magic_rules);
+    self.subclasses_mut().extend(import.subclasses);
+    (load_result, import.skipped_lines)
+  }
+
+  fn load_libmagic_file(
+    &mut self,
+    file_path: &Path,
+  ) -> std::io::Result<(LoadResult, Vec<SkippedLibmagicLine>)> {
+    let source = std::fs::read(file_path)?;
+    Ok(self.load_libmagic_str(&String::from_utf8_lossy(&source)))
+  }
+}
@@ -0,0 +1,10 @@
+mod parse_libmagic;
+pub use parse_libmagic::{
+  import_libmagic, LibmagicImport, LibmagicSkipReason, SkippedLibmagicLine,
+};
+
+mod libmagic_provider;
+pub use libmagic_provider::LibmagicProviderExt;
+
+mod write_libmagic;
+pub use write_libmagic::{write_libmagic_file, LibmagicExportOptions};
@@ -0,0 +1,400 @@
+use crate::db::magic_values::{parse_number, parse_string_value, parse_u64};
+use crate::db::{OwnedMagicRule, OwnedSubclass};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Why a line of a libmagic file could not be translated.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LibmagicSkipReason {
+  /// The line is not an entry, a comment or an annotation
+  #[error("malformed line")]
+  Malformed,
+  /// The continuation level is deeper than the previous entry allows
+  #[error("continuation without a parent entry")]
+  MissingParent,
+  /// Indirect, relative and end relative offsets have no equivalent
+  #[error("unsupported offset {offset:?}")]
+  UnsupportedOffset {
+    /// The offset as written
+    offset: String,
+  },
+  /// The type or one of its flags has no equivalent
+  #[error("unsupported type {match_type:?}")]
+  UnsupportedType {
+    /// The type as written
+    match_type: String,
+  },
+  /// Only equality tests have an equivalent
+  #[error("unsupported test {test:?}")]
+  UnsupportedTest {
+    /// The test as written
+    test: String,
+  },
+  /// The test value could not be parsed or does not fit the type
+  #[error("invalid value {value:?}")]
+  InvalidValue {
+    /// The value as written
+    value: String,
+  },
+  /// A `!:mime` annotation whose entry or one of its parents could not be translated
+  #[error("the annotated entry depends on line {line}, which could not be translated")]
+  UntranslatedEntry {
+    /// The line of the first entry that could not be translated
+    line: usize,
+  },
+  /// A `!:mime` annotation whose entries match any file
+  #[error("the annotated entry matches any file")]
+  AlwaysMatches,
+}
+
+/// A line of a libmagic file that could not be translated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SkippedLibmagicLine {
+  /// The line number, starting at 1
+  pub line: usize,
+  /// Why the line was skipped
+  pub reason: LibmagicSkipReason,
+}
+
+/// The result of [`import_libmagic`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LibmagicImport {
+  /// The translated rules
+  pub magic_rules: Vec<OwnedMagicRule>,
+  /// Annotated entries nested below an entry annotated with another MIME type
+  /// are subclasses of it, so the more specific type wins
+  pub subclasses: Vec<OwnedSubclass>,
+  /// The lines that could not be translated
+  pub skipped_lines: Vec<SkippedLibmagicLine>,
+}
+
+/// A translated test, `None` in [`Entry`] for `x` which matches anything.
+#[derive(Debug, Clone)]
+struct Test {
+  start_off: u32,
+  val: Vec<u8>,
+  mask: Vec<u8>,
+  word_len: u32,
+  region_len: u32,
+}
+
+/// An entry on the current continuation path.
+struct Entry {
+  line: usize,
+  test: Result<Option<Test>, ()>,
+  mime_type: Option<String>,
+}
+
+/// Parses a number like `strtoull` with base 0, allowing a leading `-`.
+fn parse_signed(number: &str) -> Option<u64> {
+  number.strip_prefix('-').map_or_else(
+    || parse_u64(number),
+    |number| parse_u64(number).map(u64::wrapping_neg),
+  )
+}
+
+/// Encodes a number into `width` big or little endian bytes.
+///
+/// Negative numbers are accepted if they fit as signed values.
+fn encode_number(number: &str, width: usize, little_endian: bool) -> Option<Vec<u8>> {
+  let value = parse_signed(number)?;
+  if width < 8 {
+    let bits = width * 8;
+    let fits_unsigned = value >> bits == 0;
+    let fits_signed = number.starts_with('-') && value >> (bits - 1) == u64::MAX >> (bits - 1);
+    if !fits_unsigned && !fits_signed {
+      return None;
+    }
+  }
+  Some(if little_endian {
+    value.to_le_bytes()[..width].to_vec()
+  } else {
+    value.to_be_bytes()[8 - width..].to_vec()
+  })
+}
+
+/// Splits off the next whitespace separated field, keeping escaped whitespace.
+fn next_field(line: &str) -> (&str, &str) {
+  let line = line.trim_start_matches([' ', '\t']);
+  let mut escaped = false;
+  for (i, c) in line.char_indices() {
+    match c {
+      _ if escaped => escaped = false,
+      '\\' => escaped = true,
+      ' ' | '\t' => return (&line[..i], &line[i..]),
+      _ => {},
+    }
+  }
+  (line, "")
+}
+
+/// Translates a `string` or `search` test.
+fn translate_string(
+  match_type: &str,
+  test: &str,
+) -> Result<(Vec<u8>, Vec<u8>, u32), LibmagicSkipReason> {
+  let unsupported_type = || LibmagicSkipReason::UnsupportedType {
+    match_type: match_type.to_string(),
+  };
+  let mut flags = match_type.split('/');
+  let search = flags.next() == Some("search");
+  let mut region_len = 0;
+  let (mut lower, mut upper) = (false, false);
+  for flag in flags {
+    if search && region_len == 0 && flag.bytes().all(|b| b.is_ascii_digit()) {
+      region_len = flag.parse().map_err(|_| unsupported_type())?;
+      continue;
+    }
+    for c in flag.chars() {
+      match c {
+        'c' => lower = true,
+        'C' => upper = true,
+        // Hints for text and binary files do not change what matches
+        'b' | 't' | 'T' => {},
+        _ => return Err(unsupported_type()),
+      }
+    }
+  }
+  if search && region_len == 0 {
+    return Err(unsupported_type());
+  }
+  let test = match test.as_bytes().first() {
+    Some(b'=') => &test[1..],
+    Some(b'<' | b'>' | b'!') => {
+      return Err(LibmagicSkipReason::UnsupportedTest {
+        test: test.to_string(),
+      })
+    },
+    _ => test,
+  };
+  let mut val = parse_string_value(test).map_err(|_| LibmagicSkipReason::InvalidValue {
+    value: test.to_string(),
+  })?;
+  if val.is_empty() {
+    return Err(LibmagicSkipReason::InvalidValue {
+      value: test.to_string(),
+    });
+  }
+  // Case-insensitive letters are compared with the case bit masked out
+  let mut mask = vec![0xff; val.len()];
+  for (b, mask) in val.iter_mut().zip(&mut mask) {
+    if (lower && b.is_ascii_lowercase()) || (upper && b.is_ascii_uppercase()) {
+      *b &= 0xdf;
+      *mask = 0xdf;
+    }
+  }
+  if mask.iter().all(|&m| m == 0xff) {
+    mask.clear();
+  }
+  Ok((val, mask, region_len))
+}
+
+/// Translates a numeric test.
+fn translate_number(
+  match_type: &str,
+  test: &str,
+) -> Result<(Vec<u8>, Vec<u8>, u32), LibmagicSkipReason> {
+  let (base_type, mask) = match match_type.split_once('&') {
+    Some((base_type, mask)) => (base_type, Some(mask)),
+    None => (match_type, None),
+  };
+  let base_type = base_type.strip_prefix('u').unwrap_or(base_type);
+  // Native byte order values are stored big-endian with their word size
+  let (width, little_endian, word_len) = match base_type {
+    "byte" => (1, false, 1),
+    "short" => (2, false, 2),
+    "long" => (4, false, 4),
+    "quad" => (8, false, 8),
+    "beshort" => (2, false, 1),
+    "belong" => (4, false, 1),
+    "bequad" => (8, false, 1),
+    "leshort" => (2, true, 1),
+    "lelong" => (4, true, 1),
+    "lequad" => (8, true, 1),
+    _ => {
+      return Err(LibmagicSkipReason::UnsupportedType {
+        match_type: match_type.to_string(),
+      })
+    },
+  };
+  let value = match test.as_bytes().first() {
+    Some(b'=') => &test[1..],
+    Some(b'0'..=b'9' | b'-') => test,
+    _ => {
+      return Err(LibmagicSkipReason::UnsupportedTest {
+        test: test.to_string(),
+      })
+    },
+  };
+  let val =
+    encode_number(value, width, little_endian).ok_or_else(|| LibmagicSkipReason::InvalidValue {
+      value: value.to_string(),
+    })?;
+  let mask = match mask {
+    Some(mask) => encode_number(mask, width, little_endian).ok_or_else(|| {
+      LibmagicSkipReason::UnsupportedType {
+        match_type: match_type.to_string(),
+      }
+    })?,
+    None => Vec::new(),
+  };
+  // The value is compared with the masked file contents
+  let val = if mask.is_empty() {
+    val
+  } else {
+    val.iter().zip(&mask).map(|(v, m)| v & m).collect()
+  };
+  Ok((val, mask, word_len))
+}
+
+/// Translates the offset, type and test of an entry.
+fn translate_entry(entry: &str) -> Result<Option<Test>, LibmagicSkipReason> {
+  let (offset, rest) = next_field(entry);
+  let (match_type, rest) = next_field(rest);
+  let (test, _message) = next_field(rest);
+  if offset.is_empty() || match_type.is_empty() || test.is_empty() {
+    return Err(LibmagicSkipReason::Malformed);
+  }
+  let start_off = parse_number(offset).ok_or_else(|| LibmagicSkipReason::UnsupportedOffset {
+    offset: offset.to_string(),
+  })?;
+  if test == "x" {
+    return Ok(None);
+  }
+  let (val, mask, word_len, region_len) =
+    if match_type.starts_with("string") || match_type.starts_with("search") {
+      let base_type = match_type.split('/').next().unwrap_or_default();
+      if base_type != "string" && base_type != "search" {
+        return Err(LibmagicSkipReason::UnsupportedType {
+          match_type: match_type.to_string(),
+        });
+      }
+      let (val, mask, region_len) = translate_string(match_type, test)?;
+      (val, mask, 1, region_len)
+    } else {
+      let (val, mask, word_len) = translate_number(match_type, test)?;
+      (val, mask, word_len, 0)
+    };
+  Ok(Some(Test {
+    start_off,
+    val,
+    mask,
+    word_len,
+    region_len,
+  }))
+}
+
+/// Builds the rules of a `!:mime` annotation from the entries leading to it.
+fn annotate(
+  path: &mut [Entry],
+  mime_type: &str,
+  magic_rules: &mut Vec<OwnedMagicRule>,
+  subclasses: &mut Vec<OwnedSubclass>,
+) -> Result<(), LibmagicSkipReason> {
+  if let Some(entry) = path.iter().find(|entry| entry.test.is_err()) {
+    return Err(LibmagicSkipReason::UntranslatedEntry { line: entry.line });
+  }
+  let tests: Vec<_> = path
+    .iter()
+    .filter_map(|entry| entry.test.clone().ok().flatten())
+    .collect();
+  if tests.is_empty() {
+    return Err(LibmagicSkipReason::AlwaysMatches);
+  }
+  // More specific annotations win over their parents
+  let priority = 50 + u32::try_from(path.len() - 1).unwrap_or(u32::MAX).min(49);
+  for (indent_level, test) in (0..).zip(tests) {
+    magic_rules.push(OwnedMagicRule::new(
+      priority,
+      mime_type.to_string(),
+      indent_level,
+      test.start_off,
+      test.val.into(),
+      test.mask.into(),
+      test.word_len,
+      test.region_len,
+    ));
+  }
+  let (entry, parents) = path.split_last_mut().unwrap_or_else(|| unreachable!());
+  let parent_type = parents.iter().rev().find_map(|e| e.mime_type.as_deref());
+  if let Some(parent_type) = parent_type.filter(|parent_type| *parent_type != mime_type) {
+    let subclass = OwnedSubclass::new(mime_type.to_string(), parent_type.to_string());
+    if !subclasses.contains(&subclass) {
+      subclasses.push(subclass);
+    }
+  }
+  entry.mime_type = Some(mime_type.to_string());
+  Ok(())
+}
+
+/// Translates a libmagic text magic file, like the ones read by `file(1)`.
+///
+/// Entries with absolute offsets and equality tests of the `byte`, `short`, `long`, `quad`,
+/// `string` and `search/N` types and their endian variants are supported,
+/// including `&` masks and `string/c` case folding.
+/// Every entry annotated with `!:mime` becomes a chain of rules
+/// made of the entry and its parents, with priority 50 plus its continuation level.
+/// Nested annotations of another MIME type become subclasses of the outer one.
+///
+/// Lines that cannot be translated are reported with the reason.
+/// Entries without a `!:mime` annotation are ignored.
+#[must_use]
+pub fn import_libmagic(source: &str) -> LibmagicImport {
+  let mut import = LibmagicImport::default();
+  let mut path = Vec::<Entry>::new();
+  for (line_num, line) in (1..).zip(source.lines()) {
+    let line = line.trim_end_matches('\r');
+    let mut skip = |reason| {
+      import.skipped_lines.push(SkippedLibmagicLine {
+        line: line_num,
+        reason,
+      });
+    };
+    if line.trim().is_empty() || line.starts_with('#') {
+      continue;
+    }
+    if let Some(annotation) = line.strip_prefix("!:") {
+      let (key, rest) = next_field(annotation);
+      if key != "mime" {
+        continue;
+      }
+      let (mime_type, _) = next_field(rest);
+      if mime_type.is_empty() || path.is_empty() {
+        skip(LibmagicSkipReason::Malformed);
+      } else if let Err(reason) = annotate(
+        &mut path,
+        mime_type,
+        &mut import.magic_rules,
+        &mut import.subclasses,
+      ) {
+        skip(reason);
+      }
+      continue;
+    }
+    let entry = line.trim_start_matches('>');
+    let level = line.len() - entry.len();
+    if level > path.len() {
+      skip(LibmagicSkipReason::MissingParent);
+      while path.len() <= level {
+        path.push(Entry {
+          line: line_num,
+          test: Err(()),
+          mime_type: None,
+        });
+      }
+      continue;
+    }
+    path.truncate(level);
+    let test = translate_entry(entry).map_err(&mut skip);
+    path.push(Entry {
+      line: line_num,
+      test,
+      mime_type: None,
+    });
+  }
+  import
+}
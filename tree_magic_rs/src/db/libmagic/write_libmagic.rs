@@ -0,0 +1,250 @@
+use crate::db::{DbProvider, MagicRule};
+use std::{
+  collections::BTreeMap,
+  fmt::Write as _,
+  io::{self, Write},
+};
+
+fn invalid_input(msg: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Options for [`write_libmagic_file`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibmagicExportOptions {
+  /// File extensions by MIME type, written as `!:ext` annotations
+  pub extensions: BTreeMap<String, Vec<String>>,
+}
+impl LibmagicExportOptions {
+  /// Adds the extension of a `*.ext` glob pattern.
+  ///
+  /// Returns `false` if the pattern is not a plain extension.
+  pub fn add_glob(&mut self, mime_type: &str, pattern: &str) -> bool {
+    let Some(extension) = pattern.strip_prefix("*.") else {
+      return false;
+    };
+    if extension.is_empty() || extension.contains(['*', '?', '[', '/', ' ']) {
+      return false;
+    }
+    let extensions = self.extensions.entry(mime_type.to_string()).or_default();
+    if !extensions.iter().any(|e| e == extension) {
+      extensions.push(extension.to_string());
+    }
+    true
+  }
+
+  /// Adds the plain extension globs of parsed packages XML MIME types.
+  #[cfg(feature = "xml")]
+  pub fn add_package_globs(&mut self, mime_types: &[crate::db::PackageMimeType]) {
+    for mime in mime_types {
+      for glob in &mime.globs {
+        self.add_glob(&mime.mime_type, &glob.pattern);
+      }
+    }
+  }
+}
+
+/// Writes a string test value, escaping everything but printable ASCII.
+fn escape_string(val: &[u8]) -> String {
+  let mut out = String::with_capacity(val.len());
+  // A leading operator character would be read as the test operator
+  if matches!(
+    val.first(),
+    Some(b'<' | b'>' | b'=' | b'!' | b'&' | b'^' | b'~')
+  ) || val == b"x"
+  {
+    out.push('=');
+  }
+  for &b in val {
+    match b {
+      b'\\' => out.push_str("\\\\"),
+      b' ' => out.push_str("\\ "),
+      b'!'..=b'~' => out.push(char::from(b)),
+      _ => {
+        let _ = write!(out, "\\x{b:02x}");
+      },
+    }
+  }
+  out
+}
+
+fn hex(bytes: &[u8]) -> String {
+  let mut out = String::from("0x");
+  for b in bytes {
+    let _ = write!(out, "{b:02x}");
+  }
+  out
+}
+
+/// Checks whether a mask only masks out the case bit of upper case letters,
+/// which `string/c` expresses for the lower case letters of the pattern.
+fn case_insensitive(val: &[u8], mask: &[u8]) -> Option<Vec<u8>> {
+  let mut folded = false;
+  let mut pattern = Vec::with_capacity(val.len());
+  for (&b, &m) in val.iter().zip(mask) {
+    match m {
+      0xdf if b.is_ascii_uppercase() => {
+        folded = true;
+        pattern.push(b.to_ascii_lowercase());
+      },
+      0xff if !b.is_ascii_lowercase() => pattern.push(b),
+      _ => return None,
+    }
+  }
+  folded.then_some(pattern)
+}
+
+/// Translates a rule into one or more `(offset, type, test)` tests that all have to match.
+fn translate_rule(rule: &(impl MagicRule + ?Sized)) -> io::Result<Vec<(u32, String, String)>> {
+  let val = rule.val();
+  let mask = rule.mask();
+  let unsupported = |what: &str| {
+    invalid_input(format!(
+      "{what} of a {} rule cannot be expressed in libmagic",
+      rule.mime_type()
+    ))
+  };
+  if val.is_empty() {
+    return Err(unsupported("empty value"));
+  }
+  if !mask.is_empty() && mask.len() != val.len() {
+    return Err(unsupported("mask"));
+  }
+  let start_off = rule.start_off();
+  let string_type = |flag: &str| {
+    let match_type = if rule.region_len() == 0 {
+      "string".to_string()
+    } else {
+      format!("search/{}", rule.region_len())
+    };
+    match_type + flag
+  };
+
+  if rule.word_len() != 1 {
+    let match_type = match (rule.word_len(), val.len()) {
+      (2, 2) => "short",
+      (4, 4) => "long",
+      (8, 8) => "quad",
+      _ => return Err(unsupported("word size")),
+    };
+    if rule.region_len() != 0 {
+      return Err(unsupported("range"));
+    }
+    let masked = if mask.is_empty() {
+      match_type.to_string()
+    } else {
+      format!("{match_type}&{}", hex(mask))
+    };
+    return Ok(vec![(start_off, masked, hex(&masked_val(val, mask)))]);
+  }
+  if mask.is_empty() {
+    return Ok(vec![(start_off, string_type(""), escape_string(val))]);
+  }
+  if let Some(pattern) = case_insensitive(val, mask) {
+    return Ok(vec![(
+      start_off,
+      string_type("/c"),
+      escape_string(&pattern),
+    )]);
+  }
+  if rule.region_len() != 0 {
+    return Err(unsupported("masked range"));
+  }
+  // Other masks are split into big-endian numbers
+  let val = masked_val(val, mask);
+  let mut tests = Vec::new();
+  let mut pos = 0;
+  while pos < val.len() {
+    let (width, match_type) = match val.len() - pos {
+      8.. => (8, "bequad"),
+      4..=7 => (4, "belong"),
+      2..=3 => (2, "beshort"),
+      _ => (1, "byte"),
+    };
+    let offset = u32::try_from(pos)
+      .ok()
+      .and_then(|pos| start_off.checked_add(pos))
+      .ok_or_else(|| unsupported("offset"))?;
+    tests.push((
+      offset,
+      format!("{match_type}&{}", hex(&mask[pos..pos + width])),
+      hex(&val[pos..pos + width]),
+    ));
+    pos += width;
+  }
+  Ok(tests)
+}
+
+fn masked_val(val: &[u8], mask: &[u8]) -> Vec<u8> {
+  if mask.is_empty() {
+    return val.to_vec();
+  }
+  val.iter().zip(mask).map(|(v, m)| v & m).collect()
+}
+
+/// Writes the magic rules of a [`DbProvider`] as a libmagic text magic file
+/// that `file -m` can use.
+///
+/// Indent levels become `>` continuations, ranges `search/N` tests,
+/// and masks `&` operators or `string/c` for case-insensitive letters.
+/// Every rule without children is annotated with `!:mime`,
+/// and `!:ext` if `options` has extensions for the MIME type.
+/// Aliases and subclasses have no libmagic equivalent and are not written.
+///
+/// # Errors
+/// Returns an error if writing fails or a rule cannot be expressed,
+/// like a masked range or a native byte order value that is not a single word.
+pub fn write_libmagic_file<'a, P, W>(
+  provider: &'a P,
+  options: &LibmagicExportOptions,
+  mut out: W,
+) -> io::Result<()>
+where
+  P: DbProvider<'a> + ?Sized,
+  W: Write,
+{
+  let mut sections = BTreeMap::<_, Vec<_>>::new();
+  for rule in provider.iter_magic_rules() {
+    sections
+      .entry((std::cmp::Reverse(rule.priority()), rule.mime_type()))
+      .or_default()
+      .push(rule);
+  }
+  for ((priority, mime_type), rules) in sections {
+    if mime_type.contains(char::is_whitespace) {
+      return Err(invalid_input(format!("invalid MIME type {mime_type:?}")));
+    }
+    writeln!(out, "\n# {mime_type}, priority {}", priority.0)?;
+    // The continuation level after the last test of the rule at each indent level
+    let mut levels = Vec::<usize>::new();
+    for (i, rule) in rules.iter().enumerate() {
+      let indent_level = rule.indent_level() as usize;
+      if indent_level > levels.len() {
+        return Err(invalid_input(format!(
+          "a {mime_type} rule is indented without a parent rule"
+        )));
+      }
+      levels.truncate(indent_level);
+      let first_level = levels.last().copied().unwrap_or(0);
+      let tests = translate_rule(*rule)?;
+      for (level, (offset, match_type, test)) in (first_level..).zip(&tests) {
+        write!(out, "{}{offset}\t{match_type}\t{test}", ">".repeat(level))?;
+        if level == 0 {
+          write!(out, "\t{mime_type}")?;
+        }
+        writeln!(out)?;
+      }
+      levels.push(first_level + tests.len());
+      let is_leaf = rules
+        .get(i + 1)
+        .is_none_or(|next| next.indent_level() <= rule.indent_level());
+      if is_leaf {
+        writeln!(out, "!:mime\t{mime_type}")?;
+        if let Some(extensions) = options.extensions.get(mime_type) {
+          writeln!(out, "!:ext\t{}", extensions.join("/"))?;
+        }
+      }
+    }
+  }
+  out.flush()
+}
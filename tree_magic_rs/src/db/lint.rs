@@ -0,0 +1,319 @@
+//! Consistency checks for databases.
+use crate::db::{Alias, DbProvider, GlobDatabase, MagicRule, Subclass};
+use crate::MediaType;
+use fnv::{FnvHashMap, FnvHashSet};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A problem found by [`lint_db`].
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LintIssue {
+  /// A name that is not a media type without parameters
+  #[error("{mime_type:?} is not a valid MIME type")]
+  InvalidMimeType {
+    /// The name as written
+    mime_type: String,
+  },
+  /// An alias of a type that has no magic rules, subclasses or globs
+  #[error("alias {alias} maps to the unknown type {name}")]
+  UnknownAliasTarget {
+    /// The alias
+    alias: String,
+    /// The unknown type
+    name: String,
+  },
+  /// An alias of another alias, which is not resolved further
+  #[error("alias {alias} maps to the alias {name}")]
+  ChainedAlias {
+    /// The alias
+    alias: String,
+    /// The type it maps to, which is an alias itself
+    name: String,
+  },
+  /// An alias that also has magic rules or globs, which are never used
+  #[error("alias {alias} also has magic rules or globs")]
+  ShadowedAlias {
+    /// The alias
+    alias: String,
+  },
+  /// A type that is a direct or indirect subclass of itself
+  #[error("{mime_type} is a subclass of itself")]
+  SubclassCycle {
+    /// A type of the cycle
+    mime_type: String,
+  },
+  /// A magic rule with an empty value
+  #[error("magic rule of {mime_type} has an empty value")]
+  EmptyValue {
+    /// The type of the rule
+    mime_type: String,
+  },
+  /// A magic rule whose mask is not as long as its value
+  #[error("magic rule of {mime_type} has a {mask_len} byte mask for a {val_len} byte value")]
+  MaskLengthMismatch {
+    /// The type of the rule
+    mime_type: String,
+    /// The length of the value
+    val_len: usize,
+    /// The length of the mask
+    mask_len: usize,
+  },
+  /// A magic rule whose word size is not 1, 2 or 4 or does not divide its value
+  #[error("magic rule of {mime_type} has the invalid word size {word_len}")]
+  InvalidWordLength {
+    /// The type of the rule
+    mime_type: String,
+    /// The word size
+    word_len: u32,
+  },
+  /// A magic rule nested deeper than the rule before it allows
+  #[error("magic rule of {mime_type} at level {indent_level} has no parent rule")]
+  MissingParentRule {
+    /// The type of the rule
+    mime_type: String,
+    /// The nesting level of the rule
+    indent_level: u32,
+  },
+  /// A glob listed more than once for the same type
+  #[error("glob {pattern} of {mime_type} is listed more than once")]
+  DuplicateGlob {
+    /// The type of the glob
+    mime_type: String,
+    /// The pattern
+    pattern: String,
+  },
+}
+
+/// The entries of a database, indexed for the checks.
+struct Index<'a> {
+  aliases: FnvHashMap<&'a str, &'a str>,
+  parents: FnvHashMap<&'a str, Vec<&'a str>>,
+  magic_types: FnvHashSet<&'a str>,
+  glob_types: FnvHashSet<&'a str>,
+}
+impl Index<'_> {
+  /// Whether a type has magic rules, globs or subclass relations
+  fn is_known(&self, name: &str) -> bool {
+    self.magic_types.contains(name)
+      || self.glob_types.contains(name)
+      || self.parents.contains_key(name)
+      || self
+        .parents
+        .values()
+        .flatten()
+        .any(|&parent| parent == name)
+  }
+}
+
+/// Checks the entries of a database and its globs for mistakes
+/// that the loaders accept, like unknown alias targets or subclass cycles.
+///
+/// Each issue is reported once.
+#[must_use]
+pub fn lint_db<'a, P: DbProvider<'a> + ?Sized>(
+  provider: &'a P,
+  globs: &GlobDatabase,
+) -> Vec<LintIssue> {
+  let mut parents = FnvHashMap::<&str, Vec<&str>>::default();
+  for subclass in provider.iter_subclasses() {
+    parents
+      .entry(subclass.mime_type())
+      .or_default()
+      .push(subclass.parent());
+  }
+  let index = Index {
+    aliases: provider
+      .iter_aliases()
+      .map(|alias| (alias.alias(), alias.name()))
+      .collect(),
+    parents,
+    magic_types: provider
+      .iter_magic_rules()
+      .map(MagicRule::mime_type)
+      .collect(),
+    glob_types: globs
+      .globs()
+      .iter()
+      .map(|glob| glob.mime_type.as_str())
+      .collect(),
+  };
+
+  let mut issues = Issues::default();
+  lint_names(&index, &mut issues);
+  lint_aliases(&index, &mut issues);
+  lint_subclasses(&index, &mut issues);
+  lint_magic_rules(provider, &mut issues);
+  lint_globs(globs, &mut issues);
+  issues.list
+}
+
+/// The issues found so far, in the order they were found
+#[derive(Default)]
+struct Issues {
+  list: Vec<LintIssue>,
+  seen: FnvHashSet<LintIssue>,
+}
+
+fn report(issues: &mut Issues, issue: LintIssue) {
+  if issues.seen.insert(issue.clone()) {
+    issues.list.push(issue);
+  }
+}
+
+fn lint_names(index: &Index, issues: &mut Issues) {
+  let names =
+    index
+      .magic_types
+      .iter()
+      .chain(&index.glob_types)
+      .copied()
+      .chain(
+        index
+          .aliases
+          .iter()
+          .flat_map(|(&alias, &name)| [alias, name]),
+      )
+      .chain(index.parents.iter().flat_map(|(&mime_type, parents)| {
+        std::iter::once(mime_type).chain(parents.iter().copied())
+      }));
+  let mut invalid: Vec<&str> = names
+    .filter(|name| {
+      MediaType::parse(name).map_or(true, |media_type| media_type.params().count() > 0)
+    })
+    .collect();
+  invalid.sort_unstable();
+  for mime_type in invalid {
+    report(
+      issues,
+      LintIssue::InvalidMimeType {
+        mime_type: mime_type.to_string(),
+      },
+    );
+  }
+}
+
+fn lint_aliases(index: &Index, issues: &mut Issues) {
+  let mut aliases: Vec<_> = index.aliases.iter().collect();
+  aliases.sort_unstable();
+  for (&alias, &name) in aliases {
+    if index.aliases.contains_key(name) {
+      report(
+        issues,
+        LintIssue::ChainedAlias {
+          alias: alias.to_string(),
+          name: name.to_string(),
+        },
+      );
+    } else if !index.is_known(name) {
+      report(
+        issues,
+        LintIssue::UnknownAliasTarget {
+          alias: alias.to_string(),
+          name: name.to_string(),
+        },
+      );
+    }
+    if index.magic_types.contains(alias) || index.glob_types.contains(alias) {
+      report(
+        issues,
+        LintIssue::ShadowedAlias {
+          alias: alias.to_string(),
+        },
+      );
+    }
+  }
+}
+
+fn lint_subclasses(index: &Index, issues: &mut Issues) {
+  let mut subclasses: Vec<&str> = index.parents.keys().copied().collect();
+  subclasses.sort_unstable();
+  for mime_type in subclasses {
+    let mut seen = FnvHashSet::default();
+    let mut stack = index.parents[mime_type].clone();
+    while let Some(parent) = stack.pop() {
+      if parent == mime_type {
+        report(
+          issues,
+          LintIssue::SubclassCycle {
+            mime_type: mime_type.to_string(),
+          },
+        );
+        break;
+      }
+      if seen.insert(parent) {
+        stack.extend(index.parents.get(parent).into_iter().flatten());
+      }
+    }
+  }
+}
+
+fn lint_magic_rules<'a, P: DbProvider<'a> + ?Sized>(provider: &'a P, issues: &mut Issues) {
+  let mut previous: Option<(&str, u32, u32)> = None;
+  for rule in provider.iter_magic_rules() {
+    let mime_type = rule.mime_type();
+    let (val_len, mask_len, word_len) = (rule.val().len(), rule.mask().len(), rule.word_len());
+    if val_len == 0 {
+      report(
+        issues,
+        LintIssue::EmptyValue {
+          mime_type: mime_type.to_string(),
+        },
+      );
+    }
+    if mask_len != 0 && mask_len != val_len {
+      report(
+        issues,
+        LintIssue::MaskLengthMismatch {
+          mime_type: mime_type.to_string(),
+          val_len,
+          mask_len,
+        },
+      );
+    }
+    if ![1, 2, 4].contains(&word_len) || val_len % word_len as usize != 0 {
+      report(
+        issues,
+        LintIssue::InvalidWordLength {
+          mime_type: mime_type.to_string(),
+          word_len,
+        },
+      );
+    }
+    // Rules of the same section follow each other, a section starts at level 0
+    let max_indent_level = match previous {
+      Some((previous_type, priority, indent_level))
+        if previous_type == mime_type && priority == rule.priority() =>
+      {
+        indent_level + 1
+      },
+      _ => 0,
+    };
+    if rule.indent_level() > max_indent_level {
+      report(
+        issues,
+        LintIssue::MissingParentRule {
+          mime_type: mime_type.to_string(),
+          indent_level: rule.indent_level(),
+        },
+      );
+    }
+    previous = Some((mime_type, rule.priority(), rule.indent_level()));
+  }
+}
+
+fn lint_globs(globs: &GlobDatabase, issues: &mut Issues) {
+  let mut seen = FnvHashSet::default();
+  for glob in globs.globs() {
+    if !seen.insert((&glob.mime_type, &glob.pattern, glob.case_sensitive)) {
+      report(
+        issues,
+        LintIssue::DuplicateGlob {
+          mime_type: glob.mime_type.clone(),
+          pattern: glob.pattern.clone(),
+        },
+      );
+    }
+  }
+}
@@ -0,0 +1,72 @@
+//! Decoding of the numbers and strings in magic rules, shared by the rule file formats.
+/// Parses an integer like C's `strtoull` with base 0.
+pub(in crate::db) fn parse_u64(value: &str) -> Option<u64> {
+  let value = value.trim();
+  match value.as_bytes() {
+    [b'0', b'x' | b'X', ..] => u64::from_str_radix(&value[2..], 16).ok(),
+    [b'0', _, ..] => u64::from_str_radix(&value[1..], 8).ok(),
+    _ => value.parse().ok(),
+  }
+}
+
+/// Parses an integer like C's `strtoul` with base 0.
+pub(in crate::db) fn parse_number(value: &str) -> Option<u32> {
+  parse_u64(value).and_then(|number| u32::try_from(number).ok())
+}
+
+/// Unescapes a string match value, with the C escapes, octal and `\x` hex bytes.
+pub(in crate::db) fn parse_string_value(value: &str) -> Result<Vec<u8>, String> {
+  let mut out = Vec::with_capacity(value.len());
+  let mut bytes = value.bytes().peekable();
+  while let Some(b) = bytes.next() {
+    if b != b'\\' {
+      out.push(b);
+      continue;
+    }
+    let Some(escaped) = bytes.next() else {
+      return Err("trailing backslash".to_string());
+    };
+    match escaped {
+      b'x' => {
+        let mut byte = 0u8;
+        let mut digits = 0;
+        while let Some(digit) = bytes.peek().and_then(|b| (*b as char).to_digit(16)) {
+          if digits == 2 {
+            break;
+          }
+          #[allow(clippy::cast_possible_truncation)]
+          let digit = digit as u8;
+          byte = byte << 4 | digit;
+          digits += 1;
+          bytes.next();
+        }
+        if digits == 0 {
+          return Err("missing hex digits after \\x".to_string());
+        }
+        out.push(byte);
+      },
+      b'0'..=b'7' => {
+        let mut byte = u32::from(escaped - b'0');
+        for _ in 0..2 {
+          match bytes.peek() {
+            Some(digit @ b'0'..=b'7') => {
+              byte = byte * 8 + u32::from(digit - b'0');
+              bytes.next();
+            },
+            _ => break,
+          }
+        }
+        out.push(u8::try_from(byte).map_err(|_| "octal escape out of range".to_string())?);
+      },
+      b'a' => out.push(0x07),
+      b'b' => out.push(0x08),
+      b'f' => out.push(0x0c),
+      b'n' => out.push(b'\n'),
+      b'r' => out.push(b'\r'),
+      b't' => out.push(b'\t'),
+      b'v' => out.push(0x0b),
+      other => out.push(other),
+    }
+  }
+  Ok(out)
+}
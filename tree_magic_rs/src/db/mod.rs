@@ -1,3 +1,4 @@
+//! Magic rules, aliases and subclasses and the databases that provide them.
 mod db_traits;
 pub use db_traits::{Alias, DbProvider, MagicRule, Subclass};
 
@@ -15,5 +16,63 @@ pub use buildable_db_provider::BuildeableDbProvider;
 mod stacked_db_provider;
 pub use stacked_db_provider::StackedDbProvider;
 
+mod static_db_provider;
+pub use static_db_provider::StaticDbProvider;
+
+#[cfg(feature = "bundled")]
+mod bundled;
+#[cfg(feature = "bundled")]
+pub use bundled::BUNDLED_DB;
+
 mod shared_mime_db;
-pub use shared_mime_db::SharedMimeDbProviderExt;
+pub use shared_mime_db::{
+  write_aliases_file, write_magic_file, write_subclasses_file, write_xdg_shared_magic_dir,
+  LoadResult, MagicRuleParseError, SharedMimeDbProviderError, SharedMimeDbProviderExt,
+};
+
+mod snapshot;
+pub use snapshot::{
+  read_snapshot, write_snapshot, write_snapshot_file, Snapshot, SnapshotError, SNAPSHOT_VERSION,
+};
+pub(crate) use snapshot::provider_checksum;
+
+mod globs;
+pub use globs::{
+  parse_globs2, parse_mime_types, parse_nginx_types, GlobDatabase, GlobDatabaseError, GlobRule,
+  GlobsParseError, EXTENSION_MAP_WEIGHT,
+};
+pub(crate) use globs::glob_matches;
+
+mod xml_namespaces;
+pub use xml_namespaces::{
+  parse_xml_namespaces, XmlNamespaceDatabase, XmlNamespaceDatabaseError, XmlNamespaceRule,
+  XmlNamespacesParseError,
+};
+
+mod magic_values;
+
+mod libmagic;
+pub use libmagic::{
+  import_libmagic, write_libmagic_file, LibmagicExportOptions, LibmagicImport, LibmagicProviderExt,
+  LibmagicSkipReason, SkippedLibmagicLine,
+};
+
+mod lint;
+pub use lint::{lint_db, LintIssue};
+
+#[cfg(feature = "xml")]
+mod packages_xml;
+#[cfg(feature = "xml")]
+pub use packages_xml::{
+  merge_package_mime_types, parse_packages_xml, update_mime_database, write_mime_database,
+  PackageComment, PackageGlob, PackageMimeType, PackageRootXml, PackagesXmlError,
+  PackagesXmlProviderError, PackagesXmlProviderExt,
+};
+
+#[cfg(feature = "xml")]
+mod tika;
+#[cfg(feature = "xml")]
+pub use tika::{
+  parse_tika_mimetypes, TikaImport, TikaMimeType, TikaMimetypesError, TikaProviderError,
+  TikaProviderExt, TikaUnsupported,
+};
@@ -1,19 +1,27 @@
-use std::marker::PhantomData;
-
-use crate::db::{Alias, MagicRule, Subclass};
+use crate::db::{Alias, DbProvider, MagicRule, Subclass};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use super::BuildeableDbProvider;
 
-#[derive(Clone)]
+/// An [`Alias`] that owns its strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OwnedAlias {
   alias: String,
   name: String,
 }
 impl OwnedAlias {
+  /// Creates an alias that maps `alias` to `name`.
   #[must_use]
-  pub fn new(alias: String, name: String) -> Self {
+  pub const fn new(alias: String, name: String) -> Self {
     Self { alias, name }
   }
+  /// Copies any [`Alias`].
+  #[must_use]
+  pub fn from_alias(alias: &(impl Alias + ?Sized)) -> Self {
+    Self::new(alias.alias().to_string(), alias.name().to_string())
+  }
 }
 impl Alias for OwnedAlias {
   fn alias(&self) -> &str {
@@ -24,7 +32,9 @@ impl Alias for OwnedAlias {
   }
 }
 
-#[derive(Clone)]
+/// A [`MagicRule`] that owns its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OwnedMagicRule {
   priority: u32,
   mime_type: String,
@@ -36,7 +46,9 @@ pub struct OwnedMagicRule {
   region_len: u32,
 }
 impl OwnedMagicRule {
+  /// Creates a rule; an empty `mask` means no mask.
   #[must_use]
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     priority: u32,
     mime_type: String,
@@ -58,6 +70,20 @@ impl OwnedMagicRule {
       region_len,
     }
   }
+  /// Copies any [`MagicRule`].
+  #[must_use]
+  pub fn from_rule(rule: &(impl MagicRule + ?Sized)) -> Self {
+    Self::new(
+      rule.priority(),
+      rule.mime_type().to_string(),
+      rule.indent_level(),
+      rule.start_off(),
+      rule.val().into(),
+      rule.mask().into(),
+      rule.word_len(),
+      rule.region_len(),
+    )
+  }
 }
 impl MagicRule for OwnedMagicRule {
   fn priority(&self) -> u32 {
@@ -93,21 +119,50 @@ impl MagicRule for OwnedMagicRule {
   }
 }
 
-// TODO: !
-#[derive(Clone, Copy)]
+/// A [`Subclass`] that owns its strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OwnedSubclass {
-  _data: u8,
+  mime_type: String,
+  parent: String,
 }
 impl OwnedSubclass {
+  /// Declares `mime_type` as a subclass of `parent`.
+  #[must_use]
+  pub const fn new(mime_type: String, parent: String) -> Self {
+    Self { mime_type, parent }
+  }
+  /// Copies any [`Subclass`].
   #[must_use]
-  pub fn new() -> Self {
-    Self { _data: 1 }
+  pub fn from_subclass(subclass: &(impl Subclass + ?Sized)) -> Self {
+    Self::new(
+      subclass.mime_type().to_string(),
+      subclass.parent().to_string(),
+    )
   }
 }
 impl Subclass for OwnedSubclass {
-  fn str(&self) -> &str {
-    todo!()
+  fn mime_type(&self) -> &str {
+    &self.mime_type
+  }
+  fn parent(&self) -> &str {
+    &self.parent
   }
 }
 
+/// A database that owns all of its entries.
 pub type OwnedBuildableDb = BuildeableDbProvider<OwnedMagicRule, OwnedAlias, OwnedSubclass>;
+impl OwnedBuildableDb {
+  /// Copies all entries of any [`DbProvider`].
+  #[must_use]
+  pub fn from_provider<'a, P: DbProvider<'a> + ?Sized>(provider: &'a P) -> Self {
+    let mut db = Self::new();
+    db.magic_rules_mut()
+      .extend(provider.iter_magic_rules().map(OwnedMagicRule::from_rule));
+    db.aliases_mut()
+      .extend(provider.iter_aliases().map(OwnedAlias::from_alias));
+    db.subclasses_mut()
+      .extend(provider.iter_subclasses().map(OwnedSubclass::from_subclass));
+    db
+  }
+}
@@ -0,0 +1,16 @@
+mod parse_packages_xml;
+pub(super) use parse_packages_xml::{
+  is_plain_mime_type, is_plain_root_xml, parse_offset, parse_string_mask, translate_match,
+};
+pub use parse_packages_xml::{
+  parse_packages_xml, PackageComment, PackageGlob, PackageMimeType, PackageRootXml,
+  PackagesXmlError,
+};
+
+mod packages_xml_provider;
+pub use packages_xml_provider::{PackagesXmlProviderError, PackagesXmlProviderExt};
+
+mod update_mime_database;
+pub use update_mime_database::{
+  merge_package_mime_types, update_mime_database, write_mime_database,
+};
@@ -0,0 +1,160 @@
+use super::{parse_packages_xml, PackageMimeType, PackagesXmlError};
+use crate::db::{
+  BuildeableDbProvider, LoadResult, MagicRule, OwnedAlias, OwnedMagicRule, OwnedSubclass,
+};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The errors that can occur while loading packages XML files.
+#[derive(Error, Debug)]
+pub enum PackagesXmlProviderError {
+  /// A file could not be read
+  #[error("IO error")]
+  IoError {
+    /// The file that could not be read
+    file_path: PathBuf,
+    /// The underlying error
+    source: std::io::Error,
+  },
+  /// A file is malformed
+  #[error("Error parsing packages XML file")]
+  PackagesXmlError {
+    /// The malformed file
+    file_path: PathBuf,
+    /// The underlying error
+    source: PackagesXmlError,
+  },
+  /// A MIME type cannot be written into a database
+  #[error("Invalid MIME type {mime_type:?}: {reason}")]
+  InvalidMimeType {
+    /// The MIME type
+    mime_type: String,
+    /// What is wrong with it
+    reason: String,
+  },
+}
+
+/// Loads the database from freedesktop packages XML files,
+/// the source format of the shared MIME info database.
+pub trait PackagesXmlProviderExt {
+  /// Adds the magic rules, aliases and subclasses of parsed MIME types.
+  ///
+  /// `<magic-deleteall>` discards the magic rules loaded before for its MIME type.
+  fn add_package_mime_types(&mut self, mime_types: &[PackageMimeType]) -> LoadResult;
+  /// Attempts to load a packages XML document.
+  ///
+  /// # Errors
+  /// Returns an error if the document is malformed, in which case nothing is loaded.
+  fn load_packages_xml_str(&mut self, xml: &str) -> Result<LoadResult, PackagesXmlError>;
+  /// Attempts to load a packages XML file.
+  ///
+  /// # Errors
+  /// Returns an error if the file could not be read or is malformed.
+  fn load_packages_xml_file(&mut self, file: &Path)
+    -> Result<LoadResult, PackagesXmlProviderError>;
+  /// Attempts to load all `*.xml` files of a directory like `/usr/share/mime/packages`
+  /// in alphabetical order, so later files can override earlier ones.
+  ///
+  /// # Errors
+  /// Returns the entries loaded so far and all errors
+  /// if the directory could not be read or there was an error loading a file.
+  fn load_packages_dir(
+    &mut self,
+    dir: &Path,
+  ) -> Result<LoadResult, (LoadResult, Vec<PackagesXmlProviderError>)>;
+}
+impl PackagesXmlProviderExt for BuildeableDbProvider<OwnedMagicRule, OwnedAlias, OwnedSubclass> {
+  fn add_package_mime_types(&mut self, mime_types: &[PackageMimeType]) -> LoadResult {
+    let mut load_result = LoadResult {
+      magic_rules_num: 0,
+      aliases_num: 0,
+      subclasses_num: 0,
+    };
+    for mime in mime_types {
+      if mime.magic_deleteall {
+        self
+          .magic_rules_mut()
+          .retain(|rule| rule.mime_type() != mime.mime_type);
+      }
+      self
+        .magic_rules_mut()
+        .extend(mime.magic_rules.iter().cloned());
+      self.aliases_mut().extend(
+        mime
+          .aliases
+          .iter()
+          .map(|alias| OwnedAlias::new(alias.clone(), mime.mime_type.clone())),
+      );
+      self.subclasses_mut().extend(
+        mime
+          .sub_class_of
+          .iter()
+          .map(|parent| OwnedSubclass::new(mime.mime_type.clone(), parent.clone())),
+      );
+      load_result = load_result
+        + LoadResult {
+          magic_rules_num: mime.magic_rules.len(),
+          aliases_num: mime.aliases.len(),
+          subclasses_num: mime.sub_class_of.len(),
+        };
+    }
+    load_result
+  }
+
+  fn load_packages_xml_str(&mut self, xml: &str) -> Result<LoadResult, PackagesXmlError> {
+    Ok(self.add_package_mime_types(&parse_packages_xml(xml)?))
+  }
+
+  fn load_packages_xml_file(
+    &mut self,
+    file_path: &Path,
+  ) -> Result<LoadResult, PackagesXmlProviderError> {
+    let xml =
+      std::fs::read_to_string(file_path).map_err(|source| PackagesXmlProviderError::IoError {
+        file_path: file_path.to_owned(),
+        source,
+      })?;
+    self
+      .load_packages_xml_str(&xml)
+      .map_err(|source| PackagesXmlProviderError::PackagesXmlError {
+        file_path: file_path.to_owned(),
+        source,
+      })
+  }
+
+  fn load_packages_dir(
+    &mut self,
+    dir: &Path,
+  ) -> Result<LoadResult, (LoadResult, Vec<PackagesXmlProviderError>)> {
+    let mut load_result = LoadResult {
+      magic_rules_num: 0,
+      aliases_num: 0,
+      subclasses_num: 0,
+    };
+    let io_error = |source| PackagesXmlProviderError::IoError {
+      file_path: dir.to_owned(),
+      source,
+    };
+    let mut files = std::fs::read_dir(dir)
+      .and_then(|entries| {
+        entries
+          .map(|entry| entry.map(|entry| entry.path()))
+          .collect::<Result<Vec<_>, _>>()
+      })
+      .map_err(|err| (load_result, vec![io_error(err)]))?;
+    files.retain(|path| path.extension().is_some_and(|ext| ext == "xml"));
+    files.sort();
+
+    let mut errs = Vec::new();
+    for file in files {
+      match self.load_packages_xml_file(&file) {
+        Ok(loaded) => load_result = load_result + loaded,
+        Err(err) => errs.push(err),
+      }
+    }
+    if !errs.is_empty() {
+      return Err((load_result, errs));
+    }
+    Ok(load_result)
+  }
+}
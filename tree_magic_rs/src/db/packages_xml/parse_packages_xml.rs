@@ -0,0 +1,354 @@
+use crate::db::magic_values::{parse_number, parse_string_value};
+use crate::db::OwnedMagicRule;
+use crate::MediaType;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The errors that can occur while parsing a packages XML file.
+#[derive(Error, Debug)]
+pub enum PackagesXmlError {
+  /// The file is not well-formed XML
+  #[error("XML error")]
+  XmlError(#[from] roxmltree::Error),
+  /// An element of a MIME type is invalid
+  #[error("Invalid <{element}> in {mime_type}: {reason}")]
+  InvalidElement {
+    /// The MIME type the element belongs to
+    mime_type: String,
+    /// The name of the element
+    element: String,
+    /// What is wrong with the element
+    reason: String,
+  },
+}
+
+/// A `<comment>`, the human readable name of a MIME type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PackageComment {
+  /// The language from `xml:lang`, `None` for the default English comment
+  pub lang: Option<String>,
+  /// The comment
+  pub text: String,
+}
+
+/// A `<glob>` matching file names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PackageGlob {
+  /// The pattern, e.g. `*.png`
+  pub pattern: String,
+  /// The weight, 50 by default
+  pub weight: u32,
+  /// Whether the pattern is matched case-sensitively
+  pub case_sensitive: bool,
+}
+
+/// A `<root-XML>` identifying XML documents by their root element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PackageRootXml {
+  /// The namespace of the root element, empty for none
+  pub namespace_uri: String,
+  /// The local name of the root element
+  pub local_name: String,
+}
+
+/// A `<mime-type>` of a packages XML file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PackageMimeType {
+  /// The MIME type
+  pub mime_type: String,
+  /// The comments in all languages
+  pub comments: Vec<PackageComment>,
+  /// The `<acronym>`, e.g. `PNG`
+  pub acronym: Option<String>,
+  /// The `<expanded-acronym>`, e.g. `Portable Network Graphics`
+  pub expanded_acronym: Option<String>,
+  /// The `<icon>` name
+  pub icon: Option<String>,
+  /// The `<generic-icon>` name
+  pub generic_icon: Option<String>,
+  /// Whether globs of previously loaded files are discarded
+  pub glob_deleteall: bool,
+  /// The globs
+  pub globs: Vec<PackageGlob>,
+  /// Whether magic rules of previously loaded files are discarded
+  pub magic_deleteall: bool,
+  /// The `<magic>` sections translated into magic rules
+  pub magic_rules: Vec<OwnedMagicRule>,
+  /// The root elements
+  pub root_xml: Vec<PackageRootXml>,
+  /// Other names of this MIME type
+  pub aliases: Vec<String>,
+  /// The MIME types this is a subclass of
+  pub sub_class_of: Vec<String>,
+}
+
+/// Parses a `string` mask, given in hex as `0x...`.
+pub(in crate::db) fn parse_string_mask(mask: &str, len: usize) -> Result<Vec<u8>, String> {
+  let hex = mask
+    .strip_prefix("0x")
+    .or_else(|| mask.strip_prefix("0X"))
+    .ok_or_else(|| format!("mask {mask:?} is not hexadecimal"))?;
+  // Slicing by byte offsets needs ASCII
+  if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+    return Err(format!("mask {mask:?} is not hexadecimal"));
+  }
+  if hex.len() != len * 2 {
+    return Err(format!("mask {mask:?} does not match the value length"));
+  }
+  (0..len)
+    .map(|i| {
+      u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+        .map_err(|_| format!("mask {mask:?} is not hexadecimal"))
+    })
+    .collect()
+}
+
+/// Whether a MIME type is a media type without parameters,
+/// which is safe to use as a file path and a field of the database files.
+pub(in crate::db) fn is_plain_mime_type(mime_type: &str) -> bool {
+  MediaType::parse(mime_type).is_ok_and(|media_type| media_type.params().next().is_none())
+    && mime_type.matches('/').count() == 1
+    && !mime_type.contains("..")
+    && !mime_type.contains(char::is_whitespace)
+}
+
+/// Whether a root element can be written as a line of an `XMLnamespaces` file.
+pub(in crate::db) fn is_plain_root_xml(root: &PackageRootXml) -> bool {
+  !root.local_name.is_empty()
+    && !root.local_name.contains(char::is_whitespace)
+    && !root.namespace_uri.contains(char::is_whitespace)
+}
+
+#[derive(Clone, Copy)]
+enum Endian {
+  Big,
+  Little,
+}
+
+/// Encodes a numeric match value of `width` bytes.
+fn encode_number(value: &str, width: usize, endian: Endian) -> Result<Vec<u8>, String> {
+  let number = parse_number(value).ok_or_else(|| format!("{value:?} is not a number"))?;
+  if width < 4 && number >> (width * 8) != 0 {
+    return Err(format!("{value:?} does not fit into {width} bytes"));
+  }
+  let bytes = match endian {
+    Endian::Big => number.to_be_bytes()[4 - width..].to_vec(),
+    Endian::Little => number.to_le_bytes()[..width].to_vec(),
+  };
+  Ok(bytes)
+}
+
+/// Translates a typed `<match>` into value, mask and word size.
+///
+/// Host byte order values are stored big-endian with their word size,
+/// as in the `magic` file.
+pub(in crate::db) fn translate_match(
+  match_type: &str,
+  value: &str,
+  mask: Option<&str>,
+) -> Result<(Vec<u8>, Vec<u8>, u32), String> {
+  let (width, endian, word_len) = match match_type {
+    "string" => {
+      let val = parse_string_value(value)?;
+      let mask = match mask {
+        Some(mask) => parse_string_mask(mask, val.len())?,
+        None => Vec::new(),
+      };
+      return Ok((val, mask, 1));
+    },
+    "byte" => (1, Endian::Big, 1),
+    "big16" => (2, Endian::Big, 1),
+    "big32" => (4, Endian::Big, 1),
+    "little16" => (2, Endian::Little, 1),
+    "little32" => (4, Endian::Little, 1),
+    "host16" => (2, Endian::Big, 2),
+    "host32" => (4, Endian::Big, 4),
+    other => return Err(format!("unknown match type {other:?}")),
+  };
+  let val = encode_number(value, width, endian)?;
+  let mask = match mask {
+    Some(mask) => encode_number(mask, width, endian)?,
+    None => Vec::new(),
+  };
+  Ok((val, mask, word_len))
+}
+
+/// Parses `offset` as `start` or `start:end` into the start offset and region length.
+///
+/// The value may start at any offset from `start` to `end`, inclusive.
+pub(in crate::db) fn parse_offset(offset: &str) -> Result<(u32, u32), String> {
+  let invalid = || format!("invalid offset {offset:?}");
+  let (start, end) = match offset.split_once(':') {
+    Some((start, end)) => (start, Some(end)),
+    None => (offset, None),
+  };
+  let start = parse_number(start).ok_or_else(invalid)?;
+  let region_len = match end {
+    Some(end) => parse_number(end)
+      .and_then(|end| end.checked_sub(start))
+      .ok_or_else(invalid)?,
+    None => 0,
+  };
+  Ok((start, region_len))
+}
+
+struct MimeTypeParser<'a> {
+  mime_type: &'a str,
+}
+impl MimeTypeParser<'_> {
+  fn error(&self, element: &str, reason: String) -> PackagesXmlError {
+    PackagesXmlError::InvalidElement {
+      mime_type: self.mime_type.to_string(),
+      element: element.to_string(),
+      reason,
+    }
+  }
+
+  fn attribute<'n>(
+    &self,
+    node: roxmltree::Node<'n, '_>,
+    name: &str,
+  ) -> Result<&'n str, PackagesXmlError> {
+    node
+      .attribute(name)
+      .ok_or_else(|| self.error(node.tag_name().name(), format!("missing {name} attribute")))
+  }
+
+  /// The `type` attribute of an `<alias>` or `<sub-class-of>`.
+  fn type_attribute(&self, node: roxmltree::Node<'_, '_>) -> Result<String, PackagesXmlError> {
+    let mime_type = self.attribute(node, "type")?;
+    if !is_plain_mime_type(mime_type) {
+      return Err(self.error(
+        node.tag_name().name(),
+        format!("invalid type {mime_type:?}"),
+      ));
+    }
+    Ok(mime_type.to_string())
+  }
+
+  /// Appends the rules of a `<match>` and its children in depth-first order.
+  fn parse_match(
+    &self,
+    node: roxmltree::Node<'_, '_>,
+    priority: u32,
+    indent_level: u32,
+    magic_rules: &mut Vec<OwnedMagicRule>,
+  ) -> Result<(), PackagesXmlError> {
+    let (start_off, region_len) = parse_offset(self.attribute(node, "offset")?)
+      .map_err(|reason| self.error("match", reason))?;
+    let (val, mask, word_len) = translate_match(
+      self.attribute(node, "type")?,
+      self.attribute(node, "value")?,
+      node.attribute("mask"),
+    )
+    .map_err(|reason| self.error("match", reason))?;
+    magic_rules.push(OwnedMagicRule::new(
+      priority,
+      self.mime_type.to_string(),
+      indent_level,
+      start_off,
+      val.into(),
+      mask.into(),
+      word_len,
+      region_len,
+    ));
+    for child in node.children().filter(|n| n.has_tag_name("match")) {
+      self.parse_match(child, priority, indent_level + 1, magic_rules)?;
+    }
+    Ok(())
+  }
+
+  fn parse(&self, node: roxmltree::Node<'_, '_>) -> Result<PackageMimeType, PackagesXmlError> {
+    let mut mime = PackageMimeType {
+      mime_type: self.mime_type.to_string(),
+      ..PackageMimeType::default()
+    };
+    for child in node.children().filter(roxmltree::Node::is_element) {
+      match child.tag_name().name() {
+        "comment" => mime.comments.push(PackageComment {
+          lang: child
+            .attribute((roxmltree::NS_XML_URI, "lang"))
+            .map(str::to_string),
+          text: child.text().unwrap_or("").to_string(),
+        }),
+        "acronym" => mime.acronym = Some(child.text().unwrap_or("").to_string()),
+        "expanded-acronym" => {
+          mime.expanded_acronym = Some(child.text().unwrap_or("").to_string());
+        },
+        "icon" => mime.icon = Some(self.attribute(child, "name")?.to_string()),
+        "generic-icon" => mime.generic_icon = Some(self.attribute(child, "name")?.to_string()),
+        "glob-deleteall" => mime.glob_deleteall = true,
+        "glob" => mime.globs.push(PackageGlob {
+          pattern: self.attribute(child, "pattern")?.to_string(),
+          weight: match child.attribute("weight") {
+            Some(weight) => parse_number(weight)
+              .ok_or_else(|| self.error("glob", format!("invalid weight {weight:?}")))?,
+            None => 50,
+          },
+          case_sensitive: child.attribute("case-sensitive") == Some("true"),
+        }),
+        "magic-deleteall" => mime.magic_deleteall = true,
+        "magic" => {
+          let priority = match child.attribute("priority") {
+            Some(priority) => parse_number(priority)
+              .ok_or_else(|| self.error("magic", format!("invalid priority {priority:?}")))?,
+            None => 50,
+          };
+          for match_node in child.children().filter(|n| n.has_tag_name("match")) {
+            self.parse_match(match_node, priority, 0, &mut mime.magic_rules)?;
+          }
+        },
+        "root-XML" => {
+          let root = PackageRootXml {
+            namespace_uri: self.attribute(child, "namespaceURI")?.to_string(),
+            local_name: self.attribute(child, "localName")?.to_string(),
+          };
+          if !is_plain_root_xml(&root) {
+            return Err(self.error("root-XML", "invalid namespace or local name".to_string()));
+          }
+          mime.root_xml.push(root);
+        },
+        "alias" => mime.aliases.push(self.type_attribute(child)?),
+        "sub-class-of" => mime.sub_class_of.push(self.type_attribute(child)?),
+        // treemagic and unknown extensions
+        _ => {},
+      }
+    }
+    Ok(mime)
+  }
+}
+
+/// Parses the `<mime-type>`s of a packages XML file.
+///
+/// # Errors
+/// Returns an error if the XML is malformed or an element cannot be translated.
+pub fn parse_packages_xml(xml: &str) -> Result<Vec<PackageMimeType>, PackagesXmlError> {
+  let doc = roxmltree::Document::parse_with_options(
+    xml,
+    roxmltree::ParsingOptions {
+      allow_dtd: true,
+      ..roxmltree::ParsingOptions::default()
+    },
+  )?;
+  doc
+    .root_element()
+    .children()
+    .filter(|n| n.has_tag_name("mime-type"))
+    .map(|node| {
+      let mime_type = node.attribute("type").unwrap_or("");
+      let parser = MimeTypeParser { mime_type };
+      if mime_type.is_empty() {
+        return Err(parser.error("mime-type", "missing type attribute".to_string()));
+      }
+      if !is_plain_mime_type(mime_type) {
+        return Err(parser.error("mime-type", "invalid type attribute".to_string()));
+      }
+      parser.parse(node)
+    })
+    .collect()
+}
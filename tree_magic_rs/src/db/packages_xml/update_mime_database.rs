@@ -0,0 +1,389 @@
+use super::{
+  is_plain_mime_type, is_plain_root_xml, parse_packages_xml, PackageMimeType,
+  PackagesXmlProviderError, PackagesXmlProviderExt,
+};
+use crate::db::{write_aliases_file, write_magic_file, write_subclasses_file, OwnedBuildableDb};
+use std::{
+  collections::BTreeMap,
+  fs::File,
+  io::{self, BufWriter, Write},
+  path::{Path, PathBuf},
+};
+
+const GENERATED_HEADER: &str =
+  "# This file was automatically generated by the\n# update-mime-database command. DO NOT EDIT!\n";
+
+fn push_unique(list: &mut Vec<String>, items: Vec<String>) {
+  for item in items {
+    if !list.contains(&item) {
+      list.push(item);
+    }
+  }
+}
+
+/// Merges a later definition of a MIME type into an earlier one.
+fn merge_into(merged: &mut PackageMimeType, later: PackageMimeType) {
+  for comment in later.comments {
+    merged.comments.retain(|c| c.lang != comment.lang);
+    merged.comments.push(comment);
+  }
+  if later.acronym.is_some() {
+    merged.acronym = later.acronym;
+  }
+  if later.expanded_acronym.is_some() {
+    merged.expanded_acronym = later.expanded_acronym;
+  }
+  if later.icon.is_some() {
+    merged.icon = later.icon;
+  }
+  if later.generic_icon.is_some() {
+    merged.generic_icon = later.generic_icon;
+  }
+  if later.glob_deleteall {
+    merged.globs.clear();
+    merged.glob_deleteall = true;
+  }
+  for glob in later.globs {
+    merged.globs.retain(|g| g.pattern != glob.pattern);
+    merged.globs.push(glob);
+  }
+  if later.magic_deleteall {
+    merged.magic_rules.clear();
+    merged.magic_deleteall = true;
+  }
+  merged.magic_rules.extend(later.magic_rules);
+  for root_xml in later.root_xml {
+    if !merged.root_xml.contains(&root_xml) {
+      merged.root_xml.push(root_xml);
+    }
+  }
+  push_unique(&mut merged.aliases, later.aliases);
+  push_unique(&mut merged.sub_class_of, later.sub_class_of);
+}
+
+/// Merges the definitions of MIME types from several packages XML files,
+/// later definitions overriding earlier ones.
+///
+/// Globs and magic rules are added to earlier ones,
+/// unless the later definition has `<glob-deleteall>` or `<magic-deleteall>`.
+/// Comments, icons and acronyms are replaced.
+/// The result is sorted by MIME type.
+pub fn merge_package_mime_types(
+  mime_types: impl IntoIterator<Item = PackageMimeType>,
+) -> Vec<PackageMimeType> {
+  let mut merged = BTreeMap::<String, PackageMimeType>::new();
+  for mime in mime_types {
+    match merged.get_mut(&mime.mime_type) {
+      Some(earlier) => merge_into(earlier, mime),
+      None => {
+        merged.insert(mime.mime_type.clone(), mime);
+      },
+    }
+  }
+  merged.into_values().collect()
+}
+
+/// The `*.xml` files of a packages directory in the order they are applied.
+///
+/// `Override.xml` is applied last, the other files in alphabetical order.
+fn package_files(packages_dir: &Path) -> io::Result<Vec<PathBuf>> {
+  let mut files = std::fs::read_dir(packages_dir)?
+    .map(|entry| entry.map(|entry| entry.path()))
+    .collect::<io::Result<Vec<_>>>()?;
+  files.retain(|path| path.extension().is_some_and(|ext| ext == "xml"));
+  files.sort_by_key(|path| {
+    (
+      path.file_name().is_some_and(|n| n == "Override.xml"),
+      path.clone(),
+    )
+  });
+  Ok(files)
+}
+
+/// Writes a file by writing to a temporary file and renaming it,
+/// so readers never see a partially written file.
+fn write_file(
+  path: &Path,
+  write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> Result<(), PackagesXmlProviderError> {
+  let mut tmp_path = path.as_os_str().to_owned();
+  tmp_path.push(".new");
+  let tmp_path = PathBuf::from(tmp_path);
+  let result = File::create(&tmp_path)
+    .map(BufWriter::new)
+    .and_then(|mut out| {
+      write(&mut out)?;
+      out.flush()
+    })
+    .and_then(|()| std::fs::rename(&tmp_path, path));
+  result.map_err(|source| {
+    let _ = std::fs::remove_file(&tmp_path);
+    PackagesXmlProviderError::IoError {
+      file_path: path.to_owned(),
+      source,
+    }
+  })
+}
+
+fn escape_xml(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      c => out.push(c),
+    }
+  }
+  out
+}
+
+/// Writes the description of a MIME type, without its magic and root elements.
+fn write_mime_type_xml(out: &mut impl Write, mime: &PackageMimeType) -> io::Result<()> {
+  writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+  writeln!(
+    out,
+    r#"<mime-type xmlns="http://www.freedesktop.org/standards/shared-mime-info" type="{}">"#,
+    escape_xml(&mime.mime_type)
+  )?;
+  writeln!(
+    out,
+    "  <!--Created automatically by update-mime-database. DO NOT EDIT!-->"
+  )?;
+  for comment in &mime.comments {
+    match &comment.lang {
+      Some(lang) => writeln!(
+        out,
+        r#"  <comment xml:lang="{}">{}</comment>"#,
+        escape_xml(lang),
+        escape_xml(&comment.text)
+      )?,
+      None => writeln!(out, "  <comment>{}</comment>", escape_xml(&comment.text))?,
+    }
+  }
+  if let Some(acronym) = &mime.acronym {
+    writeln!(out, "  <acronym>{}</acronym>", escape_xml(acronym))?;
+  }
+  if let Some(expanded_acronym) = &mime.expanded_acronym {
+    writeln!(
+      out,
+      "  <expanded-acronym>{}</expanded-acronym>",
+      escape_xml(expanded_acronym)
+    )?;
+  }
+  for parent in &mime.sub_class_of {
+    writeln!(out, r#"  <sub-class-of type="{}"/>"#, escape_xml(parent))?;
+  }
+  if let Some(icon) = &mime.icon {
+    writeln!(out, r#"  <icon name="{}"/>"#, escape_xml(icon))?;
+  }
+  if let Some(generic_icon) = &mime.generic_icon {
+    writeln!(
+      out,
+      r#"  <generic-icon name="{}"/>"#,
+      escape_xml(generic_icon)
+    )?;
+  }
+  for alias in &mime.aliases {
+    writeln!(out, r#"  <alias type="{}"/>"#, escape_xml(alias))?;
+  }
+  for glob in &mime.globs {
+    write!(out, r#"  <glob pattern="{}""#, escape_xml(&glob.pattern))?;
+    if glob.weight != 50 {
+      write!(out, r#" weight="{}""#, glob.weight)?;
+    }
+    if glob.case_sensitive {
+      write!(out, r#" case-sensitive="true""#)?;
+    }
+    writeln!(out, "/>")?;
+  }
+  writeln!(out, "</mime-type>")
+}
+
+/// Checks that a MIME type only writes lines of the database files
+/// and files inside the database directory.
+fn check_mime_type(mime: &PackageMimeType) -> Result<(), PackagesXmlProviderError> {
+  let reason = if !is_plain_mime_type(&mime.mime_type) {
+    "not a media type without parameters"
+  } else if !mime
+    .aliases
+    .iter()
+    .chain(&mime.sub_class_of)
+    .all(|t| is_plain_mime_type(t))
+  {
+    "invalid alias or parent type"
+  } else if !mime.root_xml.iter().all(is_plain_root_xml) {
+    "invalid root element"
+  } else {
+    return Ok(());
+  };
+  Err(PackagesXmlProviderError::InvalidMimeType {
+    mime_type: mime.mime_type.clone(),
+    reason: reason.to_string(),
+  })
+}
+
+/// Writes the `globs2` and `globs` files.
+fn write_globs_files(
+  mime_types: &[PackageMimeType],
+  mime_dir: &Path,
+) -> Result<(), PackagesXmlProviderError> {
+  // Case-insensitive patterns are stored in lower case
+  let mut globs: Vec<_> = mime_types
+    .iter()
+    .flat_map(|mime| {
+      mime.globs.iter().map(move |glob| {
+        let pattern = if glob.case_sensitive {
+          glob.pattern.clone()
+        } else {
+          glob.pattern.to_lowercase()
+        };
+        (
+          glob.weight,
+          mime.mime_type.as_str(),
+          pattern,
+          glob.case_sensitive,
+        )
+      })
+    })
+    .collect();
+  globs.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| (&a.2, a.1).cmp(&(&b.2, b.1))));
+  write_file(&mime_dir.join("globs2"), |out| {
+    out.write_all(GENERATED_HEADER.as_bytes())?;
+    for (weight, mime_type, pattern, case_sensitive) in &globs {
+      write!(out, "{weight}:{mime_type}:{pattern}")?;
+      if *case_sensitive {
+        write!(out, ":cs")?;
+      }
+      writeln!(out)?;
+    }
+    Ok(())
+  })?;
+  write_file(&mime_dir.join("globs"), |out| {
+    out.write_all(GENERATED_HEADER.as_bytes())?;
+    let mut written = std::collections::HashSet::new();
+    for (_, mime_type, pattern, _) in &globs {
+      if written.insert((mime_type, pattern)) {
+        writeln!(out, "{mime_type}:{pattern}")?;
+      }
+    }
+    Ok(())
+  })?;
+  Ok(())
+}
+
+/// Writes the files of a compiled database into `mime_dir`.
+///
+/// `mime_types` should be merged with [`merge_package_mime_types`].
+///
+/// # Errors
+/// Returns an error if a MIME type or one of its aliases, parents or root elements is invalid,
+/// in which case nothing is written, or if a file cannot be written.
+pub fn write_mime_database(
+  mime_types: &[PackageMimeType],
+  mime_dir: &Path,
+) -> Result<(), PackagesXmlProviderError> {
+  for mime in mime_types {
+    check_mime_type(mime)?;
+  }
+  let mut db = OwnedBuildableDb::new();
+  db.add_package_mime_types(mime_types);
+  write_file(&mime_dir.join("magic"), |out| write_magic_file(&db, out))?;
+  write_file(&mime_dir.join("aliases"), |out| {
+    write_aliases_file(&db, out)
+  })?;
+  write_file(&mime_dir.join("subclasses"), |out| {
+    write_subclasses_file(&db, out)
+  })?;
+
+  write_globs_files(mime_types, mime_dir)?;
+  write_file(&mime_dir.join("types"), |out| {
+    for mime in mime_types {
+      writeln!(out, "{}", mime.mime_type)?;
+    }
+    Ok(())
+  })?;
+  write_file(&mime_dir.join("icons"), |out| {
+    for mime in mime_types {
+      if let Some(icon) = &mime.icon {
+        writeln!(out, "{}:{}", mime.mime_type, icon)?;
+      }
+    }
+    Ok(())
+  })?;
+  write_file(&mime_dir.join("generic-icons"), |out| {
+    for mime in mime_types {
+      if let Some(generic_icon) = &mime.generic_icon {
+        writeln!(out, "{}:{}", mime.mime_type, generic_icon)?;
+      }
+    }
+    Ok(())
+  })?;
+
+  let mut namespaces: Vec<_> = mime_types
+    .iter()
+    .flat_map(|mime| {
+      mime.root_xml.iter().map(move |root| {
+        (
+          root.namespace_uri.as_str(),
+          root.local_name.as_str(),
+          mime.mime_type.as_str(),
+        )
+      })
+    })
+    .collect();
+  namespaces.sort_unstable();
+  write_file(&mime_dir.join("XMLnamespaces"), |out| {
+    for (namespace_uri, local_name, mime_type) in &namespaces {
+      writeln!(out, "{namespace_uri} {local_name} {mime_type}")?;
+    }
+    Ok(())
+  })?;
+
+  for mime in mime_types {
+    let Some((media, subtype)) = mime.mime_type.split_once('/') else {
+      continue;
+    };
+    let media_dir = mime_dir.join(media);
+    std::fs::create_dir_all(&media_dir).map_err(|source| PackagesXmlProviderError::IoError {
+      file_path: media_dir.clone(),
+      source,
+    })?;
+    write_file(&media_dir.join(format!("{subtype}.xml")), |out| {
+      write_mime_type_xml(out, mime)
+    })?;
+  }
+  Ok(())
+}
+
+/// Compiles `mime_dir/packages/*.xml` into the files of a shared MIME info database
+/// in `mime_dir`, like `update-mime-database`.
+///
+/// Returns the number of MIME types in the database.
+///
+/// # Errors
+/// Returns an error if a packages file cannot be read or parsed,
+/// or an output file cannot be written.
+pub fn update_mime_database(mime_dir: &Path) -> Result<usize, PackagesXmlProviderError> {
+  let packages_dir = mime_dir.join("packages");
+  let files = package_files(&packages_dir).map_err(|source| PackagesXmlProviderError::IoError {
+    file_path: packages_dir.clone(),
+    source,
+  })?;
+  let mut mime_types = Vec::new();
+  for file_path in files {
+    let xml =
+      std::fs::read_to_string(&file_path).map_err(|source| PackagesXmlProviderError::IoError {
+        file_path: file_path.clone(),
+        source,
+      })?;
+    mime_types.extend(
+      parse_packages_xml(&xml)
+        .map_err(|source| PackagesXmlProviderError::PackagesXmlError { file_path, source })?,
+    );
+  }
+  let mime_types = merge_package_mime_types(mime_types);
+  write_mime_database(&mime_types, mime_dir)?;
+  Ok(mime_types.len())
+}
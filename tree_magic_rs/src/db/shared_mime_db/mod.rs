@@ -1,10 +1,10 @@
-mod mime_db;
-
-mod shared_mime_db_types;
-use shared_mime_db_types::SharedMimeMagicRule;
-
 mod parse_magic_rule;
-use parse_magic_rule::{parse_magic_file, MagicRuleParseError};
+pub use parse_magic_rule::MagicRuleParseError;
 
 mod shared_mime_db_provider;
-pub use shared_mime_db_provider::{SharedMimeDbProviderError, SharedMimeDbProviderExt};
+pub use shared_mime_db_provider::{LoadResult, SharedMimeDbProviderError, SharedMimeDbProviderExt};
+
+mod write_shared_mime_db;
+pub use write_shared_mime_db::{
+  write_aliases_file, write_magic_file, write_subclasses_file, write_xdg_shared_magic_dir,
+};
@@ -1,35 +1,50 @@
-use super::SharedMimeMagicRule;
-use crate::db::{MagicRule, OwnedMagicRule};
+use crate::db::OwnedMagicRule;
 use nom::{
   self,
-  bytes::complete::{is_not, tag, take, take_while},
-  character::is_digit,
-  character::{self, complete::line_ending, streaming::newline},
-  combinator::{map, map_res, opt, rest},
-  multi::many0,
+  bytes::complete::{is_not, tag, take, take_till},
+  character::{self, complete::u32},
+  combinator::{map_res, opt, rest},
   number::complete::be_u16,
-  sequence::{delimited, preceded, terminated, tuple},
+  sequence::{delimited, pair, preceded, terminated, tuple},
   IResult,
 };
 use thiserror::Error;
 
-#[derive(Error, Debug, PartialEq, Eq)]
-pub enum MagicRuleParseError {}
+const MAGIC_HEADER: &[u8] = b"MIME-Magic\0\n";
+
+/// The errors that can occur while parsing a magic file.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MagicRuleParseError {
+  /// The file does not start with `MIME-Magic\0\n`
+  #[error("Missing MIME-Magic header")]
+  MissingHeader,
+  /// A `[priority:mime]` line is malformed
+  #[error("Invalid section header at byte {offset}")]
+  InvalidSection {
+    /// The offset of the section header
+    offset: usize,
+  },
+  /// A rule line is malformed
+  #[error("Invalid magic rule at byte {offset}")]
+  InvalidRule {
+    /// The offset of the rule
+    offset: usize,
+  },
+}
 
 struct PrioMime<'a> {
   priority: u32,
   mime_type: &'a str,
 }
-/// Parses the MIME type and priority from "[priority: mime]\r\n"
-fn parse_mime<'a>(input: &'a [u8]) -> IResult<&'a [u8], PrioMime<'a>> {
-  let (input, between_brackets) =
-    delimited(character::complete::char('['), is_not("]"), tag("]"))(input)?;
+/// Parses the MIME type and priority from "[priority:mime]\n"
+fn parse_mime(input: &[u8]) -> IResult<&[u8], PrioMime<'_>> {
+  let (input, between_brackets) = terminated(
+    delimited(character::complete::char('['), is_not("]\n"), tag("]")),
+    tag("\n"),
+  )(input)?;
 
   let (_, (priority, mime)) = tuple((
-    terminated(
-      nom::character::complete::u32,
-      nom::character::complete::char(':'),
-    ),
+    terminated(u32, character::complete::char(':')),
     map_res(rest, std::str::from_utf8),
   ))(between_brackets)?;
 
@@ -42,82 +57,83 @@ fn parse_mime<'a>(input: &'a [u8]) -> IResult<&'a [u8], PrioMime<'a>> {
   ))
 }
 
+/// Parses "[indent]>start-offset=value[&mask][~word-size][+range-length]\n".
+///
+/// Returns `None` for rules with unknown extensions, which must be ignored.
 fn parse_magic_match_rule<'a>(
   input: &'a [u8],
-  prio_mime: PrioMime<'a>,
-) -> IResult<&'a [u8], SharedMimeMagicRule<'a>> {
-  todo!()
-  // let int_or = |default| {
-  //   map(take_while(is_digit), move |digits| {
-  //     str::from_utf8(digits).unwrap().parse().unwrap_or(default)
-  //   })
-  // };
+  prio_mime: &PrioMime<'a>,
+) -> IResult<&'a [u8], Option<OwnedMagicRule>> {
+  let (input, (indent_level, start_off, val_len)) = tuple((
+    terminated(opt(u32), tag(">")),
+    terminated(u32, tag("=")),
+    be_u16,
+  ))(input)?;
 
-  // let (input, (indent_level, start_off, val_len)) = tuple((
-  //   terminated(int_or(0), tag(">")),
-  //   terminated(int_or(0), tag("=")),
-  //   be_u16,
-  // ))(input)?;
+  let (input, (val, mask, word_len, region_len)) = tuple((
+    take(val_len),
+    opt(preceded(tag("&"), take(val_len))),
+    opt(preceded(tag("~"), u32)),
+    opt(preceded(tag("+"), u32)),
+  ))(input)?;
 
-  // let (input, (val, mask, word_len, region_len)) = terminated(
-  //   tuple((
-  //     take(val_len),
-  //     opt(preceded(tag("&"), take(val_len))),
-  //     opt(preceded(tag("~"), int_or(1))),
-  //     opt(preceded(tag("+"), int_or(0))),
-  //   )),
-  //   tag("\n"),
-  // )(input)?;
+  let (input, (unknown, _)) = pair(take_till(|b| b == b'\n'), tag("\n"))(input)?;
+  if !unknown.is_empty() {
+    return Ok((input, None));
+  }
 
-  // Ok((
-  //   input,
-  //   SharedMimeMagicRule::new(
-  //     prio_mime.priority,
-  //     prio_mime.mime_type,
-  //     indent_level,
-  //     start_off,
-  //     val,
-  //     mask,
-  //     word_len.unwrap_or(1),
-  //     region_len.unwrap_or(0),
-  //   ),
-  // ))
-}
-
-/// Converts a magic file given as a &[u8] array
-/// to a vector of MagicEntry structs
-/// Returns the amount of parsed magic rules
-pub fn parse_magic_file1<'a>(
-  input: impl Iterator<Item = u8>,
-  mime_types: &'a mut Vec<String>,
-  magic_rules: &mut Vec<SharedMimeMagicRule<'a>>,
-) -> Result<usize, (usize, MagicRuleParseError)> {
-  // let magic_entry = tuple((mime_parser, many0(parse_single_magic_rules)));
-  todo!()
-  // preceded(tag("MIME-Magic\0\n"), many0(magic_entry))(input)
+  Ok((
+    input,
+    Some(OwnedMagicRule::new(
+      prio_mime.priority,
+      prio_mime.mime_type.to_string(),
+      indent_level.unwrap_or(0),
+      start_off,
+      val.into(),
+      mask.unwrap_or_default().into(),
+      word_len.unwrap_or(1),
+      region_len.unwrap_or(0),
+    )),
+  ))
 }
 
-/// Converts a magic file given as a &[u8] array
-/// to a vector of MagicEntry structs
-/// Returns the amount of parsed magic rules
+/// Parses a magic file and appends its rules to `magic_rules`.
+/// Returns the amount of parsed magic rules,
+/// which are kept even if a later part of the file is malformed.
 pub fn parse_magic_file(
-  input: impl Iterator<Item = u8>,
+  input: &[u8],
   magic_rules: &mut Vec<OwnedMagicRule>,
 ) -> Result<usize, (usize, MagicRuleParseError)> {
-  // // Parse the MIME type from "[priority: mime]"
-  // let mime_parser = map_res(
-  //   terminated(
-  //     delimited(
-  //       delimited(tag("["), is_not(":"), tag(":")), // priority
-  //       is_not("]"),                                // mime
-  //       tag("]"),
-  //     ),
-  //     tag("\n"),
-  //   ),
-  //   std::str::from_utf8,
-  // );
-
-  // let magic_entry = tuple((mime_parser, many0(parse_single_magic_rules)));
-  todo!()
-  // preceded(tag("MIME-Magic\0\n"), many0(magic_entry))(input)
+  let offset = |remaining: &[u8]| input.len() - remaining.len();
+  let mut input_left = input
+    .strip_prefix(MAGIC_HEADER)
+    .ok_or((0, MagicRuleParseError::MissingHeader))?;
+  let mut parsed = 0;
+  while !input_left.is_empty() {
+    let (remaining, prio_mime) = parse_mime(input_left).map_err(|_| {
+      (
+        parsed,
+        MagicRuleParseError::InvalidSection {
+          offset: offset(input_left),
+        },
+      )
+    })?;
+    input_left = remaining;
+    while !input_left.is_empty() && input_left[0] != b'[' {
+      let (remaining, rule) = parse_magic_match_rule(input_left, &prio_mime).map_err(|_| {
+        (
+          parsed,
+          MagicRuleParseError::InvalidRule {
+            offset: offset(input_left),
+          },
+        )
+      })?;
+      input_left = remaining;
+      if let Some(rule) = rule {
+        magic_rules.push(rule);
+        parsed += 1;
+      }
+    }
+  }
+  Ok(parsed)
 }
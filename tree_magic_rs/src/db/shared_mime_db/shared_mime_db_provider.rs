@@ -1,31 +1,52 @@
 use super::parse_magic_rule::{parse_magic_file, MagicRuleParseError};
 use crate::db::{BuildeableDbProvider, OwnedAlias, OwnedMagicRule, OwnedSubclass};
 use derive_more::{Add, Sum};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::{
-  fs::File,
-  io::Read,
+  io::ErrorKind,
   path::{Path, PathBuf},
 };
 use thiserror::Error;
 
+/// The errors that can occur while loading the database files.
 #[derive(Error, Debug)]
 pub enum SharedMimeDbProviderError {
+  /// A file could not be read
   #[error("IO error")]
   IoError {
+    /// The file that could not be read
     file_path: PathBuf,
+    /// The underlying error
     source: std::io::Error,
   },
+  /// A magic file is malformed
   #[error("Error parsing magic file")]
   MagicRuleParseError {
+    /// The malformed file
     file_path: PathBuf,
+    /// The underlying error
     source: MagicRuleParseError,
   },
+  /// A line of an aliases or subclasses file is malformed
+  #[error("Error parsing line {line}")]
+  LineParseError {
+    /// The malformed file
+    file_path: PathBuf,
+    /// The line number, starting at 1
+    line: usize,
+  },
 }
 
+/// How many entries were loaded from the database files.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Add, Sum)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LoadResult {
+  /// The number of magic rules
   pub magic_rules_num: usize,
+  /// The number of aliases
   pub aliases_num: usize,
+  /// The number of subclasses
   pub subclasses_num: usize,
 }
 
@@ -72,7 +93,7 @@ impl SharedMimeDbProviderExt for BuildeableDbProvider<OwnedMagicRule, OwnedAlias
       "/usr/local/share/mime",
       "/usr/share/mime",
     ];
-    let errs = Vec::new();
+    let mut errs = Vec::new();
     let load_result = SEARCH_DIRS
       .iter()
       .map(|str_path| {
@@ -97,9 +118,9 @@ impl SharedMimeDbProviderExt for BuildeableDbProvider<OwnedMagicRule, OwnedAlias
     &mut self,
     dir: &Path,
   ) -> Result<LoadResult, (LoadResult, Vec<SharedMimeDbProviderError>)> {
-    let errs = Vec::new();
+    let mut errs = Vec::new();
 
-    let unpack = |packed: Result<usize, (usize, SharedMimeDbProviderError)>| -> usize {
+    let mut unpack = |packed: Result<usize, (usize, SharedMimeDbProviderError)>| -> usize {
       match packed {
         Err(err) => {
           errs.push(err.1);
@@ -124,32 +145,10 @@ impl SharedMimeDbProviderExt for BuildeableDbProvider<OwnedMagicRule, OwnedAlias
     &mut self,
     file_path: &Path,
   ) -> Result<usize, (usize, SharedMimeDbProviderError)> {
-    let file = File::open(file_path);
-    let Ok(file) = file else {
+    let Some(bytes) = read_optional(file_path).map_err(|err| (0, err))? else {
       return Ok(0);
     };
-
-    fn unpack_usize(packed: Result<usize, (usize, MagicRuleParseError)>) -> usize {
-      match packed {
-        Err(err) => err.0,
-        Ok(ok) => ok,
-      }
-    }
-
-    let mut io_error = Ok(());
-    let bytes = file.bytes().scan(&mut io_error, until_err);
-    let parse_result = parse_magic_file(bytes, &mut self.magic_rules_mut());
-    let loaded = unpack_usize(parse_result);
-    if let Err(io_error) = io_error {
-      return Err((
-        loaded,
-        SharedMimeDbProviderError::IoError {
-          file_path: file_path.to_owned(),
-          source: io_error,
-        },
-      ));
-    }
-    parse_result.map_err(|err| {
+    parse_magic_file(&bytes, self.magic_rules_mut()).map_err(|err| {
       (
         err.0,
         SharedMimeDbProviderError::MagicRuleParseError {
@@ -162,34 +161,67 @@ impl SharedMimeDbProviderExt for BuildeableDbProvider<OwnedMagicRule, OwnedAlias
 
   fn load_aliases_file(
     &mut self,
-    file: &Path,
+    file_path: &Path,
   ) -> Result<usize, (usize, SharedMimeDbProviderError)> {
-    todo!()
+    let aliases = self.aliases_mut();
+    load_pairs_file(file_path, |alias, name| {
+      aliases.push(OwnedAlias::new(alias.to_string(), name.to_string()));
+    })
   }
 
   fn load_subclasses_file(
     &mut self,
-    file: &Path,
+    file_path: &Path,
   ) -> Result<usize, (usize, SharedMimeDbProviderError)> {
-    todo!()
+    let subclasses = self.subclasses_mut();
+    load_pairs_file(file_path, |mime_type, parent| {
+      subclasses.push(OwnedSubclass::new(
+        mime_type.to_string(),
+        parent.to_string(),
+      ));
+    })
   }
 }
-/// https://stackoverflow.com/a/63120052/4479969
-fn until_err<T, E>(err: &mut &mut Result<(), E>, item: Result<T, E>) -> Option<T> {
-  match item {
-    Ok(item) => Some(item),
-    Err(e) => {
-      **err = Err(e);
-      None
-    },
+
+/// Reads a file, treating a missing file as empty.
+fn read_optional(file_path: &Path) -> Result<Option<Vec<u8>>, SharedMimeDbProviderError> {
+  match std::fs::read(file_path) {
+    Ok(bytes) => Ok(Some(bytes)),
+    Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+    Err(err) => Err(SharedMimeDbProviderError::IoError {
+      file_path: file_path.to_owned(),
+      source: err,
+    }),
   }
 }
-fn count_errs<T, E>(errs: &mut &mut Vec<E>, item: Result<T, E>) -> Option<T> {
-  match item {
-    Ok(item) => Some(item),
-    Err(e) => {
-      (*errs).push(e);
-      None
-    },
+
+/// Loads a file with two whitespace separated MIME types per line.
+fn load_pairs_file(
+  file_path: &Path,
+  mut add: impl FnMut(&str, &str),
+) -> Result<usize, (usize, SharedMimeDbProviderError)> {
+  let Some(bytes) = read_optional(file_path).map_err(|err| (0, err))? else {
+    return Ok(0);
+  };
+  let mut loaded = 0;
+  for (line_idx, line) in bytes.split(|b| *b == b'\n').enumerate() {
+    let malformed = || {
+      (
+        loaded,
+        SharedMimeDbProviderError::LineParseError {
+          file_path: file_path.to_owned(),
+          line: line_idx + 1,
+        },
+      )
+    };
+    let line = std::str::from_utf8(line).map_err(|_| malformed())?;
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+      (None, ..) => continue,
+      (Some(first), Some(second), None) => add(first, second),
+      _ => return Err(malformed()),
+    }
+    loaded += 1;
   }
+  Ok(loaded)
 }
@@ -0,0 +1,142 @@
+use crate::db::{Alias, DbProvider, MagicRule, Subclass};
+use std::{
+  collections::BTreeMap,
+  fs::File,
+  io::{self, BufWriter, Write},
+  path::Path,
+};
+
+fn invalid_input(msg: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+fn write_magic_rule(out: &mut impl Write, rule: &(impl MagicRule + ?Sized)) -> io::Result<()> {
+  let val = rule.val();
+  let val_len = u16::try_from(val.len()).map_err(|_| {
+    invalid_input(format!(
+      "value of a {} rule is longer than 65535 bytes",
+      rule.mime_type()
+    ))
+  })?;
+  if rule.indent_level() != 0 {
+    write!(out, "{}", rule.indent_level())?;
+  }
+  write!(out, ">{}=", rule.start_off())?;
+  out.write_all(&val_len.to_be_bytes())?;
+  out.write_all(val)?;
+  let mask = rule.mask();
+  if !mask.is_empty() {
+    if mask.len() != val.len() {
+      return Err(invalid_input(format!(
+        "mask of a {} rule does not match the value length",
+        rule.mime_type()
+      )));
+    }
+    out.write_all(b"&")?;
+    out.write_all(mask)?;
+  }
+  if rule.word_len() != 1 {
+    write!(out, "~{}", rule.word_len())?;
+  }
+  if rule.region_len() != 0 {
+    write!(out, "+{}", rule.region_len())?;
+  }
+  out.write_all(b"\n")
+}
+
+/// Writes the magic rules of a [`DbProvider`] as a `magic` file.
+///
+/// Rules are grouped into sections sorted by descending priority and then by MIME type.
+/// The order of the rules within a section is kept.
+///
+/// # Errors
+/// Returns an error if writing fails or a rule cannot be encoded.
+pub fn write_magic_file<'a, P, W>(provider: &'a P, mut out: W) -> io::Result<()>
+where
+  P: DbProvider<'a> + ?Sized,
+  W: Write,
+{
+  let mut sections = BTreeMap::<_, Vec<_>>::new();
+  for rule in provider.iter_magic_rules() {
+    sections
+      .entry((std::cmp::Reverse(rule.priority()), rule.mime_type()))
+      .or_default()
+      .push(rule);
+  }
+  out.write_all(b"MIME-Magic\0\n")?;
+  for ((priority, mime_type), rules) in sections {
+    if mime_type.contains([']', '\n']) {
+      return Err(invalid_input(format!("invalid MIME type {mime_type:?}")));
+    }
+    writeln!(out, "[{}:{}]", priority.0, mime_type)?;
+    for rule in rules {
+      write_magic_rule(&mut out, rule)?;
+    }
+  }
+  out.flush()
+}
+
+/// Writes sorted lines of two MIME types.
+fn write_pairs<'a>(
+  pairs: impl Iterator<Item = (&'a str, &'a str)>,
+  mut out: impl Write,
+) -> io::Result<()> {
+  let mut pairs: Vec<_> = pairs.collect();
+  pairs.sort_unstable();
+  pairs.dedup();
+  for (first, second) in pairs {
+    writeln!(out, "{first} {second}")?;
+  }
+  out.flush()
+}
+
+/// Writes the aliases of a [`DbProvider`] as an `aliases` file.
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_aliases_file<'a, P, W>(provider: &'a P, out: W) -> io::Result<()>
+where
+  P: DbProvider<'a> + ?Sized,
+  W: Write,
+{
+  write_pairs(
+    provider
+      .iter_aliases()
+      .map(|alias| (alias.alias(), alias.name())),
+    out,
+  )
+}
+
+/// Writes the subclasses of a [`DbProvider`] as a `subclasses` file.
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_subclasses_file<'a, P, W>(provider: &'a P, out: W) -> io::Result<()>
+where
+  P: DbProvider<'a> + ?Sized,
+  W: Write,
+{
+  write_pairs(
+    provider
+      .iter_subclasses()
+      .map(|subclass| (subclass.mime_type(), subclass.parent())),
+    out,
+  )
+}
+
+/// Writes the `magic`, `aliases` and `subclasses` files of a [`DbProvider`] into `dir`.
+///
+/// The directory can be loaded with
+/// [`load_from_xdg_shared_magic_dir`](super::SharedMimeDbProviderExt::load_from_xdg_shared_magic_dir).
+///
+/// # Errors
+/// Returns an error if writing fails or a rule cannot be encoded.
+pub fn write_xdg_shared_magic_dir<'a, P>(provider: &'a P, dir: &Path) -> io::Result<()>
+where
+  P: DbProvider<'a> + ?Sized,
+{
+  let create = |name: &str| File::create(dir.join(name)).map(BufWriter::new);
+  write_magic_file(provider, create("magic")?)?;
+  write_aliases_file(provider, create("aliases")?)?;
+  write_subclasses_file(provider, create("subclasses")?)
+}
@@ -0,0 +1,405 @@
+//! A versioned, checksummed binary snapshot of a database.
+//!
+//! Reading a snapshot only validates it, the text database files are not parsed.
+//! The entries of [`Snapshot::provider`] borrow their strings and bytes from the
+//! snapshot, but [`MimeDatabase::from_provider`](crate::MimeDatabase::from_provider)
+//! copies every entry and builds its type graph, so a database is not shared
+//! between processes that map the same snapshot.
+//!
+//! All integers are little-endian. The layout is:
+//! - header: magic, version, reserved, payload length, FNV-1a checksum of the payload
+//! - counts of magic rules, aliases and subclasses
+//! - fixed size records referencing byte ranges of the data area
+//! - data area holding all strings and byte values
+use super::{
+  Alias, BorrowedAlias, BorrowedBuildableDb, BorrowedMagicRule, BorrowedSubclass, DbProvider,
+  MagicRule, Subclass,
+};
+use fnv::{FnvHashMap, FnvHasher};
+use std::{
+  fs::File,
+  hash::Hasher,
+  io::{self, Write},
+  path::Path,
+};
+use thiserror::Error;
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"TMRSNAP\0";
+/// The snapshot format version written by this version of the crate
+pub const SNAPSHOT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 32;
+const COUNTS_LEN: usize = 16;
+const RULE_RECORD_LEN: usize = 11 * 4;
+const ALIAS_RECORD_LEN: usize = 4 * 4;
+const SUBCLASS_RECORD_LEN: usize = 4 * 4;
+
+/// The errors that can occur while reading a snapshot.
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+  /// The snapshot file could not be read
+  #[error("IO error")]
+  IoError(#[from] io::Error),
+  /// The data does not start with the snapshot magic
+  #[error("Not a database snapshot")]
+  BadMagic,
+  /// The snapshot was written by an incompatible version of the crate
+  #[error("Unsupported snapshot version {version}")]
+  UnsupportedVersion {
+    /// The version found in the header
+    version: u32,
+  },
+  /// The payload does not match the checksum in the header
+  #[error("Snapshot checksum mismatch")]
+  ChecksumMismatch,
+  /// A record points outside of the snapshot or a string is not UTF-8
+  #[error("Malformed snapshot at byte {offset}")]
+  Malformed {
+    /// The offset of the offending record or value
+    offset: usize,
+  },
+}
+
+fn checksum(payload: &[u8]) -> u64 {
+  let mut hasher = FnvHasher::default();
+  hasher.write(payload);
+  hasher.finish()
+}
+
+/// Collects strings and byte values into the data area, storing duplicates once
+#[derive(Default)]
+struct DataWriter {
+  data: Vec<u8>,
+  offsets: FnvHashMap<Vec<u8>, u32>,
+}
+impl DataWriter {
+  fn push(&mut self, bytes: &[u8]) -> io::Result<[u32; 2]> {
+    let len = to_u32(bytes.len())?;
+    if let Some(offset) = self.offsets.get(bytes) {
+      return Ok([*offset, len]);
+    }
+    let offset = to_u32(self.data.len())?;
+    self.data.extend_from_slice(bytes);
+    self.offsets.insert(bytes.to_vec(), offset);
+    Ok([offset, len])
+  }
+}
+
+fn to_u32(n: usize) -> io::Result<u32> {
+  u32::try_from(n).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "database too large"))
+}
+
+fn put_u32s(out: &mut Vec<u8>, values: &[u32]) {
+  for value in values {
+    out.extend_from_slice(&value.to_le_bytes());
+  }
+}
+
+/// Writes all entries of a [`DbProvider`] as a snapshot.
+///
+/// # Errors
+/// Returns an error if writing fails or the database exceeds 4 GiB.
+pub fn write_snapshot<'a, P, W>(provider: &'a P, mut writer: W) -> io::Result<()>
+where
+  P: DbProvider<'a> + ?Sized,
+  W: Write,
+{
+  let mut data = DataWriter::default();
+  let mut rules = Vec::new();
+  let mut rules_num = 0;
+  for rule in provider.iter_magic_rules() {
+    let [mime_off, mime_len] = data.push(rule.mime_type().as_bytes())?;
+    let [val_off, val_len] = data.push(rule.val())?;
+    let [mask_off, mask_len] = data.push(rule.mask())?;
+    put_u32s(
+      &mut rules,
+      &[
+        rule.priority(),
+        rule.indent_level(),
+        rule.start_off(),
+        rule.word_len(),
+        rule.region_len(),
+        mime_off,
+        mime_len,
+        val_off,
+        val_len,
+        mask_off,
+        mask_len,
+      ],
+    );
+    rules_num += 1;
+  }
+  let mut aliases = Vec::new();
+  let mut aliases_num = 0;
+  for alias in provider.iter_aliases() {
+    let [alias_off, alias_len] = data.push(alias.alias().as_bytes())?;
+    let [name_off, name_len] = data.push(alias.name().as_bytes())?;
+    put_u32s(&mut aliases, &[alias_off, alias_len, name_off, name_len]);
+    aliases_num += 1;
+  }
+  let mut subclasses = Vec::new();
+  let mut subclasses_num = 0;
+  for subclass in provider.iter_subclasses() {
+    let [mime_off, mime_len] = data.push(subclass.mime_type().as_bytes())?;
+    let [parent_off, parent_len] = data.push(subclass.parent().as_bytes())?;
+    put_u32s(
+      &mut subclasses,
+      &[mime_off, mime_len, parent_off, parent_len],
+    );
+    subclasses_num += 1;
+  }
+
+  let mut payload = Vec::with_capacity(
+    COUNTS_LEN + rules.len() + aliases.len() + subclasses.len() + data.data.len(),
+  );
+  put_u32s(
+    &mut payload,
+    &[
+      to_u32(rules_num)?,
+      to_u32(aliases_num)?,
+      to_u32(subclasses_num)?,
+      0,
+    ],
+  );
+  payload.extend_from_slice(&rules);
+  payload.extend_from_slice(&aliases);
+  payload.extend_from_slice(&subclasses);
+  payload.extend_from_slice(&data.data);
+
+  writer.write_all(SNAPSHOT_MAGIC)?;
+  writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+  writer.write_all(&0u32.to_le_bytes())?;
+  writer.write_all(&(payload.len() as u64).to_le_bytes())?;
+  writer.write_all(&checksum(&payload).to_le_bytes())?;
+  writer.write_all(&payload)?;
+  writer.flush()
+}
+
+/// The checksum that a snapshot of the provider has, see [`Snapshot::checksum`].
+pub fn provider_checksum<'a, P: DbProvider<'a> + ?Sized>(provider: &'a P) -> u64 {
+  let mut bytes = Vec::new();
+  // Writing to a `Vec` only fails for databases that exceed 4 GiB
+  write_snapshot(provider, &mut bytes).map_or(0, |()| checksum(&bytes[HEADER_LEN..]))
+}
+
+/// Writes a snapshot to `path`.
+///
+/// The snapshot is written to a temporary file first and then renamed,
+/// so processes that have the old snapshot mapped keep seeing consistent data.
+///
+/// # Errors
+/// Returns an error if writing fails or the database exceeds 4 GiB.
+pub fn write_snapshot_file<'a, P>(provider: &'a P, path: &Path) -> io::Result<()>
+where
+  P: DbProvider<'a> + ?Sized,
+{
+  let mut tmp_path = path.as_os_str().to_owned();
+  tmp_path.push(format!(".{}.tmp", std::process::id()));
+  let tmp_path = Path::new(&tmp_path);
+  let result = File::create(tmp_path)
+    .and_then(|file| write_snapshot(provider, io::BufWriter::new(file)))
+    .and_then(|()| std::fs::rename(tmp_path, path));
+  if result.is_err() {
+    let _ = std::fs::remove_file(tmp_path);
+  }
+  result
+}
+
+/// Checks the header and checksum and returns the payload.
+fn validate(bytes: &[u8]) -> Result<&[u8], SnapshotError> {
+  if bytes.len() < HEADER_LEN || &bytes[..8] != SNAPSHOT_MAGIC {
+    return Err(SnapshotError::BadMagic);
+  }
+  let version = read_u32(bytes, 8)?;
+  if version != SNAPSHOT_VERSION {
+    return Err(SnapshotError::UnsupportedVersion { version });
+  }
+  let payload_len =
+    usize::try_from(read_u64(bytes, 16)?).map_err(|_| SnapshotError::Malformed { offset: 16 })?;
+  let payload = bytes
+    .get(HEADER_LEN..)
+    .filter(|payload| payload.len() == payload_len)
+    .ok_or(SnapshotError::Malformed { offset: 16 })?;
+  if checksum(payload) != read_u64(bytes, 24)? {
+    return Err(SnapshotError::ChecksumMismatch);
+  }
+  Ok(payload)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, SnapshotError> {
+  bytes
+    .get(offset..offset + 4)
+    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    .ok_or(SnapshotError::Malformed { offset })
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, SnapshotError> {
+  Ok(u64::from(read_u32(bytes, offset)?) | u64::from(read_u32(bytes, offset + 4)?) << 32)
+}
+
+/// Reads records out of the payload
+struct Records<'a> {
+  payload: &'a [u8],
+  data_start: usize,
+}
+impl<'a> Records<'a> {
+  fn bytes(&self, record: usize, field: usize) -> Result<&'a [u8], SnapshotError> {
+    let offset = read_u32(self.payload, record + field * 4)? as usize;
+    let len = read_u32(self.payload, record + field * 4 + 4)? as usize;
+    let start = self.data_start + offset;
+    self
+      .payload
+      .get(start..start + len)
+      .ok_or(SnapshotError::Malformed {
+        offset: HEADER_LEN + record,
+      })
+  }
+  fn str(&self, record: usize, field: usize) -> Result<&'a str, SnapshotError> {
+    std::str::from_utf8(self.bytes(record, field)?).map_err(|_| SnapshotError::Malformed {
+      offset: HEADER_LEN + record,
+    })
+  }
+}
+
+/// Reads the entries of a snapshot, borrowing their strings and bytes from it.
+///
+/// # Errors
+/// Returns an error if `bytes` is not a valid snapshot of this version.
+pub fn read_snapshot(bytes: &[u8]) -> Result<BorrowedBuildableDb<'_>, SnapshotError> {
+  let payload = validate(bytes)?;
+  let rules_num = read_u32(payload, 0)? as usize;
+  let aliases_num = read_u32(payload, 4)? as usize;
+  let subclasses_num = read_u32(payload, 8)? as usize;
+
+  let rules_start = COUNTS_LEN;
+  let aliases_start = rules_start + rules_num * RULE_RECORD_LEN;
+  let subclasses_start = aliases_start + aliases_num * ALIAS_RECORD_LEN;
+  let data_start = subclasses_start + subclasses_num * SUBCLASS_RECORD_LEN;
+  if data_start > payload.len() {
+    return Err(SnapshotError::Malformed { offset: HEADER_LEN });
+  }
+  let records = Records {
+    payload,
+    data_start,
+  };
+
+  let mut db = BorrowedBuildableDb::new();
+  db.magic_rules_mut().reserve_exact(rules_num);
+  for i in 0..rules_num {
+    let record = rules_start + i * RULE_RECORD_LEN;
+    let field = |n: usize| read_u32(payload, record + n * 4);
+    db.magic_rules_mut().push(BorrowedMagicRule::new(
+      field(0)?,
+      records.str(record, 5)?,
+      field(1)?,
+      field(2)?,
+      records.bytes(record, 7)?,
+      records.bytes(record, 9)?,
+      field(3)?,
+      field(4)?,
+    ));
+  }
+  db.aliases_mut().reserve_exact(aliases_num);
+  for i in 0..aliases_num {
+    let record = aliases_start + i * ALIAS_RECORD_LEN;
+    db.aliases_mut().push(BorrowedAlias::new(
+      records.str(record, 0)?,
+      records.str(record, 2)?,
+    ));
+  }
+  db.subclasses_mut().reserve_exact(subclasses_num);
+  for i in 0..subclasses_num {
+    let record = subclasses_start + i * SUBCLASS_RECORD_LEN;
+    db.subclasses_mut().push(BorrowedSubclass::new(
+      records.str(record, 0)?,
+      records.str(record, 2)?,
+    ));
+  }
+  Ok(db)
+}
+
+enum Storage {
+  Owned(Vec<u8>),
+  #[cfg(feature = "mmap")]
+  Mapped(memmap2::Mmap),
+}
+
+/// A validated snapshot held in memory or mapped from a file.
+///
+/// # Examples
+/// ```rust
+/// use tree_magic_rs::db::{read_snapshot, write_snapshot, OwnedBuildableDb, Snapshot};
+///
+/// let mut bytes = Vec::new();
+/// write_snapshot(&OwnedBuildableDb::new(), &mut bytes).unwrap();
+/// let snapshot = Snapshot::from_vec(bytes).unwrap();
+/// let db = tree_magic_rs::MimeDatabase::from_provider(&snapshot.provider().unwrap());
+/// ```
+pub struct Snapshot {
+  storage: Storage,
+}
+impl Snapshot {
+  /// Validates a snapshot that is already in memory.
+  ///
+  /// # Errors
+  /// Returns an error if `bytes` is not a valid snapshot of this version.
+  pub fn from_vec(bytes: Vec<u8>) -> Result<Self, SnapshotError> {
+    validate(&bytes)?;
+    Ok(Self {
+      storage: Storage::Owned(bytes),
+    })
+  }
+
+  /// Reads and validates a snapshot file.
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be read or is not a valid snapshot.
+  pub fn read(path: &Path) -> Result<Self, SnapshotError> {
+    Self::from_vec(std::fs::read(path)?)
+  }
+
+  /// Maps and validates a snapshot file.
+  ///
+  /// The pages of the file are shared with every other process mapping it.
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be mapped or is not a valid snapshot.
+  ///
+  /// # Safety
+  /// The file must not be modified while the snapshot is alive.
+  /// [`write_snapshot_file`] replaces snapshots without modifying them.
+  #[cfg(feature = "mmap")]
+  pub unsafe fn map(path: &Path) -> Result<Self, SnapshotError> {
+    let file = File::open(path)?;
+    // SAFETY: The caller guarantees that the file is not modified while mapped.
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    validate(&mmap)?;
+    Ok(Self {
+      storage: Storage::Mapped(mmap),
+    })
+  }
+
+  /// The raw snapshot
+  #[must_use]
+  pub fn as_bytes(&self) -> &[u8] {
+    match &self.storage {
+      Storage::Owned(bytes) => bytes,
+      #[cfg(feature = "mmap")]
+      Storage::Mapped(mmap) => mmap,
+    }
+  }
+
+  /// The checksum of the snapshot's contents
+  #[must_use]
+  pub fn checksum(&self) -> u64 {
+    let bytes = self.as_bytes();
+    u64::from_le_bytes(bytes[24..32].try_into().unwrap_or_default())
+  }
+
+  /// Entries borrowing from the snapshot.
+  ///
+  /// # Errors
+  /// Returns an error if a record of the snapshot is malformed.
+  pub fn provider(&self) -> Result<BorrowedBuildableDb<'_>, SnapshotError> {
+    read_snapshot(self.as_bytes())
+  }
+}
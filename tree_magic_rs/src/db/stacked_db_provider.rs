@@ -13,19 +13,23 @@ type DynDbProvider<'a> = Box<
   >,
 >;
 
+/// Several databases searched as one, in order.
 pub struct StackedDbProvider<'a> {
   dbs: VecDeque<DynDbProvider<'a>>,
 }
 impl<'a> StackedDbProvider<'a> {
+  /// Creates a stack without databases.
   #[must_use]
   pub fn new() -> Self {
     Self {
       dbs: VecDeque::new(),
     }
   }
+  /// Adds a database that is searched before the others.
   pub fn prepend_db(&mut self, db: DynDbProvider<'a>) {
     self.dbs.push_front(db)
   }
+  /// Adds a database that is searched after the others.
   pub fn append_db(&mut self, db: DynDbProvider<'a>) {
     self.dbs.push_back(db)
   }
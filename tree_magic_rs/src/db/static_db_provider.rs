@@ -0,0 +1,45 @@
+use crate::db::{BorrowedAlias, BorrowedMagicRule, BorrowedSubclass, DbProvider};
+
+/// A database of borrowed slices that can be built in a `static`.
+///
+/// See [`codegen`](crate::codegen) for generating one at build time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticDbProvider<'a> {
+  magic_rules: &'a [BorrowedMagicRule<'a>],
+  aliases: &'a [BorrowedAlias<'a>],
+  subclasses: &'a [BorrowedSubclass<'a>],
+}
+impl<'a> StaticDbProvider<'a> {
+  /// Creates a database from its entries.
+  #[must_use]
+  pub const fn new(
+    magic_rules: &'a [BorrowedMagicRule<'a>],
+    aliases: &'a [BorrowedAlias<'a>],
+    subclasses: &'a [BorrowedSubclass<'a>],
+  ) -> Self {
+    Self {
+      magic_rules,
+      aliases,
+      subclasses,
+    }
+  }
+}
+impl<'a, 'b: 'a> DbProvider<'a> for StaticDbProvider<'b> {
+  type MagicRule = BorrowedMagicRule<'b>;
+
+  fn iter_magic_rules(&'a self) -> Box<dyn Iterator<Item = &'a Self::MagicRule> + 'a> {
+    Box::new(self.magic_rules.iter())
+  }
+
+  type Alias = BorrowedAlias<'b>;
+
+  fn iter_aliases(&'a self) -> Box<dyn Iterator<Item = &'a Self::Alias> + 'a> {
+    Box::new(self.aliases.iter())
+  }
+
+  type Subclass = BorrowedSubclass<'b>;
+
+  fn iter_subclasses(&'a self) -> Box<dyn Iterator<Item = &'a Self::Subclass> + 'a> {
+    Box::new(self.subclasses.iter())
+  }
+}
@@ -0,0 +1,7 @@
+mod parse_tika_mimetypes;
+pub use parse_tika_mimetypes::{
+  parse_tika_mimetypes, TikaImport, TikaMimeType, TikaMimetypesError, TikaUnsupported,
+};
+
+mod tika_provider;
+pub use tika_provider::{TikaProviderError, TikaProviderExt};
@@ -0,0 +1,276 @@
+use crate::db::{
+  magic_values::{parse_number, parse_string_value},
+  packages_xml::{parse_offset, parse_string_mask, translate_match},
+  OwnedMagicRule,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The errors that can occur while parsing a Tika MIME types file.
+#[derive(Error, Debug)]
+pub enum TikaMimetypesError {
+  /// The file is not well-formed XML
+  #[error("XML error")]
+  XmlError(#[from] roxmltree::Error),
+  /// A `<mime-type>` has no `type` attribute
+  #[error("<mime-type> without a type attribute")]
+  MissingType,
+}
+
+/// A `<mime-type>` of a Tika MIME types file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TikaMimeType {
+  /// The MIME type
+  pub mime_type: String,
+  /// The `<glob>` patterns, without regular expressions
+  pub globs: Vec<String>,
+  /// The `<magic>` sections translated into magic rules
+  pub magic_rules: Vec<OwnedMagicRule>,
+  /// Other names of this MIME type
+  pub aliases: Vec<String>,
+  /// The MIME types this is a subclass of
+  pub sub_class_of: Vec<String>,
+}
+
+/// A construct of a Tika MIME types file that has no equivalent and was left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TikaUnsupported {
+  /// The MIME type the construct belongs to
+  pub mime_type: String,
+  /// The name of the element
+  pub element: String,
+  /// Why it was left out
+  pub reason: String,
+}
+
+/// The result of [`parse_tika_mimetypes`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TikaImport {
+  /// The parsed MIME types
+  pub mime_types: Vec<TikaMimeType>,
+  /// The constructs that were left out
+  pub unsupported: Vec<TikaUnsupported>,
+}
+
+/// Decodes a string value, given in hex as `0x...` or with escapes.
+fn parse_tika_string(value: &str) -> Result<Vec<u8>, String> {
+  if value.starts_with("0x") {
+    parse_string_mask(value, (value.len() - 2) / 2)
+  } else {
+    parse_string_value(value)
+  }
+}
+
+/// Translates the string types of Tika into value and mask.
+fn translate_tika_string(
+  match_type: &str,
+  value: &str,
+  mask: Option<&str>,
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+  let mut val = parse_tika_string(value)?;
+  let mut mask = match mask {
+    Some(mask) => parse_tika_string(mask)?,
+    None => Vec::new(),
+  };
+  match match_type {
+    "string" => {},
+    // Letters are compared with the case bit masked out
+    "stringignorecase" => {
+      if mask.is_empty() {
+        mask = vec![0xff; val.len()];
+      }
+      for (b, m) in val.iter_mut().zip(&mut mask) {
+        if b.is_ascii_alphabetic() {
+          *b = b.to_ascii_uppercase();
+          *m &= 0xdf;
+        }
+      }
+    },
+    "unicodeLE" | "unicodeBE" => {
+      if !mask.is_empty() {
+        return Err(format!("masks are not supported for {match_type}"));
+      }
+      let text = String::from_utf8(val).map_err(|_| format!("{value:?} is not UTF-8"))?;
+      val = text
+        .encode_utf16()
+        .flat_map(|unit| match match_type {
+          "unicodeLE" => unit.to_le_bytes(),
+          _ => unit.to_be_bytes(),
+        })
+        .collect();
+    },
+    other => return Err(format!("unsupported match type {other:?}")),
+  }
+  if !mask.is_empty() && mask.len() != val.len() {
+    return Err(format!("mask {mask:?} does not match the value length"));
+  }
+  // The value is compared with the masked file contents
+  if !mask.is_empty() {
+    for (b, m) in val.iter_mut().zip(&mask) {
+      *b &= m;
+    }
+  }
+  Ok((val, mask))
+}
+
+struct MimeTypeParser<'a> {
+  mime_type: &'a str,
+  unsupported: &'a mut Vec<TikaUnsupported>,
+}
+impl MimeTypeParser<'_> {
+  fn unsupported(&mut self, element: &str, reason: String) {
+    self.unsupported.push(TikaUnsupported {
+      mime_type: self.mime_type.to_string(),
+      element: element.to_string(),
+      reason,
+    });
+  }
+
+  /// Translates a `<match>` without its children.
+  fn translate_match(
+    &self,
+    node: roxmltree::Node<'_, '_>,
+    priority: u32,
+    indent_level: u32,
+  ) -> Result<OwnedMagicRule, String> {
+    if node.attribute("minShouldMatch").is_some() {
+      return Err("minShouldMatch is not supported".to_string());
+    }
+    let match_type = node.attribute("type").unwrap_or("string");
+    let value = node
+      .attribute("value")
+      .ok_or_else(|| "missing value attribute".to_string())?;
+    let (start_off, region_len) = parse_offset(node.attribute("offset").unwrap_or("0"))?;
+    let mask = node.attribute("mask");
+    let (val, mask, word_len) = match match_type {
+      "regex" => return Err("regular expressions are not supported".to_string()),
+      "string" | "stringignorecase" | "unicodeLE" | "unicodeBE" => {
+        let (val, mask) = translate_tika_string(match_type, value, mask)?;
+        (val, mask, 1)
+      },
+      _ => translate_match(match_type, value, mask)?,
+    };
+    if val.is_empty() {
+      return Err("empty value".to_string());
+    }
+    Ok(OwnedMagicRule::new(
+      priority,
+      self.mime_type.to_string(),
+      indent_level,
+      start_off,
+      val.into(),
+      mask.into(),
+      word_len,
+      region_len,
+    ))
+  }
+
+  /// Returns the rules of a `<match>` and its children in depth-first order.
+  ///
+  /// A match whose children are all left out is left out as well,
+  /// as it would match more files than in Tika.
+  fn parse_match(
+    &mut self,
+    node: roxmltree::Node<'_, '_>,
+    priority: u32,
+    indent_level: u32,
+  ) -> Option<Vec<OwnedMagicRule>> {
+    let rule = match self.translate_match(node, priority, indent_level) {
+      Ok(rule) => rule,
+      Err(reason) => {
+        self.unsupported("match", reason);
+        return None;
+      },
+    };
+    let mut rules = vec![rule];
+    let mut has_children = false;
+    let mut any_child = false;
+    for child in node.children().filter(|n| n.has_tag_name("match")) {
+      has_children = true;
+      if let Some(child_rules) = self.parse_match(child, priority, indent_level + 1) {
+        any_child = true;
+        rules.extend(child_rules);
+      }
+    }
+    (any_child || !has_children).then_some(rules)
+  }
+
+  fn parse(&mut self, node: roxmltree::Node<'_, '_>) -> TikaMimeType {
+    let mut mime = TikaMimeType {
+      mime_type: self.mime_type.to_string(),
+      ..TikaMimeType::default()
+    };
+    for child in node.children().filter(roxmltree::Node::is_element) {
+      let element = child.tag_name().name();
+      match element {
+        "glob" => match child.attribute("pattern") {
+          Some(_) if child.attribute("isregex") == Some("true") => {
+            self.unsupported(element, "regular expressions are not supported".to_string());
+          },
+          Some(pattern) => mime.globs.push(pattern.to_string()),
+          None => self.unsupported(element, "missing pattern attribute".to_string()),
+        },
+        "magic" => {
+          let priority = child.attribute("priority").unwrap_or("50");
+          let Some(priority) = parse_number(priority) else {
+            self.unsupported(element, format!("invalid priority {priority:?}"));
+            continue;
+          };
+          for match_node in child.children().filter(|n| n.has_tag_name("match")) {
+            if let Some(rules) = self.parse_match(match_node, priority, 0) {
+              mime.magic_rules.extend(rules);
+            }
+          }
+        },
+        "alias" | "sub-class-of" => match child.attribute("type") {
+          Some(other) if element == "alias" => mime.aliases.push(other.to_string()),
+          Some(other) => mime.sub_class_of.push(other.to_string()),
+          None => self.unsupported(element, "missing type attribute".to_string()),
+        },
+        // Descriptions, links and XML roots do not affect magic detection
+        _ => {},
+      }
+    }
+    mime
+  }
+}
+
+/// Parses the `<mime-type>`s of Apache Tika's `tika-mimetypes.xml` or `custom-mimetypes.xml`.
+///
+/// `<match>` elements of the `string`, `stringignorecase`, `unicodeLE`, `unicodeBE`,
+/// `byte`, `big16`, `big32`, `little16`, `little32`, `host16` and `host32` types
+/// are translated with their offset ranges and masks.
+/// Regular expressions, `minShouldMatch` and invalid elements are reported and left out.
+///
+/// # Errors
+/// Returns an error if the XML is malformed or a `<mime-type>` has no type.
+pub fn parse_tika_mimetypes(xml: &str) -> Result<TikaImport, TikaMimetypesError> {
+  let doc = roxmltree::Document::parse_with_options(
+    xml,
+    roxmltree::ParsingOptions {
+      allow_dtd: true,
+      ..roxmltree::ParsingOptions::default()
+    },
+  )?;
+  let mut import = TikaImport::default();
+  for node in doc
+    .root_element()
+    .children()
+    .filter(|n| n.has_tag_name("mime-type"))
+  {
+    let mime_type = node
+      .attribute("type")
+      .ok_or(TikaMimetypesError::MissingType)?;
+    let mime = MimeTypeParser {
+      mime_type,
+      unsupported: &mut import.unsupported,
+    }
+    .parse(node);
+    import.mime_types.push(mime);
+  }
+  Ok(import)
+}
@@ -0,0 +1,111 @@
+use super::{parse_tika_mimetypes, TikaMimeType, TikaMimetypesError, TikaUnsupported};
+use crate::db::{BuildeableDbProvider, LoadResult, OwnedAlias, OwnedMagicRule, OwnedSubclass};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The errors that can occur while loading Tika MIME types files.
+#[derive(Error, Debug)]
+pub enum TikaProviderError {
+  /// A file could not be read
+  #[error("IO error")]
+  IoError {
+    /// The file that could not be read
+    file_path: PathBuf,
+    /// The underlying error
+    source: std::io::Error,
+  },
+  /// A file is malformed
+  #[error("Error parsing Tika MIME types file")]
+  TikaMimetypesError {
+    /// The malformed file
+    file_path: PathBuf,
+    /// The underlying error
+    source: TikaMimetypesError,
+  },
+}
+
+/// Loads the database from Apache Tika's MIME types files.
+pub trait TikaProviderExt {
+  /// Adds the magic rules, aliases and subclasses of parsed MIME types.
+  fn add_tika_mime_types(&mut self, mime_types: &[TikaMimeType]) -> LoadResult;
+  /// Attempts to load a Tika MIME types document.
+  ///
+  /// Returns the constructs that were left out along with the loaded entries.
+  ///
+  /// # Errors
+  /// Returns an error if the document is malformed, in which case nothing is loaded.
+  fn load_tika_mimetypes_str(
+    &mut self,
+    xml: &str,
+  ) -> Result<(LoadResult, Vec<TikaUnsupported>), TikaMimetypesError>;
+  /// Attempts to load a Tika MIME types file, like `tika-mimetypes.xml`.
+  ///
+  /// Load `custom-mimetypes.xml` afterwards to extend it like Tika does.
+  ///
+  /// # Errors
+  /// Returns an error if the file could not be read or is malformed.
+  fn load_tika_mimetypes_file(
+    &mut self,
+    file_path: &Path,
+  ) -> Result<(LoadResult, Vec<TikaUnsupported>), TikaProviderError>;
+}
+impl TikaProviderExt for BuildeableDbProvider<OwnedMagicRule, OwnedAlias, OwnedSubclass> {
+  fn add_tika_mime_types(&mut self, mime_types: &[TikaMimeType]) -> LoadResult {
+    let mut load_result = LoadResult {
+      magic_rules_num: 0,
+      aliases_num: 0,
+      subclasses_num: 0,
+    };
+    for mime in mime_types {
+      self
+        .magic_rules_mut()
+        .extend(mime.magic_rules.iter().cloned());
+      self.aliases_mut().extend(
+        mime
+          .aliases
+          .iter()
+          .map(|alias| OwnedAlias::new(alias.clone(), mime.mime_type.clone())),
+      );
+      self.subclasses_mut().extend(
+        mime
+          .sub_class_of
+          .iter()
+          .map(|parent| OwnedSubclass::new(mime.mime_type.clone(), parent.clone())),
+      );
+      load_result = load_result
+        + LoadResult {
+          magic_rules_num: mime.magic_rules.len(),
+          aliases_num: mime.aliases.len(),
+          subclasses_num: mime.sub_class_of.len(),
+        };
+    }
+    load_result
+  }
+
+  fn load_tika_mimetypes_str(
+    &mut self,
+    xml: &str,
+  ) -> Result<(LoadResult, Vec<TikaUnsupported>), TikaMimetypesError> {
+    let import = parse_tika_mimetypes(xml)?;
+    Ok((
+      self.add_tika_mime_types(&import.mime_types),
+      import.unsupported,
+    ))
+  }
+
+  fn load_tika_mimetypes_file(
+    &mut self,
+    file_path: &Path,
+  ) -> Result<(LoadResult, Vec<TikaUnsupported>), TikaProviderError> {
+    let xml = std::fs::read_to_string(file_path).map_err(|source| TikaProviderError::IoError {
+      file_path: file_path.to_owned(),
+      source,
+    })?;
+    self
+      .load_tika_mimetypes_str(&xml)
+      .map_err(|source| TikaProviderError::TikaMimetypesError {
+        file_path: file_path.to_owned(),
+        source,
+      })
+  }
+}
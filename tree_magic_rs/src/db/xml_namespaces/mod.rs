@@ -0,0 +1,5 @@
+mod parse_xml_namespaces;
+pub use parse_xml_namespaces::{parse_xml_namespaces, XmlNamespaceRule, XmlNamespacesParseError};
+
+mod xml_namespace_db;
+pub use xml_namespace_db::{XmlNamespaceDatabase, XmlNamespaceDatabaseError};
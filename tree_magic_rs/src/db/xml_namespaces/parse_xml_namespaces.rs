@@ -0,0 +1,57 @@
+use crate::db::globs::is_mime_type;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The errors that can occur while parsing an `XMLnamespaces` file.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum XmlNamespacesParseError {
+  /// A line is malformed
+  #[error("Error parsing line {line}")]
+  LineParseError {
+    /// The line number, starting at 1
+    line: usize,
+  },
+}
+
+/// The root element of XML documents of a MIME type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct XmlNamespaceRule {
+  /// The namespace of the root element, empty for none
+  pub namespace_uri: String,
+  /// The local name of the root element
+  pub local_name: String,
+  /// The MIME type
+  pub mime_type: String,
+}
+
+/// Parses a shared MIME info `XMLnamespaces` file of `namespaceURI localName type` lines.
+///
+/// Root elements without a namespace have an empty first field.
+///
+/// # Errors
+/// Returns the first malformed line.
+pub fn parse_xml_namespaces(text: &str) -> Result<Vec<XmlNamespaceRule>, XmlNamespacesParseError> {
+  let mut rules = Vec::new();
+  for (i, line) in text.lines().enumerate() {
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let mut fields = line.split(' ');
+    let (Some(namespace_uri), Some(local_name), Some(mime_type), None) =
+      (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+      return Err(XmlNamespacesParseError::LineParseError { line: i + 1 });
+    };
+    if local_name.is_empty() || !is_mime_type(mime_type) {
+      return Err(XmlNamespacesParseError::LineParseError { line: i + 1 });
+    }
+    rules.push(XmlNamespaceRule {
+      namespace_uri: namespace_uri.to_string(),
+      local_name: local_name.to_string(),
+      mime_type: mime_type.to_string(),
+    });
+  }
+  Ok(rules)
+}
@@ -0,0 +1,130 @@
+use super::parse_xml_namespaces::{
+  parse_xml_namespaces, XmlNamespaceRule, XmlNamespacesParseError,
+};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The errors that can occur while loading `XMLnamespaces` files.
+#[derive(Error, Debug)]
+pub enum XmlNamespaceDatabaseError {
+  /// A file could not be read
+  #[error("IO error")]
+  IoError {
+    /// The file that could not be read
+    file_path: PathBuf,
+    /// The underlying error
+    source: std::io::Error,
+  },
+  /// A file is malformed
+  #[error("Error parsing XMLnamespaces file")]
+  XmlNamespacesParseError {
+    /// The malformed file
+    file_path: PathBuf,
+    /// The underlying error
+    source: XmlNamespacesParseError,
+  },
+}
+
+/// The root elements that identify XML types, from the `XMLnamespaces` files
+/// of the shared MIME info database.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct XmlNamespaceDatabase {
+  rules: Vec<XmlNamespaceRule>,
+}
+impl XmlNamespaceDatabase {
+  /// Creates an empty database.
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { rules: Vec::new() }
+  }
+
+  /// The loaded rules, in loading order.
+  #[must_use]
+  pub fn rules(&self) -> &[XmlNamespaceRule] {
+    &self.rules
+  }
+
+  /// Adds rules and returns how many were added.
+  pub fn add_rules(&mut self, rules: impl IntoIterator<Item = XmlNamespaceRule>) -> usize {
+    let len = self.rules.len();
+    self.rules.extend(rules);
+    self.rules.len() - len
+  }
+
+  /// Loads the contents of an `XMLnamespaces` file.
+  ///
+  /// # Errors
+  /// Returns the first malformed line.
+  pub fn load_xml_namespaces_str(&mut self, text: &str) -> Result<usize, XmlNamespacesParseError> {
+    Ok(self.add_rules(parse_xml_namespaces(text)?))
+  }
+
+  /// Loads a shared MIME info `XMLnamespaces` file.
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be read or is malformed.
+  pub fn load_xml_namespaces_file(
+    &mut self,
+    file: &Path,
+  ) -> Result<usize, XmlNamespaceDatabaseError> {
+    let text =
+      std::fs::read_to_string(file).map_err(|source| XmlNamespaceDatabaseError::IoError {
+        file_path: file.to_path_buf(),
+        source,
+      })?;
+    self.load_xml_namespaces_str(&text).map_err(|source| {
+      XmlNamespaceDatabaseError::XmlNamespacesParseError {
+        file_path: file.to_path_buf(),
+        source,
+      }
+    })
+  }
+
+  /// Loads the `XMLnamespaces` files of the well known shared MIME info directories,
+  /// with the user directory loaded last so that it overrides the system ones.
+  ///
+  /// Missing files are skipped.
+  ///
+  /// # Errors
+  /// Returns the number of loaded rules and the errors of unreadable or malformed files.
+  pub fn load_xdg_xml_namespaces_default(
+    &mut self,
+  ) -> Result<usize, (usize, Vec<XmlNamespaceDatabaseError>)> {
+    let mut dirs = vec![
+      PathBuf::from("/usr/share/mime"),
+      PathBuf::from("/usr/local/share/mime"),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+      dirs.push(Path::new(&home).join(".local/share/mime"));
+    }
+    let mut errs = Vec::new();
+    let mut loaded = 0;
+    for dir in dirs {
+      let file = dir.join("XMLnamespaces");
+      if !file.exists() {
+        continue;
+      }
+      match self.load_xml_namespaces_file(&file) {
+        Ok(num) => loaded += num,
+        Err(err) => errs.push(err),
+      }
+    }
+    if !errs.is_empty() {
+      return Err((loaded, errs));
+    }
+    Ok(loaded)
+  }
+
+  /// Returns the MIME type of documents with the given root element.
+  ///
+  /// Rules loaded later win. An empty namespace matches root elements without one.
+  #[must_use]
+  pub fn match_root(&self, namespace_uri: &str, local_name: &str) -> Option<&str> {
+    self
+      .rules
+      .iter()
+      .rev()
+      .find(|rule| rule.namespace_uri == namespace_uri && rule.local_name == local_name)
+      .map(|rule| rule.mime_type.as_str())
+  }
+}
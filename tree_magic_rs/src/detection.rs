@@ -0,0 +1,180 @@
+//! Detected types together with the charset of text.
+use crate::{read_bytes, MediaType, MimeDatabase, TextClassifier, TextEncoding, TextVerdict};
+use std::{fmt, path::Path};
+
+/// The character encoding of text, for the `charset` parameter of a `Content-Type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Charset {
+  /// 7-bit ASCII
+  UsAscii,
+  /// UTF-8, with or without byte order mark
+  Utf8,
+  /// UTF-16, little-endian
+  Utf16Le,
+  /// UTF-16, big-endian
+  Utf16Be,
+  /// UTF-32, little-endian
+  Utf32Le,
+  /// UTF-32, big-endian
+  Utf32Be,
+  /// Latin-1, guessed for 8-bit text without bytes in `0x80..=0x9f`
+  Iso8859_1,
+  /// The Windows variant of Latin-1, guessed for other 8-bit text
+  Windows1252,
+  /// A legacy encoding guessed with the `encoding` feature, by its WHATWG name
+  Other(&'static str),
+}
+impl Charset {
+  /// The name of the charset, like `utf-8`.
+  #[must_use]
+  pub const fn name(self) -> &'static str {
+    match self {
+      Self::UsAscii => "us-ascii",
+      Self::Utf8 => "utf-8",
+      Self::Utf16Le => "utf-16le",
+      Self::Utf16Be => "utf-16be",
+      Self::Utf32Le => "utf-32le",
+      Self::Utf32Be => "utf-32be",
+      Self::Iso8859_1 => "iso-8859-1",
+      Self::Windows1252 => "windows-1252",
+      Self::Other(name) => name,
+    }
+  }
+
+  /// The charset of text classified by a [`TextClassifier`], `None` for binary data.
+  #[must_use]
+  pub fn from_verdict(verdict: TextVerdict, bytes: &[u8]) -> Option<Self> {
+    Some(match verdict.encoding()? {
+      TextEncoding::Ascii => Self::UsAscii,
+      TextEncoding::Utf8 => Self::Utf8,
+      TextEncoding::Utf16Le => Self::Utf16Le,
+      TextEncoding::Utf16Be => Self::Utf16Be,
+      TextEncoding::Utf32Le => Self::Utf32Le,
+      TextEncoding::Utf32Be => Self::Utf32Be,
+      TextEncoding::Unknown8Bit => Self::guess_legacy(bytes),
+    })
+  }
+
+  /// Guesses the encoding of 8-bit text that is not UTF-8
+  fn guess_legacy(bytes: &[u8]) -> Self {
+    #[cfg(feature = "encoding")]
+    {
+      let mut detector = chardetng::EncodingDetector::new();
+      detector.feed(bytes, true);
+      let name = detector.guess(None, false).name();
+      if name != "windows-1252" {
+        return Self::Other(name);
+      }
+    }
+    // The C1 controls of Latin-1 are printable characters in Windows-1252
+    if bytes.iter().any(|byte| (0x80..=0x9f).contains(byte)) {
+      Self::Windows1252
+    } else {
+      Self::Iso8859_1
+    }
+  }
+}
+impl fmt::Display for Charset {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.name())
+  }
+}
+#[cfg(feature = "serde")]
+impl serde::Serialize for Charset {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(self.name())
+  }
+}
+
+/// Detects the charset of text with the default [`TextClassifier`], `None` for binary data.
+///
+/// # Examples
+/// ```rust
+/// use tree_magic_rs::{detect_charset, Charset};
+///
+/// assert_eq!(detect_charset("grüße".as_bytes()), Some(Charset::Utf8));
+/// assert_eq!(detect_charset(b"gr\xfc\xdfe"), Some(Charset::Iso8859_1));
+/// assert_eq!(detect_charset(b"\x93quoted\x94"), Some(Charset::Windows1252));
+/// assert_eq!(detect_charset(b"\0\x01\x02"), None);
+/// ```
+#[must_use]
+pub fn detect_charset(bytes: &[u8]) -> Option<Charset> {
+  Charset::from_verdict(TextClassifier::new().classify(bytes), bytes)
+}
+
+/// A detected type with the charset of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Detection<'a> {
+  /// The type of the contents
+  pub mime_type: &'a str,
+  /// The charset, for types that are `text/plain` or a subclass of it
+  pub charset: Option<Charset>,
+}
+impl<'a> Detection<'a> {
+  /// The type with a `charset` parameter if the charset is known.
+  #[must_use]
+  pub fn media_type(&self) -> MediaType<'a> {
+    let media_type = MediaType::from_essence(self.mime_type);
+    match self.charset {
+      Some(charset) => media_type.with_param("charset", charset.name()),
+      None => media_type,
+    }
+  }
+}
+impl fmt::Display for Detection<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.media_type().fmt(f)
+  }
+}
+
+impl MimeDatabase {
+  /// The type and charset of a byte stream.
+  ///
+  /// The charset is detected once the type is known to be `text/plain` or a subclass of it,
+  /// with the [`TextClassifier`] of the database. ASCII text is `utf-8`,
+  /// use [`detect_charset`] to tell it apart.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use tree_magic_rs::{db::OwnedBuildableDb, Charset, MimeDatabase};
+  ///
+  /// let db = MimeDatabase::from_provider(&OwnedBuildableDb::new());
+  /// let detection = db.detection_from_u8("¡Hola!".as_bytes());
+  /// assert_eq!(detection.charset, Some(Charset::Utf8));
+  /// assert_eq!(detection.to_string(), "text/plain; charset=utf-8");
+  /// ```
+  #[must_use]
+  pub fn detection_from_u8(&self, bytes: &[u8]) -> Detection<'_> {
+    let mime_type = self.from_u8(bytes);
+    let charset = if self.is_a(mime_type, "text/plain") {
+      self.charset(bytes)
+    } else {
+      None
+    };
+    Detection { mime_type, charset }
+  }
+
+  /// The type and charset of a file.
+  ///
+  /// Returns `None` if the file is not found or cannot be opened.
+  /// See [`detection_from_u8`](Self::detection_from_u8).
+  #[must_use]
+  pub fn detection_from_filepath(&self, filepath: &Path) -> Option<Detection<'_>> {
+    let mime_type = self.from_filepath(filepath)?;
+    let charset = if self.is_a(mime_type, "text/plain") {
+      self.charset(&read_bytes(filepath, 2048).ok()?)
+    } else {
+      None
+    };
+    Some(Detection { mime_type, charset })
+  }
+
+  fn charset(&self, bytes: &[u8]) -> Option<Charset> {
+    match Charset::from_verdict(self.text_classifier().classify(bytes), bytes)? {
+      // Only a prefix of a file is read, so ASCII text is reported as the UTF-8 it is a subset of
+      Charset::UsAscii => Some(Charset::Utf8),
+      charset => Some(charset),
+    }
+  }
+}
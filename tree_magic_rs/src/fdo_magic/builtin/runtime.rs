@@ -1,8 +1,9 @@
 ///! Attempts to load the magic database from well-known directories.
 use std::fs::File;
+use std::hash::Hasher;
 use std::io::Read;
 
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHasher};
 use petgraph::prelude::DiGraph;
 
 use super::MagicRule;
@@ -60,6 +61,20 @@ impl LoadedDatabase {
   pub fn subclasses(&self) -> &str {
     &self.subclass_string
   }
+
+  /// A checksum of the loaded files
+  pub fn checksum(&self) -> u64 {
+    let mut hasher = FnvHasher::default();
+    for rules in &self.runtime_rules {
+      hasher.write_usize(rules.len());
+      hasher.write(rules);
+    }
+    for string in [&self.alias_string, &self.subclass_string] {
+      hasher.write_usize(string.len());
+      hasher.write(string.as_bytes());
+    }
+    hasher.finish()
+  }
 }
 
 /// Load a number of files at `paths` and concatenate them together with a newline
@@ -1,18 +1,39 @@
+use super::MagicMatch;
 use petgraph::prelude::*;
+use std::borrow::Cow;
+
+/// Swaps the bytes of each word of a host-endian value,
+/// which magic rules store in big-endian order, on little-endian hosts
+fn to_host_order(bytes: &[u8], word_len: u32) -> Cow<'_, [u8]> {
+    let word_len = word_len as usize;
+    if cfg!(target_endian = "big")
+        || !matches!(word_len, 2 | 4)
+        || !bytes.len().is_multiple_of(word_len)
+    {
+        return Cow::Borrowed(bytes);
+    }
+    bytes
+        .chunks_exact(word_len)
+        .flat_map(|word| word.iter().rev().copied())
+        .collect()
+}
+
+fn from_u8_singlerule<R: MagicMatch>(file: &[u8], rule: &R) -> bool {
+    let rule_val = to_host_order(rule.val(), rule.word_len());
+    let rule_mask = rule.mask().map(|mask| to_host_order(mask, rule.word_len()));
 
-fn from_u8_singlerule(file: &[u8], rule: &super::MagicRule) -> bool {
     // Check if we're even in bounds
-    let bound_min = rule.start_off as usize;
-    let bound_max = rule.start_off as usize + rule.val.len() + rule.region_len as usize;
+    let bound_min = rule.start_off() as usize;
+    let bound_max = rule.start_off() as usize + rule_val.len() + rule.region_len() as usize;
 
     if (file.len()) < bound_max {
         return false;
     }
 
-    if rule.region_len == 0 {
+    if rule.region_len() == 0 {
         //println!("Region == 0");
 
-        match rule.mask {
+        match rule_mask.as_deref() {
             None => {
                 //println!("\tMask == None");
                 let x: Vec<u8> = file
@@ -23,9 +44,9 @@ fn from_u8_singlerule(file: &[u8], rule: &super::MagicRule) -> bool {
                     .collect();
                 //println!("\t{:?} / {:?}", x, rule.val);
                 //println!("\tIndent: {}, Start: {}", rule.indent_level, rule.start_off);
-                return rule.val.iter().eq(x.iter());
+                return rule_val.iter().eq(x.iter());
             }
-            Some(ref mask) => {
+            Some(mask) => {
                 //println!("\tMask == Some, len == {}", mask.len());
                 //println!("\tIndent: {}, Start: {}", rule.indent_level, rule.start_off);
                 let mut x: Vec<u8> = file
@@ -34,7 +55,7 @@ fn from_u8_singlerule(file: &[u8], rule: &super::MagicRule) -> bool {
                     .take(bound_max - bound_min) // Take until end of area - region length
                     .copied()
                     .collect(); // Convert to vector
-                let mut val: Vec<u8> = rule.val.iter().copied().collect();
+                let mut val = rule_val.to_vec();
                 //println!("\t{:?} / {:?}", x, rule.val);
 
                 assert_eq!(x.len(), mask.len());
@@ -44,7 +65,7 @@ fn from_u8_singlerule(file: &[u8], rule: &super::MagicRule) -> bool {
                 }
                 //println!("\t & {:?} => {:?}", mask, x);
 
-                return rule.val.iter().eq(x.iter());
+                return rule_val.iter().eq(x.iter());
             }
         }
     } else {
@@ -63,21 +84,20 @@ fn from_u8_singlerule(file: &[u8], rule: &super::MagicRule) -> bool {
 
         // Search down until we find a hit
         let mut y = Vec::<u8>::with_capacity(testarea.len());
-        for x in testarea.windows(rule.val.len()) {
+        for x in testarea.windows(rule_val.len()) {
             y.clear();
 
             // Apply mask to value
-            let rule_mask = &rule.mask;
-            match *rule_mask {
-                Some(ref mask) => {
-                    for i in 0..rule.val.len() {
+            match rule_mask.as_deref() {
+                Some(mask) => {
+                    for i in 0..rule_val.len() {
                         y.push(x[i] & mask[i]);
                     }
                 }
                 None => y = x.to_vec(),
             }
 
-            if y.iter().eq(rule.val.iter()) {
+            if y.iter().eq(rule_val.iter()) {
                 return true;
             }
         }
@@ -88,10 +108,10 @@ fn from_u8_singlerule(file: &[u8], rule: &super::MagicRule) -> bool {
 
 /// Test every given rule by walking graph
 /// TODO: Not loving the code duplication here.
-pub fn from_u8_walker(
+pub fn from_u8_walker<R: MagicMatch>(
     file: &[u8],
     mimetype: &str,
-    graph: &DiGraph<super::MagicRule, u32>,
+    graph: &DiGraph<R, u32>,
     node: NodeIndex,
     isroot: bool,
 ) -> bool {
@@ -1,5 +1,7 @@
 /// Common routines for all fdo_magic parsers
 pub mod builtin;
+/// Magic rules from a `DbProvider`
+pub mod provider;
 
 #[derive(Debug, Clone)]
 pub struct MagicRule<'a> {
@@ -11,5 +13,51 @@ pub struct MagicRule<'a> {
   pub region_len: u32,
 }
 
+/// The parts of a magic rule needed to test it against a file
+pub trait MagicMatch {
+  fn start_off(&self) -> u32;
+  fn val(&self) -> &[u8];
+  fn mask(&self) -> Option<&[u8]>;
+  /// The size of the words of a host-endian value, which is stored in big-endian order
+  fn word_len(&self) -> u32;
+  fn region_len(&self) -> u32;
+}
+
+impl MagicMatch for MagicRule<'_> {
+  fn start_off(&self) -> u32 {
+    self.start_off
+  }
+  fn val(&self) -> &[u8] {
+    self.val
+  }
+  fn mask(&self) -> Option<&[u8]> {
+    self.mask
+  }
+  fn word_len(&self) -> u32 {
+    self.word_len
+  }
+  fn region_len(&self) -> u32 {
+    self.region_len
+  }
+}
+
+impl MagicMatch for crate::db::OwnedMagicRule {
+  fn start_off(&self) -> u32 {
+    crate::db::MagicRule::start_off(self)
+  }
+  fn val(&self) -> &[u8] {
+    crate::db::MagicRule::val(self)
+  }
+  fn mask(&self) -> Option<&[u8]> {
+    Some(crate::db::MagicRule::mask(self)).filter(|mask| !mask.is_empty())
+  }
+  fn word_len(&self) -> u32 {
+    crate::db::MagicRule::word_len(self)
+  }
+  fn region_len(&self) -> u32 {
+    crate::db::MagicRule::region_len(self)
+  }
+}
+
 pub mod check;
 pub mod ruleset;
@@ -0,0 +1,77 @@
+use crate::{db::DbProvider, db::OwnedMagicRule, fdo_magic, read_bytes, MIME};
+use fnv::FnvHashMap;
+use petgraph::prelude::*;
+use std::path::Path;
+
+/// Checks magic rules copied out of a `DbProvider`
+pub struct ProviderMagic {
+  rules: FnvHashMap<String, DiGraph<OwnedMagicRule, u32>>,
+  aliases: FnvHashMap<String, String>,
+  subclasses: Vec<(String, String)>,
+}
+
+impl ProviderMagic {
+  pub fn new<'a, P: DbProvider<'a> + ?Sized>(provider: &'a P) -> Self {
+    let aliases = super::init::get_aliaslist(provider);
+    Self {
+      rules: super::init::get_rules(provider),
+      subclasses: super::init::get_subclasses(provider, &aliases),
+      aliases,
+    }
+  }
+}
+
+impl crate::Checker for ProviderMagic {
+  /// Test against all rules
+  fn from_u8(&self, file: &[u8], mimetype: &str) -> bool {
+    let Some(graph) = self.rules.get(mimetype) else {
+      return false; // No rule for this mime
+    };
+
+    // Check all rulesets
+    graph
+      .externals(Incoming)
+      .any(|x| fdo_magic::check::from_u8_walker(file, mimetype, graph, x, true))
+  }
+
+  fn from_filepath(&self, filepath: &Path, mimetype: &str) -> bool {
+    let Some(graph) = self.rules.get(mimetype) else {
+      return false; // No rule for this mime
+    };
+
+    // Get # of bytes to read
+    let scanlen = graph
+      .node_weights()
+      .map(|rule| {
+        use crate::db::MagicRule;
+        rule.start_off() as usize + rule.val().len() + rule.region_len() as usize
+      })
+      .max()
+      .unwrap_or(0);
+
+    let Ok(b) = read_bytes(filepath, scanlen) else {
+      return false;
+    };
+    self.from_u8(b.as_slice(), mimetype)
+  }
+
+  fn get_supported(&self) -> Vec<MIME<'_>> {
+    self.rules.keys().map(String::as_str).collect()
+  }
+
+  fn get_subclasses(&self) -> Vec<(MIME<'_>, MIME<'_>)> {
+    self
+      .subclasses
+      .iter()
+      .map(|(parent, child)| (parent.as_str(), child.as_str()))
+      .collect()
+  }
+
+  fn get_aliaslist(&self) -> FnvHashMap<MIME<'_>, MIME<'_>> {
+    self
+      .aliases
+      .iter()
+      .map(|(alias, name)| (alias.as_str(), name.as_str()))
+      .collect()
+  }
+}
@@ -0,0 +1,54 @@
+use crate::db::{Alias, DbProvider, MagicRule, OwnedMagicRule, Subclass};
+use fnv::FnvHashMap;
+use petgraph::prelude::*;
+
+/// Groups the magic rules by MIME type and links each rule to the rule it is nested in
+pub fn get_rules<'a, P: DbProvider<'a> + ?Sized>(
+  provider: &'a P,
+) -> FnvHashMap<String, DiGraph<OwnedMagicRule, u32>> {
+  let mut out = FnvHashMap::<String, DiGraph<OwnedMagicRule, u32>>::default();
+  // The last rule seen on each indent level, per MIME type
+  let mut rulestacks = FnvHashMap::<String, Vec<(u32, NodeIndex)>>::default();
+
+  for rule in provider.iter_magic_rules() {
+    let graph = out.entry(rule.mime_type().to_string()).or_default();
+    let rulestack = rulestacks.entry(rule.mime_type().to_string()).or_default();
+    let node = graph.add_node(OwnedMagicRule::from_rule(rule));
+
+    while let Some((indent_level, parent)) = rulestack.last().copied() {
+      if indent_level < rule.indent_level() {
+        graph.add_edge(parent, node, 1);
+        break;
+      }
+      rulestack.pop();
+    }
+    rulestack.push((rule.indent_level(), node));
+  }
+  out
+}
+
+pub fn get_aliaslist<'a, P: DbProvider<'a> + ?Sized>(
+  provider: &'a P,
+) -> FnvHashMap<String, String> {
+  provider
+    .iter_aliases()
+    .map(|alias| (alias.alias().to_string(), alias.name().to_string()))
+    .collect()
+}
+
+/// Get list of parent -> child subclass links
+pub fn get_subclasses<'a, P: DbProvider<'a> + ?Sized>(
+  provider: &'a P,
+  aliases: &FnvHashMap<String, String>,
+) -> Vec<(String, String)> {
+  let resolve = |mime: &str| {
+    aliases
+      .get(mime)
+      .cloned()
+      .unwrap_or_else(|| mime.to_string())
+  };
+  provider
+    .iter_subclasses()
+    .map(|subclass| (resolve(subclass.parent()), resolve(subclass.mime_type())))
+    .collect()
+}
@@ -0,0 +1,5 @@
+//! Magic rules loaded from any `DbProvider`
+
+pub mod check;
+pub use check::ProviderMagic;
+pub mod init;
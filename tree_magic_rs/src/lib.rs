@@ -13,10 +13,44 @@ TODO: COPY README
 
 mod basetype;
 mod fdo_magic;
+mod ole_storage;
+mod xml_root;
+mod zip_container;
 
+pub mod codegen;
 pub mod db;
 
+mod media_type;
+pub use media_type::{MediaType, MediaTypeParseError, MimeLike};
+
+#[cfg(feature = "mime")]
+mod mime_interop;
+
 mod tree_magic;
 pub use tree_magic::MimeDatabase;
 
-use tree_magic::{read_bytes, Checker, MIME};
+mod text;
+pub use text::{is_text, TextClassifier, TextEncoding, TextVerdict};
+
+mod detection;
+pub use detection::{detect_charset, Charset, Detection};
+
+mod verdict;
+pub use verdict::{Verdict, VerdictKind};
+
+mod policy;
+pub use policy::{
+  Policy, PolicyAction, PolicyDecision, PolicyError, PolicyRule, Selector, EXECUTABLE_TYPES,
+};
+
+mod scan;
+pub use scan::{
+  Scan, ScanEntry, ScanError, ScanOptions, ScanSummary, SymlinkPolicy, TypeTotals,
+};
+
+#[cfg(feature = "cache")]
+mod cache;
+#[cfg(feature = "cache")]
+pub use cache::{DetectionCache, DetectionCacheError};
+
+use tree_magic::{read_bytes, Checker, Identifier, MIME};
@@ -0,0 +1,360 @@
+//! Structured media types as described in RFC 6838.
+use nom::{
+  branch::alt,
+  bytes::complete::{escaped_transform, is_not, take_while, take_while1, take_while_m_n},
+  character::complete::{anychar, char, satisfy},
+  combinator::{all_consuming, map, recognize, value},
+  multi::many0,
+  sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+  IResult,
+};
+use std::{
+  borrow::Cow,
+  fmt,
+  hash::{Hash, Hasher},
+  str::FromStr,
+};
+use thiserror::Error;
+
+/// Maps structured syntax suffixes to the media type they are based on.
+///
+/// https://www.iana.org/assignments/media-type-structured-suffix/media-type-structured-suffix.xhtml
+const SUFFIX_PARENTS: [(&str, &str); 10] = [
+  ("xml", "application/xml"),
+  ("json", "application/json"),
+  ("zip", "application/zip"),
+  ("gzip", "application/gzip"),
+  ("zstd", "application/zstd"),
+  ("cbor", "application/cbor"),
+  ("json-seq", "application/json-seq"),
+  ("cbor-seq", "application/cbor-seq"),
+  ("sqlite3", "application/vnd.sqlite3"),
+  ("jwt", "application/jwt"),
+];
+
+/// The error returned when a string is not a valid media type.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid media type {input:?} at byte {offset}")]
+pub struct MediaTypeParseError {
+  /// The string that failed to parse
+  pub input: String,
+  /// The byte offset at which parsing failed
+  pub offset: usize,
+}
+
+/// A parsed media type like `image/svg+xml; charset=utf-8`.
+///
+/// Type, subtype and parameter names compare case-insensitively,
+/// parameter values compare exactly.
+///
+/// # Examples
+/// ```rust
+/// use tree_magic_rs::MediaType;
+///
+/// let svg = MediaType::parse("Image/SVG+XML; charset=\"utf-8\"").unwrap();
+/// assert_eq!(svg.type_(), "Image");
+/// assert_eq!(svg.subtype(), "SVG+XML");
+/// assert_eq!(svg.suffix(), Some("XML"));
+/// assert_eq!(svg.param("CharSet"), Some("utf-8"));
+/// assert_eq!(svg.suffix_parent(), Some("application/xml"));
+/// assert_eq!(svg, "image/svg+xml; charset=utf-8");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MediaType<'a> {
+  essence: Cow<'a, str>,
+  slash: usize,
+  params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> MediaType<'a> {
+  /// Parses a media type following the RFC 6838 grammar,
+  /// optionally followed by RFC 2045 parameters.
+  ///
+  /// # Errors
+  /// Returns an error if `input` is not a valid media type.
+  pub fn parse(input: &'a str) -> Result<Self, MediaTypeParseError> {
+    match all_consuming(media_type)(input) {
+      Ok((_, (essence, params))) => Ok(Self {
+        slash: essence.find('/').unwrap_or(essence.len()),
+        essence: Cow::Borrowed(essence),
+        params: params
+          .into_iter()
+          .map(|(name, value)| (Cow::Borrowed(name), value))
+          .collect(),
+      }),
+      Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(MediaTypeParseError {
+        input: input.to_string(),
+        offset: input.len() - e.input.len(),
+      }),
+      Err(nom::Err::Incomplete(_)) => Err(MediaTypeParseError {
+        input: input.to_string(),
+        offset: input.len(),
+      }),
+    }
+  }
+
+  /// Wraps a MIME type name that is already known to be valid,
+  /// like the nodes of the type graph.
+  pub(crate) fn from_essence(essence: &'a str) -> Self {
+    Self {
+      slash: essence.find('/').unwrap_or(essence.len()),
+      essence: Cow::Borrowed(essence),
+      params: Vec::new(),
+    }
+  }
+
+  /// The top-level type, e.g. `image` in `image/svg+xml`.
+  #[must_use]
+  pub fn type_(&self) -> &str {
+    &self.essence[..self.slash]
+  }
+
+  /// The subtype including any suffix, e.g. `svg+xml` in `image/svg+xml`.
+  #[must_use]
+  pub fn subtype(&self) -> &str {
+    self.essence.get(self.slash + 1..).unwrap_or("")
+  }
+
+  /// The structured syntax suffix, e.g. `xml` in `image/svg+xml`.
+  #[must_use]
+  pub fn suffix(&self) -> Option<&str> {
+    self
+      .subtype()
+      .rsplit_once('+')
+      .map(|(_, suffix)| suffix)
+      .filter(|suffix| !suffix.is_empty())
+  }
+
+  /// The media type without parameters, e.g. `image/svg+xml`.
+  #[must_use]
+  pub fn essence(&self) -> &str {
+    &self.essence
+  }
+
+  /// The value of the parameter `name`, looked up case-insensitively.
+  #[must_use]
+  pub fn param(&self, name: &str) -> Option<&str> {
+    self
+      .params
+      .iter()
+      .find(|(n, _)| n.eq_ignore_ascii_case(name))
+      .map(|(_, v)| v.as_ref())
+  }
+
+  /// All parameters in the order they were given.
+  pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+    self.params.iter().map(|(n, v)| (n.as_ref(), v.as_ref()))
+  }
+
+  /// Adds a parameter, replacing any existing parameter with the same name.
+  #[must_use]
+  pub fn with_param(
+    mut self,
+    name: impl Into<Cow<'a, str>>,
+    value: impl Into<Cow<'a, str>>,
+  ) -> Self {
+    let name = name.into();
+    self.params.retain(|(n, _)| !n.eq_ignore_ascii_case(&name));
+    self.params.push((name, value.into()));
+    self
+  }
+
+  /// The generic media type this type's suffix is based on,
+  /// e.g. `application/xml` for `image/svg+xml`.
+  ///
+  /// Returns `None` if there is no suffix, the suffix is unknown
+  /// or this type is the generic type itself.
+  #[must_use]
+  pub fn suffix_parent(&self) -> Option<&'static str> {
+    let suffix = self.suffix()?;
+    SUFFIX_PARENTS
+      .iter()
+      .find(|(s, _)| s.eq_ignore_ascii_case(suffix))
+      .map(|(_, parent)| *parent)
+      .filter(|parent| !parent.eq_ignore_ascii_case(self.essence()))
+  }
+
+  /// Converts all borrowed parts into owned strings.
+  #[must_use]
+  pub fn into_owned(self) -> MediaType<'static> {
+    MediaType {
+      essence: Cow::Owned(self.essence.into_owned()),
+      slash: self.slash,
+      params: self
+        .params
+        .into_iter()
+        .map(|(n, v)| (Cow::Owned(n.into_owned()), Cow::Owned(v.into_owned())))
+        .collect(),
+    }
+  }
+}
+
+impl PartialEq for MediaType<'_> {
+  fn eq(&self, other: &MediaType<'_>) -> bool {
+    self.essence.eq_ignore_ascii_case(&other.essence)
+      && self.params.len() == other.params.len()
+      && self.params().all(|(n, v)| other.param(n) == Some(v))
+  }
+}
+impl Eq for MediaType<'_> {}
+
+impl PartialEq<str> for MediaType<'_> {
+  fn eq(&self, other: &str) -> bool {
+    MediaType::parse(other).is_ok_and(|other| *self == other)
+  }
+}
+impl PartialEq<&str> for MediaType<'_> {
+  fn eq(&self, other: &&str) -> bool {
+    *self == **other
+  }
+}
+
+impl Hash for MediaType<'_> {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    // Parameters are unordered, so only the essence is hashed
+    for b in self.essence.bytes() {
+      state.write_u8(b.to_ascii_lowercase());
+    }
+  }
+}
+
+impl fmt::Display for MediaType<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.essence)?;
+    for (name, value) in self.params() {
+      if !value.is_empty() && value.chars().all(is_token_char) {
+        write!(f, "; {name}={value}")?;
+      } else {
+        write!(f, "; {name}=\"")?;
+        for c in value.chars() {
+          if c == '"' || c == '\\' {
+            f.write_str("\\")?;
+          }
+          write!(f, "{c}")?;
+        }
+        f.write_str("\"")?;
+      }
+    }
+    Ok(())
+  }
+}
+
+impl FromStr for MediaType<'static> {
+  type Err = MediaTypeParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    MediaType::parse(s).map(MediaType::into_owned)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MediaType<'_> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MediaType<'static> {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+  }
+}
+
+/// Anything that names a MIME type in the `match_*` functions.
+///
+/// Parameters are ignored and names are compared case-insensitively.
+pub trait MimeLike {
+  /// The MIME type without parameters, e.g. `image/svg+xml`.
+  #[must_use]
+  fn essence(&self) -> Cow<'_, str>;
+}
+impl MimeLike for str {
+  fn essence(&self) -> Cow<'_, str> {
+    Cow::Borrowed(self.split(';').next().unwrap_or("").trim())
+  }
+}
+impl MimeLike for String {
+  fn essence(&self) -> Cow<'_, str> {
+    self.as_str().essence()
+  }
+}
+impl MimeLike for MediaType<'_> {
+  fn essence(&self) -> Cow<'_, str> {
+    Cow::Borrowed(self.essence())
+  }
+}
+impl<T: MimeLike + ?Sized> MimeLike for &T {
+  fn essence(&self) -> Cow<'_, str> {
+    (**self).essence()
+  }
+}
+
+fn is_restricted_name_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c)
+}
+
+/// RFC 2045 token characters
+fn is_token_char(c: char) -> bool {
+  c.is_ascii_graphic() && !"()<>@,;:\\\"/[]?=".contains(c)
+}
+
+const fn is_whitespace(c: char) -> bool {
+  c == ' ' || c == '\t'
+}
+
+/// restricted-name = restricted-name-first *126restricted-name-chars
+fn restricted_name(input: &str) -> IResult<&str, &str> {
+  recognize(pair(
+    satisfy(|c| c.is_ascii_alphanumeric()),
+    take_while_m_n(0, 126, is_restricted_name_char),
+  ))(input)
+}
+
+fn quoted_string(input: &str) -> IResult<&str, Cow<'_, str>> {
+  alt((
+    // escaped_transform fails on empty input
+    value(Cow::Borrowed(""), pair(char('"'), char('"'))),
+    map(
+      delimited(
+        char('"'),
+        escaped_transform(is_not("\\\""), '\\', anychar),
+        char('"'),
+      ),
+      Cow::Owned,
+    ),
+  ))(input)
+}
+
+fn parameter(input: &str) -> IResult<&str, (&str, Cow<'_, str>)> {
+  preceded(
+    tuple((
+      take_while(is_whitespace),
+      char(';'),
+      take_while(is_whitespace),
+    )),
+    separated_pair(
+      take_while1(is_token_char),
+      char('='),
+      alt((
+        map(take_while1(is_token_char), Cow::Borrowed),
+        quoted_string,
+      )),
+    ),
+  )(input)
+}
+
+#[allow(clippy::type_complexity)]
+fn media_type(input: &str) -> IResult<&str, (&str, Vec<(&str, Cow<'_, str>)>)> {
+  preceded(
+    take_while(is_whitespace),
+    terminated(
+      pair(
+        recognize(separated_pair(restricted_name, char('/'), restricted_name)),
+        many0(parameter),
+      ),
+      take_while(is_whitespace),
+    ),
+  )(input)
+}
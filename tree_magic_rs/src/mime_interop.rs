@@ -0,0 +1,99 @@
+//! Interoperability with the `mime` and `http` crates.
+use crate::{MediaType, MimeDatabase, MimeLike};
+use std::{borrow::Cow, path::Path};
+
+impl MimeLike for mime::Mime {
+  fn essence(&self) -> Cow<'_, str> {
+    Cow::Borrowed(self.essence_str())
+  }
+}
+
+impl TryFrom<&MediaType<'_>> for mime::Mime {
+  type Error = mime::FromStrError;
+
+  fn try_from(media_type: &MediaType<'_>) -> Result<Self, Self::Error> {
+    media_type.to_string().parse()
+  }
+}
+
+impl TryFrom<MediaType<'_>> for mime::Mime {
+  type Error = mime::FromStrError;
+
+  fn try_from(media_type: MediaType<'_>) -> Result<Self, Self::Error> {
+    Self::try_from(&media_type)
+  }
+}
+
+impl From<&mime::Mime> for MediaType<'static> {
+  fn from(mime: &mime::Mime) -> Self {
+    mime
+      .params()
+      .fold(
+        MediaType::from_essence(mime.essence_str()),
+        |media_type, (name, value)| media_type.with_param(name.as_str(), value.as_str()),
+      )
+      .into_owned()
+  }
+}
+
+/// Converts a detected type, falling back to `application/octet-stream`
+/// for names the `mime` crate rejects.
+fn to_mime(mimetype: &str) -> mime::Mime {
+  mimetype.parse().unwrap_or(mime::APPLICATION_OCTET_STREAM)
+}
+
+#[cfg(feature = "http")]
+fn content_type(detection: &crate::Detection) -> http::HeaderValue {
+  http::HeaderValue::from_str(&detection.to_string())
+    .unwrap_or(http::HeaderValue::from_static("application/octet-stream"))
+}
+
+impl MimeDatabase {
+  /// Gets the type of a file from a byte stream as a [`mime::Mime`].
+  ///
+  /// See [`from_u8`](Self::from_u8).
+  #[must_use]
+  pub fn mime_from_u8(&self, bytes: &[u8]) -> mime::Mime {
+    to_mime(self.from_u8(bytes))
+  }
+
+  /// Gets the type of a file from a filepath as a [`mime::Mime`].
+  ///
+  /// See [`from_filepath`](Self::from_filepath).
+  #[must_use]
+  pub fn mime_from_filepath(&self, filepath: &Path) -> Option<mime::Mime> {
+    self.from_filepath(filepath).map(to_mime)
+  }
+
+  /// Builds a `Content-Type` header value for a byte stream.
+  ///
+  /// Text types get a `charset` parameter if it is known,
+  /// see [`detection_from_u8`](Self::detection_from_u8).
+  ///
+  /// # Examples
+  /// ```rust
+  /// use http::header::CONTENT_TYPE;
+  ///
+  /// let db = tree_magic_rs::MimeDatabase::new();
+  /// let mut headers = http::HeaderMap::new();
+  /// headers.insert(CONTENT_TYPE, db.content_type_from_u8(b"Hello world!"));
+  /// assert_eq!(headers[CONTENT_TYPE], "text/plain; charset=utf-8");
+  /// ```
+  #[cfg(feature = "http")]
+  #[must_use]
+  pub fn content_type_from_u8(&self, bytes: &[u8]) -> http::HeaderValue {
+    content_type(&self.detection_from_u8(bytes))
+  }
+
+  /// Builds a `Content-Type` header value for a file.
+  ///
+  /// Returns `None` if the file is not found or cannot be opened.
+  /// See [`content_type_from_u8`](Self::content_type_from_u8).
+  #[cfg(feature = "http")]
+  #[must_use]
+  pub fn content_type_from_filepath(&self, filepath: &Path) -> Option<http::HeaderValue> {
+    self
+      .detection_from_filepath(filepath)
+      .map(|detection| content_type(&detection))
+  }
+}
@@ -0,0 +1,222 @@
+use super::{CLSID_TYPES, MSI, MS_OUTLOOK, STREAM_TYPES, TYPES};
+use crate::{Identifier, MIME};
+use fnv::FnvHashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const SIGNATURE: &[u8] = b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1";
+/// Sector numbers from here on mark free sectors and the ends of chains
+const MAX_REGULAR_SECTOR: u32 = 0xffff_fffa;
+/// The sibling or child of an entry that has none
+const NO_STREAM: u32 = 0xffff_ffff;
+/// Larger directories are only read in part
+const MAX_DIRECTORY_SECTORS: usize = 64;
+/// The header lists the first 109 sectors of the allocation table
+const HEADER_FAT_SECTORS: usize = 109;
+
+pub struct OleStorage;
+
+impl crate::Checker for OleStorage {
+  fn from_u8(&self, file: &[u8], mimetype: &str) -> bool {
+    self.identify_u8(file) == Some(mimetype)
+  }
+
+  fn from_filepath(&self, filepath: &Path, mimetype: &str) -> bool {
+    File::open(filepath).is_ok_and(|mut file| storage_type(&mut file) == Some(mimetype))
+  }
+
+  fn from_prefix(&self, prefix: &[u8], filepath: &Path, mimetype: &str) -> bool {
+    self.identify_prefix(prefix, filepath) == Some(mimetype)
+  }
+
+  fn identifier(&self) -> Option<&dyn Identifier> {
+    Some(self)
+  }
+
+  fn get_supported(&self) -> Vec<MIME<'_>> {
+    TYPES.to_vec()
+  }
+
+  fn get_subclasses(&self) -> Vec<(MIME<'_>, MIME<'_>)> {
+    TYPES
+      .map(|mimetype| ("application/x-ole-storage", mimetype))
+      .to_vec()
+  }
+
+  fn get_aliaslist(&self) -> FnvHashMap<MIME<'_>, MIME<'_>> {
+    FnvHashMap::default()
+  }
+}
+
+impl Identifier for OleStorage {
+  fn identify_u8(&self, file: &[u8]) -> Option<MIME<'static>> {
+    file
+      .starts_with(SIGNATURE)
+      .then(|| storage_type(&mut Cursor::new(file)))?
+  }
+
+  fn identify_prefix(&self, prefix: &[u8], filepath: &Path) -> Option<MIME<'static>> {
+    // The directory is usually past the start of the file
+    if !prefix.starts_with(SIGNATURE) {
+      return None;
+    }
+    storage_type(&mut File::open(filepath).ok()?)
+  }
+}
+
+/// A used directory entry
+struct Entry {
+  name: String,
+  clsid: [u8; 16],
+  left: u32,
+  right: u32,
+  child: u32,
+}
+
+impl Entry {
+  fn parse(entry: &[u8; 128]) -> Option<Self> {
+    // Unused entries have type 0
+    if entry[66] == 0 {
+      return None;
+    }
+    let name_len = usize::from(u16_le(entry, 64)).min(64);
+    let units = entry[..name_len]
+      .as_chunks()
+      .0
+      .iter()
+      .map(|&unit| u16::from_le_bytes(unit));
+    let name: String = char::decode_utf16(units)
+      .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+      .collect();
+    Some(Self {
+      name: name.trim_end_matches('\0').to_string(),
+      clsid: entry[80..96].try_into().ok()?,
+      left: u32_le(entry, 68),
+      right: u32_le(entry, 72),
+      child: u32_le(entry, 76),
+    })
+  }
+}
+
+/// The type of a compound file by the CLSID of its root storage or the names of its streams
+fn storage_type<R: Read + Seek>(reader: &mut R) -> Option<&'static str> {
+  let entries = read_directory(reader)?;
+  let root = entries.first()?.as_ref()?;
+  if let Some((_, mimetype)) = CLSID_TYPES.iter().find(|(clsid, _)| *clsid == root.clsid) {
+    return Some(mimetype);
+  }
+  let mimetype = root_children(&entries, root.child).find_map(|name| {
+    STREAM_TYPES
+      .iter()
+      .find(|(stream, _)| *stream == name)
+      .map(|(_, mimetype)| *mimetype)
+      .or_else(|| name.starts_with("__substg1.0_").then_some(MS_OUTLOOK))
+      // The compressed names of MSI tables start with this character
+      .or_else(|| name.starts_with('\u{4840}').then_some(MSI))
+  });
+  mimetype
+}
+
+/// The names of the entries of the root storage, which form a tree of siblings.
+///
+/// Entries that were not read are skipped, and so are their siblings.
+fn root_children(entries: &[Option<Entry>], child: u32) -> impl Iterator<Item = &str> {
+  let mut stack = vec![child];
+  // A corrupted tree can have cycles
+  std::iter::from_fn(move || {
+    while let Some(id) = stack.pop() {
+      let Some(Some(entry)) = usize::try_from(id).ok().and_then(|id| entries.get(id)) else {
+        continue;
+      };
+      stack.extend(
+        [entry.right, entry.left]
+          .into_iter()
+          .filter(|&id| id != NO_STREAM),
+      );
+      return Some(entry.name.as_str());
+    }
+    None
+  })
+  .take(entries.len())
+}
+
+/// Reads the directory entries, as far as the data goes, `None` if it is not a compound file
+fn read_directory<R: Read + Seek>(reader: &mut R) -> Option<Vec<Option<Entry>>> {
+  let mut header = [0; 512];
+  reader.rewind().ok()?;
+  reader.read_exact(&mut header).ok()?;
+  if !header.starts_with(SIGNATURE) {
+    return None;
+  }
+  let sector_shift = u16_le(&header, 30);
+  if !matches!(sector_shift, 9 | 12) {
+    return None;
+  }
+  let fat_len = usize::try_from(u32_le(&header, 44))
+    .map_or(HEADER_FAT_SECTORS, |len| len.min(HEADER_FAT_SECTORS));
+  let fat_sectors: Vec<u32> = header[76..]
+    .as_chunks()
+    .0
+    .iter()
+    .take(fat_len)
+    .map(|&sector| u32::from_le_bytes(sector))
+    .collect();
+
+  let mut entries = Vec::new();
+  let mut sector_buf = vec![0; 1 << sector_shift];
+  let mut sector = u32_le(&header, 48);
+  for _ in 0..MAX_DIRECTORY_SECTORS {
+    if sector >= MAX_REGULAR_SECTOR
+      || read_sector(reader, sector_shift, sector, &mut sector_buf).is_none()
+    {
+      break;
+    }
+    entries.extend(sector_buf.as_chunks().0.iter().map(Entry::parse));
+    let Some(next) = next_sector(reader, sector_shift, &fat_sectors, sector) else {
+      break;
+    };
+    sector = next;
+  }
+  Some(entries)
+}
+
+fn read_sector<R: Read + Seek>(
+  reader: &mut R,
+  sector_shift: u16,
+  sector: u32,
+  buf: &mut [u8],
+) -> Option<()> {
+  // The header takes up the first sector
+  reader
+    .seek(SeekFrom::Start((u64::from(sector) + 1) << sector_shift))
+    .ok()?;
+  reader.read_exact(buf).ok()
+}
+
+/// Follows a chain of sectors in the allocation table
+fn next_sector<R: Read + Seek>(
+  reader: &mut R,
+  sector_shift: u16,
+  fat_sectors: &[u32],
+  sector: u32,
+) -> Option<u32> {
+  let per_sector = 1 << (sector_shift - 2);
+  let fat_sector = *fat_sectors.get(usize::try_from(sector / per_sector).ok()?)?;
+  if fat_sector >= MAX_REGULAR_SECTOR {
+    return None;
+  }
+  let offset = ((u64::from(fat_sector) + 1) << sector_shift) + u64::from(sector % per_sector) * 4;
+  reader.seek(SeekFrom::Start(offset)).ok()?;
+  let mut next = [0; 4];
+  reader.read_exact(&mut next).ok()?;
+  Some(u32::from_le_bytes(next))
+}
+
+const fn u16_le(b: &[u8], offset: usize) -> u16 {
+  u16::from_le_bytes([b[offset], b[offset + 1]])
+}
+
+const fn u32_le(b: &[u8], offset: usize) -> u32 {
+  u32::from_le_bytes([b[offset], b[offset + 1], b[offset + 2], b[offset + 3]])
+}
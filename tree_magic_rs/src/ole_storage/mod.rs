@@ -0,0 +1,46 @@
+//! Handles formats stored in OLE2 compound files, told apart by the class of the root storage
+//! and the names of its streams
+
+const MSWORD: &str = "application/msword";
+const MS_EXCEL: &str = "application/vnd.ms-excel";
+const MS_POWERPOINT: &str = "application/vnd.ms-powerpoint";
+const MSI: &str = "application/x-msi";
+const MS_OUTLOOK: &str = "application/vnd.ms-outlook";
+
+const TYPES: [&str; 5] = [MSWORD, MS_EXCEL, MS_POWERPOINT, MSI, MS_OUTLOOK];
+
+/// A CLSID as stored in a compound file, with the first three fields little-endian
+const fn clsid(data1: u32, data2: u16, data3: u16, data4: u64) -> [u8; 16] {
+  let (a, b, c, d) = (
+    data1.to_le_bytes(),
+    data2.to_le_bytes(),
+    data3.to_le_bytes(),
+    data4.to_be_bytes(),
+  );
+  [
+    a[0], a[1], a[2], a[3], b[0], b[1], c[0], c[1], d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7],
+  ]
+}
+
+/// Types by the CLSID of the root storage
+const CLSID_TYPES: [([u8; 16], &str); 6] = [
+  (clsid(0x0002_0906, 0, 0, 0xc000_0000_0000_0046), MSWORD),
+  (clsid(0x0002_0810, 0, 0, 0xc000_0000_0000_0046), MS_EXCEL),
+  (clsid(0x0002_0820, 0, 0, 0xc000_0000_0000_0046), MS_EXCEL),
+  (
+    clsid(0x6481_8d10, 0x4f9b, 0x11cf, 0x86ea_00aa_00b9_29e8),
+    MS_POWERPOINT,
+  ),
+  (clsid(0x000c_1084, 0, 0, 0xc000_0000_0000_0046), MSI),
+  (clsid(0x0002_0d0b, 0, 0, 0xc000_0000_0000_0046), MS_OUTLOOK),
+];
+
+/// Types by the name of a stream in the root storage
+const STREAM_TYPES: [(&str, &str); 4] = [
+  ("WordDocument", MSWORD),
+  ("Workbook", MS_EXCEL),
+  ("Book", MS_EXCEL),
+  ("PowerPoint Document", MS_POWERPOINT),
+];
+
+pub mod check;
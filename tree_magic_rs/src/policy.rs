@@ -0,0 +1,432 @@
+//! Declarative allow and deny rules for detected MIME types.
+use crate::{read_bytes, MimeDatabase};
+use std::{fmt, path::Path, str::FromStr};
+use thiserror::Error;
+
+/// Types treated as executable, together with their subclasses.
+///
+/// Scripts are subclasses of `application/x-executable` in the shared MIME info database.
+pub const EXECUTABLE_TYPES: [&str; 7] = [
+  "application/x-executable",
+  "application/x-pie-executable",
+  "application/x-sharedlib",
+  "application/x-ms-dos-executable",
+  "application/x-msdownload",
+  "application/vnd.microsoft.portable-executable",
+  "application/x-mach-binary",
+];
+
+/// The errors that can occur while building a policy.
+#[derive(Error, Debug)]
+pub enum PolicyError {
+  /// A selector is malformed
+  #[error("invalid selector {selector:?}")]
+  InvalidSelector {
+    /// The malformed selector
+    selector: String,
+  },
+  /// A key of the policy file has an invalid value
+  #[error("invalid value for {key:?}")]
+  InvalidValue {
+    /// The key
+    key: String,
+  },
+  /// The policy file is not valid TOML
+  #[cfg(feature = "toml")]
+  #[error("TOML error")]
+  TomlError(#[from] toml::de::Error),
+  /// The policy file could not be read
+  #[cfg(feature = "toml")]
+  #[error("IO error")]
+  IoError {
+    /// The file that could not be read
+    file_path: std::path::PathBuf,
+    /// The underlying error
+    source: std::io::Error,
+  },
+}
+
+/// Whether a file is accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PolicyAction {
+  /// Accept the file
+  Allow,
+  /// Reject the file
+  Deny,
+}
+impl FromStr for PolicyAction {
+  type Err = PolicyError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "allow" => Ok(Self::Allow),
+      "deny" => Ok(Self::Deny),
+      _ => Err(PolicyError::InvalidValue {
+        key: "action".to_string(),
+      }),
+    }
+  }
+}
+impl fmt::Display for PolicyAction {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Allow => "allow",
+      Self::Deny => "deny",
+    })
+  }
+}
+
+/// A set of MIME types a rule applies to.
+///
+/// Selectors are written as `*/*` for any type, `image/*` for a top-level type,
+/// `application/pdf` for a single type, `descendants:application/pdf` for a type
+/// and its subclasses, and `executable` for [`EXECUTABLE_TYPES`] and their subclasses.
+/// Aliases are resolved and types compare case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Selector {
+  /// Any type
+  Any,
+  /// All types with this top-level type
+  TopLevel(String),
+  /// A single type
+  Type(String),
+  /// A type and its direct and indirect subclasses
+  DescendantsOf(String),
+  /// Executables and scripts
+  Executable,
+}
+impl Selector {
+  /// Whether a detected type is selected.
+  #[must_use]
+  pub fn matches(&self, db: &MimeDatabase, mimetype: &str) -> bool {
+    match self {
+      Self::Any => true,
+      Self::TopLevel(top_level) => mimetype
+        .split_once('/')
+        .is_some_and(|(t, _)| t.eq_ignore_ascii_case(top_level)),
+      // The same type once aliases are resolved
+      Self::Type(other) => db.is_a(mimetype, other.as_str()) && db.is_a(other.as_str(), mimetype),
+      Self::DescendantsOf(parent) => db.is_a(mimetype, parent.as_str()),
+      Self::Executable => EXECUTABLE_TYPES
+        .iter()
+        .any(|parent| db.is_a(mimetype, *parent)),
+    }
+  }
+}
+impl FromStr for Selector {
+  type Err = PolicyError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || PolicyError::InvalidSelector {
+      selector: s.to_string(),
+    };
+    // Restricted names of RFC 6838
+    let is_name = |name: &str| {
+      !name.is_empty()
+        && name
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    let is_type = |t: &str| {
+      t.split_once('/')
+        .is_some_and(|(top_level, sub)| is_name(top_level) && is_name(sub))
+    };
+    let selector = match (s.strip_suffix("/*"), s.strip_prefix("descendants:")) {
+      _ if s == "*" || s == "*/*" => Some(Self::Any),
+      _ if s == "executable" => Some(Self::Executable),
+      (Some(top_level), _) => is_name(top_level).then(|| Self::TopLevel(top_level.to_string())),
+      (None, Some(parent)) => is_type(parent).then(|| Self::DescendantsOf(parent.to_string())),
+      (None, None) => is_type(s).then(|| Self::Type(s.to_string())),
+    };
+    selector.ok_or_else(invalid)
+  }
+}
+impl fmt::Display for Selector {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Any => f.write_str("*/*"),
+      Self::TopLevel(top_level) => write!(f, "{top_level}/*"),
+      Self::Type(mimetype) => f.write_str(mimetype),
+      Self::DescendantsOf(parent) => write!(f, "descendants:{parent}"),
+      Self::Executable => f.write_str("executable"),
+    }
+  }
+}
+
+/// A clause of a [`Policy`]: an action for the selected types, except some of them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PolicyRule {
+  /// What to do with matching files
+  pub action: PolicyAction,
+  /// The types the rule applies to
+  pub selector: Selector,
+  /// Selected types the rule does not apply to
+  pub except: Vec<Selector>,
+}
+impl PolicyRule {
+  /// Creates a rule that accepts the selected types.
+  #[must_use]
+  pub const fn allow(selector: Selector) -> Self {
+    Self {
+      action: PolicyAction::Allow,
+      selector,
+      except: Vec::new(),
+    }
+  }
+
+  /// Creates a rule that rejects the selected types.
+  #[must_use]
+  pub const fn deny(selector: Selector) -> Self {
+    Self {
+      action: PolicyAction::Deny,
+      selector,
+      except: Vec::new(),
+    }
+  }
+
+  /// Adds an exception.
+  #[must_use]
+  pub fn except(mut self, selector: Selector) -> Self {
+    self.except.push(selector);
+    self
+  }
+
+  /// Whether the rule applies to a detected type.
+  #[must_use]
+  pub fn matches(&self, db: &MimeDatabase, mimetype: &str) -> bool {
+    self.selector.matches(db, mimetype) && !self.except.iter().any(|e| e.matches(db, mimetype))
+  }
+}
+impl fmt::Display for PolicyRule {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} {}", self.action, self.selector)?;
+    for (i, selector) in self.except.iter().enumerate() {
+      let separator = if i == 0 { " except " } else { ", " };
+      write!(f, "{separator}{selector}")?;
+    }
+    Ok(())
+  }
+}
+
+/// The result of evaluating a [`Policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyDecision<'a> {
+  /// Whether the file is accepted
+  pub action: PolicyAction,
+  /// The detected type
+  pub mime_type: &'a str,
+  /// The rule that decided, or `None` if the default action was used
+  pub clause: Option<&'a PolicyRule>,
+}
+impl PolicyDecision<'_> {
+  /// Whether the file is accepted.
+  #[must_use]
+  pub fn is_allowed(&self) -> bool {
+    self.action == PolicyAction::Allow
+  }
+}
+
+/// Ordered allow and deny rules evaluated against the detected type of a file.
+///
+/// The first matching rule decides, files no rule matches get the default action.
+///
+/// # Examples
+/// ```rust
+/// use tree_magic_rs::{Policy, PolicyAction, PolicyRule, Selector};
+///
+/// let policy = Policy::new(PolicyAction::Deny)
+///   .with_rule(PolicyRule::deny(Selector::Executable))
+///   .with_rule(
+///     PolicyRule::allow("image/*".parse().unwrap()).except("image/svg+xml".parse().unwrap()),
+///   )
+///   .with_rule(PolicyRule::allow("descendants:application/pdf".parse().unwrap()))
+///   .with_max_sniff_len(64 * 1024);
+/// assert_eq!(
+///   policy.rules()[1].to_string(),
+///   "allow image/* except image/svg+xml"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+  rules: Vec<PolicyRule>,
+  default_action: PolicyAction,
+  max_sniff_len: Option<usize>,
+}
+impl Policy {
+  /// The number of bytes read by [`evaluate_filepath`](Self::evaluate_filepath)
+  /// if no maximum sniff length is set.
+  pub const DEFAULT_SNIFF_LEN: usize = 2048;
+
+  /// Creates a policy without rules.
+  #[must_use]
+  pub const fn new(default_action: PolicyAction) -> Self {
+    Self {
+      rules: Vec::new(),
+      default_action,
+      max_sniff_len: None,
+    }
+  }
+
+  /// Appends a rule, rules are evaluated in order.
+  #[must_use]
+  pub fn with_rule(mut self, rule: PolicyRule) -> Self {
+    self.rules.push(rule);
+    self
+  }
+
+  /// Limits how many bytes of a file are used for detection.
+  #[must_use]
+  pub const fn with_max_sniff_len(mut self, max_sniff_len: usize) -> Self {
+    self.max_sniff_len = Some(max_sniff_len);
+    self
+  }
+
+  /// The rules in evaluation order.
+  #[must_use]
+  pub fn rules(&self) -> &[PolicyRule] {
+    &self.rules
+  }
+
+  /// The action for files no rule matches.
+  #[must_use]
+  pub const fn default_action(&self) -> PolicyAction {
+    self.default_action
+  }
+
+  /// How many bytes of a file are used for detection.
+  #[must_use]
+  pub const fn max_sniff_len(&self) -> Option<usize> {
+    self.max_sniff_len
+  }
+
+  /// Decides on an already detected type.
+  #[must_use]
+  pub fn evaluate_type<'a>(&'a self, db: &MimeDatabase, mimetype: &'a str) -> PolicyDecision<'a> {
+    let clause = self.rules.iter().find(|rule| rule.matches(db, mimetype));
+    PolicyDecision {
+      action: clause.map_or(self.default_action, |rule| rule.action),
+      mime_type: mimetype,
+      clause,
+    }
+  }
+
+  /// Detects the type of the contents and decides on it.
+  #[must_use]
+  pub fn evaluate<'a>(&'a self, db: &'a MimeDatabase, bytes: &[u8]) -> PolicyDecision<'a> {
+    let len = self
+      .max_sniff_len
+      .map_or(bytes.len(), |max| max.min(bytes.len()));
+    self.evaluate_type(db, db.from_u8(&bytes[..len]))
+  }
+
+  /// Detects the type of a file and decides on it.
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be read.
+  pub fn evaluate_filepath<'a>(
+    &'a self,
+    db: &'a MimeDatabase,
+    filepath: &Path,
+  ) -> std::io::Result<PolicyDecision<'a>> {
+    let bytes = read_bytes(
+      filepath,
+      self.max_sniff_len.unwrap_or(Self::DEFAULT_SNIFF_LEN),
+    )?;
+    Ok(self.evaluate(db, &bytes))
+  }
+}
+
+#[cfg(feature = "toml")]
+impl Policy {
+  /// Reads a policy file.
+  ///
+  /// ```toml
+  /// default = "deny"
+  /// max_sniff = 65536
+  ///
+  /// [[rule]]
+  /// action = "deny"
+  /// match = "executable"
+  ///
+  /// [[rule]]
+  /// action = "allow"
+  /// match = "image/*"
+  /// except = ["image/svg+xml"]
+  /// ```
+  ///
+  /// `default` is `deny` if not given. Without `max_sniff`, [`evaluate`](Self::evaluate) uses
+  /// all given bytes and [`evaluate_filepath`](Self::evaluate_filepath) reads
+  /// [`DEFAULT_SNIFF_LEN`](Self::DEFAULT_SNIFF_LEN) bytes.
+  ///
+  /// # Errors
+  /// Returns an error if the file is not valid TOML, has unknown keys or invalid values.
+  pub fn from_toml_str(text: &str) -> Result<Self, PolicyError> {
+    let table: toml::Table = text.parse()?;
+    let invalid = |key: &str| PolicyError::InvalidValue {
+      key: key.to_string(),
+    };
+    let mut policy = Self::new(PolicyAction::Deny);
+    for (key, value) in &table {
+      match key.as_str() {
+        "default" => {
+          let action = value.as_str().ok_or_else(|| invalid(key))?;
+          policy.default_action = action.parse().map_err(|_| invalid(key))?;
+        },
+        "max_sniff" => {
+          let max_sniff = value.as_integer().ok_or_else(|| invalid(key))?;
+          policy.max_sniff_len = Some(usize::try_from(max_sniff).map_err(|_| invalid(key))?);
+        },
+        "rule" => {
+          let rules = value.as_array().ok_or_else(|| invalid(key))?;
+          for rule in rules {
+            policy.rules.push(Self::rule_from_toml(rule)?);
+          }
+        },
+        _ => return Err(invalid(key)),
+      }
+    }
+    Ok(policy)
+  }
+
+  fn rule_from_toml(rule: &toml::Value) -> Result<PolicyRule, PolicyError> {
+    let invalid = |key: &str| PolicyError::InvalidValue {
+      key: format!("rule.{key}"),
+    };
+    let table = rule.as_table().ok_or_else(|| invalid("rule"))?;
+    let mut action = None;
+    let mut selector = None;
+    let mut except = Vec::new();
+    for (key, value) in table {
+      match key.as_str() {
+        "action" => {
+          let value = value.as_str().ok_or_else(|| invalid(key))?;
+          action = Some(value.parse::<PolicyAction>().map_err(|_| invalid(key))?);
+        },
+        "match" => selector = Some(value.as_str().ok_or_else(|| invalid(key))?.parse()?),
+        "except" => {
+          for selector in value.as_array().ok_or_else(|| invalid(key))? {
+            except.push(selector.as_str().ok_or_else(|| invalid(key))?.parse()?);
+          }
+        },
+        _ => return Err(invalid(key)),
+      }
+    }
+    Ok(PolicyRule {
+      action: action.ok_or_else(|| invalid("action"))?,
+      selector: selector.ok_or_else(|| invalid("match"))?,
+      except,
+    })
+  }
+
+  /// Reads a policy file, see [`from_toml_str`](Self::from_toml_str).
+  ///
+  /// # Errors
+  /// Returns an error if the file cannot be read or is invalid.
+  pub fn from_toml_file(file: &Path) -> Result<Self, PolicyError> {
+    let text = std::fs::read_to_string(file).map_err(|source| PolicyError::IoError {
+      file_path: file.to_path_buf(),
+      source,
+    })?;
+    Self::from_toml_str(&text)
+  }
+}
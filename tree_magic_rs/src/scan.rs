@@ -0,0 +1,358 @@
+//! Recursive detection of the files below a directory on a pool of threads.
+use crate::db::glob_matches;
+use crate::MimeDatabase;
+use fnv::FnvHashSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::BTreeMap,
+  fs, io,
+  num::NonZeroUsize,
+  path::{Path, PathBuf},
+  sync::{
+    mpsc::{self, Receiver, SyncSender},
+    Arc, Mutex,
+  },
+  thread,
+};
+use thiserror::Error;
+
+/// How [`MimeDatabase::scan`] treats symbolic links.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SymlinkPolicy {
+  /// Links are skipped, unless the root itself is one
+  #[default]
+  Skip,
+  /// Links to files are detected, links to directories are skipped
+  FollowFiles,
+  /// All links are followed, directories that were already visited are skipped
+  Follow,
+}
+
+/// What [`MimeDatabase::scan`] visits and how many threads it uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanOptions {
+  symlinks: SymlinkPolicy,
+  hidden: bool,
+  max_depth: Option<usize>,
+  excludes: Vec<String>,
+  threads: Option<NonZeroUsize>,
+}
+impl ScanOptions {
+  /// Skips symbolic links and hidden files, with no depth limit
+  /// and a thread for each available CPU.
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets how symbolic links are treated.
+  #[must_use]
+  pub const fn with_symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+    self.symlinks = symlinks;
+    self
+  }
+
+  /// Whether files and directories whose name starts with a `.` are visited.
+  #[must_use]
+  pub const fn with_hidden(mut self, hidden: bool) -> Self {
+    self.hidden = hidden;
+    self
+  }
+
+  /// Limits how deep directories are entered, `1` only visits the entries of the root.
+  #[must_use]
+  pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+    self.max_depth = Some(max_depth);
+    self
+  }
+
+  /// Skips the entries whose name or path relative to the root matches a glob pattern,
+  /// like `*.tmp` or `build/cache`. Excluded directories are not entered.
+  #[must_use]
+  pub fn with_exclude(mut self, pattern: impl Into<String>) -> Self {
+    self.excludes.push(pattern.into());
+    self
+  }
+
+  /// Sets the number of detection threads.
+  #[must_use]
+  pub const fn with_threads(mut self, threads: NonZeroUsize) -> Self {
+    self.threads = Some(threads);
+    self
+  }
+
+  fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+    let name = path
+      .file_name()
+      .map_or_else(Default::default, |name| name.to_string_lossy());
+    let relative = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+    self
+      .excludes
+      .iter()
+      .any(|pattern| glob_matches(pattern, &name) || glob_matches(pattern, &relative))
+  }
+}
+
+/// A detected file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanEntry {
+  /// The path, starting with the root
+  pub path: PathBuf,
+  /// The type of the contents
+  pub mime_type: String,
+  /// The size in bytes
+  pub size: u64,
+}
+
+/// The errors that can occur while scanning.
+#[derive(Error, Debug)]
+pub enum ScanError {
+  /// A directory or file could not be read
+  #[error("IO error")]
+  IoError {
+    /// The directory or file
+    path: PathBuf,
+    /// The underlying error
+    source: io::Error,
+  },
+  /// The file disappeared or could not be opened for detection
+  #[error("Could not detect the type")]
+  Undetected {
+    /// The file
+    path: PathBuf,
+  },
+}
+
+/// The number and total size of the files of a type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TypeTotals {
+  /// The number of files
+  pub files: u64,
+  /// Their total size in bytes
+  pub bytes: u64,
+}
+
+/// A histogram of the types found by a scan.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanSummary {
+  /// The totals of each type, sorted by type
+  pub types: BTreeMap<String, TypeTotals>,
+  /// The totals of all detected files
+  pub total: TypeTotals,
+  /// The number of errors
+  pub errors: u64,
+}
+impl ScanSummary {
+  /// Counts a result of a scan.
+  pub fn add(&mut self, result: &Result<ScanEntry, ScanError>) {
+    let Ok(entry) = result else {
+      self.errors += 1;
+      return;
+    };
+    let totals = self.types.entry(entry.mime_type.clone()).or_default();
+    for totals in [totals, &mut self.total] {
+      totals.files += 1;
+      totals.bytes += entry.size;
+    }
+  }
+}
+impl Extend<Result<ScanEntry, ScanError>> for ScanSummary {
+  fn extend<T: IntoIterator<Item = Result<ScanEntry, ScanError>>>(&mut self, iter: T) {
+    iter.into_iter().for_each(|result| self.add(&result));
+  }
+}
+impl FromIterator<Result<ScanEntry, ScanError>> for ScanSummary {
+  fn from_iter<T: IntoIterator<Item = Result<ScanEntry, ScanError>>>(iter: T) -> Self {
+    let mut summary = Self::default();
+    summary.extend(iter);
+    summary
+  }
+}
+
+/// The results of [`MimeDatabase::scan`], in the order the threads finish them.
+///
+/// Dropping it stops the scan.
+#[derive(Debug)]
+pub struct Scan {
+  results: Receiver<Result<ScanEntry, ScanError>>,
+}
+impl Scan {
+  /// Waits for the remaining results and counts them.
+  #[must_use]
+  pub fn summary(self) -> ScanSummary {
+    self.collect()
+  }
+
+  /// The channel the results arrive on, to select on or hand to another thread.
+  #[must_use]
+  pub fn into_receiver(self) -> Receiver<Result<ScanEntry, ScanError>> {
+    self.results
+  }
+}
+impl Iterator for Scan {
+  type Item = Result<ScanEntry, ScanError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.results.recv().ok()
+  }
+}
+
+impl MimeDatabase {
+  /// Walks a directory and detects the type of every file below it
+  /// with [`from_filepath`](Self::from_filepath) on a pool of threads.
+  ///
+  /// Only regular files are detected. The results are streamed while the walk goes on;
+  /// a root that is a file yields just that file.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use std::sync::Arc;
+  /// use tree_magic_rs::{db::OwnedBuildableDb, MimeDatabase, ScanOptions};
+  ///
+  /// let db = Arc::new(MimeDatabase::from_provider(&OwnedBuildableDb::new()));
+  /// let options = ScanOptions::new().with_max_depth(2).with_exclude("target");
+  /// let summary = db.scan(".", &options).summary();
+  /// for (mime_type, totals) in &summary.types {
+  ///   println!("{mime_type}: {} files, {} bytes", totals.files, totals.bytes);
+  /// }
+  /// assert_eq!(summary.errors, 0);
+  /// ```
+  #[must_use]
+  pub fn scan<P: AsRef<Path>>(self: &Arc<Self>, root: P, options: &ScanOptions) -> Scan {
+    let threads = options
+      .threads
+      .or_else(|| thread::available_parallelism().ok())
+      .map_or(1, NonZeroUsize::get);
+    let (paths_tx, paths_rx) = mpsc::sync_channel(threads * 64);
+    let (results_tx, results_rx) = mpsc::sync_channel(threads * 64);
+
+    let paths_rx = Arc::new(Mutex::new(paths_rx));
+    for _ in 0..threads {
+      let (db, paths_rx, results_tx) =
+        (Arc::clone(self), Arc::clone(&paths_rx), results_tx.clone());
+      thread::spawn(move || detect_files(&db, &paths_rx, &results_tx));
+    }
+    let (root, options) = (root.as_ref().to_path_buf(), options.clone());
+    thread::spawn(move || walk(&root, &options, &paths_tx, &results_tx));
+
+    Scan {
+      results: results_rx,
+    }
+  }
+}
+
+/// Detects the files sent by the walk until it is done or the [`Scan`] is dropped.
+fn detect_files(
+  db: &MimeDatabase,
+  paths: &Mutex<Receiver<(PathBuf, u64)>>,
+  results: &SyncSender<Result<ScanEntry, ScanError>>,
+) {
+  loop {
+    let Ok(Ok((path, size))) = paths.lock().map(|paths| paths.recv()) else {
+      return;
+    };
+    let result = match db.from_filepath(&path) {
+      Some(mime_type) => Ok(ScanEntry {
+        mime_type: mime_type.to_string(),
+        path,
+        size,
+      }),
+      None => Err(ScanError::Undetected { path }),
+    };
+    if results.send(result).is_err() {
+      return;
+    }
+  }
+}
+
+/// Sends the regular files below `root` to the detection threads.
+fn walk(
+  root: &Path,
+  options: &ScanOptions,
+  paths: &SyncSender<(PathBuf, u64)>,
+  results: &SyncSender<Result<ScanEntry, ScanError>>,
+) {
+  // Whether the `Scan` still receives results
+  let report = |path: &Path, source| {
+    let err = ScanError::IoError {
+      path: path.to_path_buf(),
+      source,
+    };
+    results.send(Err(err)).is_ok()
+  };
+  let mut visited = FnvHashSet::default();
+  let mut stack = vec![(root.to_path_buf(), 0)];
+  while let Some((path, depth)) = stack.pop() {
+    let metadata = match fs::symlink_metadata(&path) {
+      Ok(metadata) if metadata.is_symlink() => {
+        if depth > 0 && options.symlinks == SymlinkPolicy::Skip {
+          continue;
+        }
+        fs::metadata(&path)
+      },
+      metadata => metadata,
+    };
+    let metadata = match metadata {
+      Ok(metadata) => metadata,
+      Err(err) => {
+        if !report(&path, err) {
+          return;
+        }
+        continue;
+      },
+    };
+
+    if metadata.is_file() {
+      if paths.send((path, metadata.len())).is_err() {
+        return;
+      }
+      continue;
+    }
+    if !metadata.is_dir()
+      || options
+        .max_depth
+        .is_some_and(|max_depth| depth >= max_depth)
+    {
+      continue;
+    }
+    if depth > 0 && path.is_symlink() && options.symlinks == SymlinkPolicy::FollowFiles {
+      continue;
+    }
+    // Links can lead back into a directory that is being walked
+    if options.symlinks == SymlinkPolicy::Follow
+      && fs::canonicalize(&path).is_ok_and(|canonical| !visited.insert(canonical))
+    {
+      continue;
+    }
+    let entries = match fs::read_dir(&path) {
+      Ok(entries) => entries,
+      Err(err) => {
+        if !report(&path, err) {
+          return;
+        }
+        continue;
+      },
+    };
+    for entry in entries {
+      let entry = match entry {
+        Ok(entry) => entry,
+        Err(err) => {
+          if !report(&path, err) {
+            return;
+          }
+          continue;
+        },
+      };
+      let hidden = entry.file_name().to_string_lossy().starts_with('.');
+      let child = entry.path();
+      if (hidden && !options.hidden) || options.is_excluded(root, &child) {
+        continue;
+      }
+      stack.push((child, depth + 1));
+    }
+  }
+}
@@ -0,0 +1,275 @@
+//! Telling text from binary data.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The encoding of text found by [`is_text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TextEncoding {
+  /// 7-bit ASCII, which is also valid UTF-8
+  Ascii,
+  /// UTF-8 with non-ASCII characters
+  Utf8,
+  /// UTF-16, little-endian
+  Utf16Le,
+  /// UTF-16, big-endian
+  Utf16Be,
+  /// UTF-32, little-endian
+  Utf32Le,
+  /// UTF-32, big-endian
+  Utf32Be,
+  /// An 8-bit encoding other than UTF-8, like ISO-8859-1
+  Unknown8Bit,
+}
+impl TextEncoding {
+  /// The IANA charset name, if the encoding is known.
+  #[must_use]
+  pub const fn charset(self) -> Option<&'static str> {
+    match self {
+      Self::Ascii => Some("us-ascii"),
+      Self::Utf8 => Some("utf-8"),
+      Self::Utf16Le => Some("utf-16le"),
+      Self::Utf16Be => Some("utf-16be"),
+      Self::Utf32Le => Some("utf-32le"),
+      Self::Utf32Be => Some("utf-32be"),
+      Self::Unknown8Bit => None,
+    }
+  }
+}
+
+/// The result of [`is_text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TextVerdict {
+  /// The data is text
+  Text {
+    /// The detected encoding
+    encoding: TextEncoding,
+    /// Whether the data starts with a byte order mark
+    bom: bool,
+  },
+  /// The data is not text in any of the known encodings
+  Binary,
+}
+impl TextVerdict {
+  /// Whether the data is text.
+  #[must_use]
+  pub const fn is_text(&self) -> bool {
+    matches!(self, Self::Text { .. })
+  }
+
+  /// The encoding of text.
+  #[must_use]
+  pub const fn encoding(&self) -> Option<TextEncoding> {
+    match self {
+      Self::Text { encoding, .. } => Some(*encoding),
+      Self::Binary => None,
+    }
+  }
+}
+
+/// Byte order marks, longest first so UTF-32LE is not taken for UTF-16LE
+const BOMS: [(&[u8], TextEncoding); 5] = [
+  (b"\xef\xbb\xbf", TextEncoding::Utf8),
+  (b"\xff\xfe\x00\x00", TextEncoding::Utf32Le),
+  (b"\x00\x00\xfe\xff", TextEncoding::Utf32Be),
+  (b"\xff\xfe", TextEncoding::Utf16Le),
+  (b"\xfe\xff", TextEncoding::Utf16Be),
+];
+
+/// Decides whether data is text, and in which encoding.
+///
+/// Data is text if it is UTF-8, if it starts with a UTF-8, UTF-16 or UTF-32 byte order mark,
+/// or if it looks like UTF-16 without one, and few of its characters are control characters.
+/// Data without NUL bytes that is not UTF-8 is text in an unknown 8-bit encoding
+/// under the same limit. A character sequence cut off at the end is ignored,
+/// so the start of a file can be classified.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextClassifier {
+  max_control_ratio: f32,
+}
+impl Default for TextClassifier {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl TextClassifier {
+  /// Allows 5% control characters.
+  #[must_use]
+  pub const fn new() -> Self {
+    Self {
+      max_control_ratio: 0.05,
+    }
+  }
+
+  /// Sets the largest share of control characters, like `0.05` for 5%, that text may have.
+  ///
+  /// Tabs, line breaks, form feeds and escapes are not counted, invalid characters are.
+  #[must_use]
+  pub const fn with_max_control_ratio(mut self, max_control_ratio: f32) -> Self {
+    self.max_control_ratio = max_control_ratio;
+    self
+  }
+
+  /// The largest share of control characters that text may have.
+  #[must_use]
+  pub const fn max_control_ratio(self) -> f32 {
+    self.max_control_ratio
+  }
+
+  /// Classifies data as text or binary.
+  #[must_use]
+  pub fn classify(self, bytes: &[u8]) -> TextVerdict {
+    if let Some((bom, encoding)) = BOMS.iter().find(|(bom, _)| bytes.starts_with(bom)) {
+      return self.verdict(decode(&bytes[bom.len()..], *encoding), *encoding, true);
+    }
+    if bytes.contains(&0) {
+      return guess_utf16(bytes).map_or(TextVerdict::Binary, |encoding| {
+        self.verdict(decode(bytes, encoding), encoding, false)
+      });
+    }
+    let complete = trim_incomplete_utf8(bytes);
+    let encoding = match std::str::from_utf8(complete) {
+      Ok(text) if text.is_ascii() && complete.len() == bytes.len() => TextEncoding::Ascii,
+      Ok(_) => TextEncoding::Utf8,
+      Err(_) => TextEncoding::Unknown8Bit,
+    };
+    self.verdict(decode(bytes, encoding), encoding, false)
+  }
+
+  fn verdict(
+    self,
+    chars: impl Iterator<Item = Option<char>>,
+    encoding: TextEncoding,
+    bom: bool,
+  ) -> TextVerdict {
+    let (mut total, mut control) = (0u32, 0u32);
+    for char in chars {
+      total = total.saturating_add(1);
+      if char.is_none_or(is_control) {
+        control = control.saturating_add(1);
+      }
+    }
+    if f64::from(control) > f64::from(self.max_control_ratio) * f64::from(total) {
+      TextVerdict::Binary
+    } else {
+      TextVerdict::Text { encoding, bom }
+    }
+  }
+}
+
+/// Decides whether data is text with the default [`TextClassifier`].
+///
+/// # Examples
+/// ```rust
+/// use tree_magic_rs::{is_text, TextEncoding, TextVerdict};
+///
+/// assert_eq!(
+///   is_text("grüße\n".as_bytes()).encoding(),
+///   Some(TextEncoding::Utf8)
+/// );
+/// assert_eq!(
+///   is_text(b"h\0i\0\n\0"),
+///   TextVerdict::Text {
+///     encoding: TextEncoding::Utf16Le,
+///     bom: false
+///   }
+/// );
+/// assert_eq!(is_text(b"\x7fELF\x02\x01\x01\0"), TextVerdict::Binary);
+/// ```
+#[must_use]
+pub fn is_text(bytes: &[u8]) -> TextVerdict {
+  TextClassifier::new().classify(bytes)
+}
+
+/// Control characters that are not common in text
+const fn is_control(char: char) -> bool {
+  char.is_control() && !matches!(char, '\t' | '\n' | '\x0b' | '\x0c' | '\r' | '\x1b')
+}
+
+/// UTF-16 without a byte order mark, if every other byte is mostly zero
+fn guess_utf16(bytes: &[u8]) -> Option<TextEncoding> {
+  let units = bytes.len() / 2;
+  let zeros = |start| {
+    bytes[start..]
+      .iter()
+      .step_by(2)
+      .take(units)
+      .filter(|&&byte| byte == 0)
+      .count()
+  };
+  let (even_zeros, odd_zeros) = (zeros(0), zeros(1));
+  // Characters below U+0100 have a zero high byte
+  if odd_zeros * 2 > units && even_zeros * 10 < units {
+    Some(TextEncoding::Utf16Le)
+  } else if even_zeros * 2 > units && odd_zeros * 10 < units {
+    Some(TextEncoding::Utf16Be)
+  } else {
+    None
+  }
+}
+
+/// Leaves out a UTF-8 sequence that is cut off at the end
+fn trim_incomplete_utf8(bytes: &[u8]) -> &[u8] {
+  for start in (bytes.len().saturating_sub(3)..bytes.len()).rev() {
+    let len = match bytes[start] {
+      0x80..=0xbf => continue,
+      0xc0..=0xdf => 2,
+      0xe0..=0xef => 3,
+      0xf0..=0xf7 => 4,
+      _ => return bytes,
+    };
+    return if bytes.len() - start < len {
+      &bytes[..start]
+    } else {
+      bytes
+    };
+  }
+  bytes
+}
+
+/// The characters of text, `None` for invalid ones.
+///
+/// A character that is cut off at the end is left out.
+fn decode(bytes: &[u8], encoding: TextEncoding) -> Box<dyn Iterator<Item = Option<char>> + '_> {
+  match encoding {
+    TextEncoding::Ascii | TextEncoding::Utf8 => {
+      Box::new(trim_incomplete_utf8(bytes).utf8_chunks().flat_map(|chunk| {
+        let invalid = !chunk.invalid().is_empty();
+        chunk
+          .valid()
+          .chars()
+          .map(Some)
+          .chain(invalid.then_some(None))
+      }))
+    },
+    TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+      let units = bytes.as_chunks().0.iter().map(move |&unit| {
+        if encoding == TextEncoding::Utf16Le {
+          u16::from_le_bytes(unit)
+        } else {
+          u16::from_be_bytes(unit)
+        }
+      });
+      Box::new(char::decode_utf16(units).map(Result::ok))
+    },
+    TextEncoding::Utf32Le | TextEncoding::Utf32Be => {
+      Box::new(bytes.as_chunks().0.iter().map(move |&unit| {
+        char::from_u32(if encoding == TextEncoding::Utf32Le {
+          u32::from_le_bytes(unit)
+        } else {
+          u32::from_be_bytes(unit)
+        })
+      }))
+    },
+    // Only control characters matter, the bytes above ASCII are taken as printable
+    TextEncoding::Unknown8Bit => Box::new(bytes.iter().map(|&byte| {
+      Some(if byte.is_ascii() {
+        char::from(byte)
+      } else {
+        char::REPLACEMENT_CHARACTER
+      })
+    })),
+  }
+}
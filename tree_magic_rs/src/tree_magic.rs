@@ -1,11 +1,20 @@
 use crate::basetype;
+use crate::db::{provider_checksum, DbProvider, GlobDatabase, XmlNamespaceDatabase};
 use crate::fdo_magic;
 use crate::fdo_magic::builtin::LoadedDatabase;
+use crate::ole_storage;
+use crate::xml_root;
+use crate::zip_container;
+use crate::{MediaType, MimeLike, TextClassifier, Verdict, VerdictKind};
 use fnv::FnvHashMap;
 use fnv::FnvHashSet;
+use fnv::FnvHasher;
 use petgraph::prelude::*;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::Path;
-use std::rc::Rc;
 use std::sync::Arc;
 
 pub type MIME<'a> = &'a str;
@@ -24,12 +33,31 @@ pub struct MimeDatabase {
   /// The root of the graph is "all/all", so start traversing there unless
   /// you need to jump to a particular node.
   graph: TypeStruct,
-  checker_support: FnvHashMap<String, Rc<dyn Checker>>,
+  /// The checkers of each type, a type matches if any of them matches
+  checker_support: FnvHashMap<String, Vec<Arc<dyn Checker>>>,
   aliases: FnvHashMap<String, String>,
+  /// Lowercased MIME types and aliases mapped to their real type
+  folded_names: FnvHashMap<String, String>,
+  /// Subclass parents of all types, including those without magic that are not in the `graph`
+  parents: FnvHashMap<String, Vec<String>>,
+  /// File name globs for `from_filename`
+  globs: GlobDatabase,
+  /// The magic rules, aliases and subclasses
+  magic: Arc<dyn Checker>,
+  /// Checksum of the magic rules, aliases and subclasses
+  magic_fingerprint: u64,
+  /// Root elements of XML types
+  xml_namespaces: XmlNamespaceDatabase,
+  /// Checksum of the magic rules, aliases, subclasses and XML root elements
+  fingerprint: u64,
+  /// Whether `from_filepath` and `match_filepath` resolve symbolic links
+  follow_symlinks: bool,
+  /// Decides `text/plain` in the basetype checker
+  text: TextClassifier,
 }
 impl MimeDatabase {
   // Initialize filetype graph
-  fn graph_init(checkers: &[Rc<dyn Checker>]) -> TypeStruct {
+  fn graph_init(checkers: &[Arc<dyn Checker>]) -> TypeStruct {
     let mut graph = DiGraph::<String, u32>::new(); // TODO: restore &str
     let mut added_mimes = FnvHashMap::<String, NodeIndex>::default(); // TODO: restore &str
 
@@ -77,6 +105,8 @@ impl MimeDatabase {
 
     graph.extend_with_edges(&edge_list);
 
+    Self::graph_add_suffix_edges(&mut graph, &added_mimes, &mut edge_list);
+
     //Add to applicaton/octet-stream, all/all, or text/plain, depending on top-level
     //(We'll just do it here because having the graph makes it really nice)
     let added_mimes_tmp = added_mimes.clone();
@@ -139,7 +169,26 @@ impl MimeDatabase {
 
     graph
   }
-  fn aliases_init(checkers: &[Rc<dyn Checker>]) -> FnvHashMap<String, String> {
+  /// Types with a structured syntax suffix are related to their generic type
+  /// even if the checkers don't know about it (EX: `image/svg+xml` -> `application/xml`)
+  fn graph_add_suffix_edges(
+    graph: &mut TypeStruct,
+    added_mimes: &FnvHashMap<String, NodeIndex>,
+    edge_list: &mut FnvHashSet<(NodeIndex, NodeIndex)>,
+  ) {
+    let suffix_edges: Vec<(NodeIndex, NodeIndex)> = graph
+      .externals(Incoming)
+      .filter_map(|mimenode| {
+        let parent = MediaType::from_essence(&graph[mimenode]).suffix_parent()?;
+        added_mimes.get(parent).map(|parent| (*parent, mimenode))
+      })
+      .collect();
+    for (parent, child) in suffix_edges {
+      graph.add_edge(parent, child, 1);
+      edge_list.insert((parent, child));
+    }
+  }
+  fn aliases_init(checkers: &[Arc<dyn Checker>]) -> FnvHashMap<String, String> {
     let mut out = FnvHashMap::<String, String>::default();
     for c in checkers {
       out.extend(
@@ -150,41 +199,581 @@ impl MimeDatabase {
     }
     out
   }
+  fn parents_init(checkers: &[Arc<dyn Checker>]) -> FnvHashMap<String, Vec<String>> {
+    let mut out = FnvHashMap::<String, Vec<String>>::default();
+    for c in checkers {
+      for (parent, child) in c.get_subclasses() {
+        let parents = out.entry(child.to_string()).or_default();
+        if !parents.iter().any(|p| p == parent) {
+          parents.push(parent.to_string());
+        }
+      }
+    }
+    out
+  }
+  /// Case-insensitive lookup table for MIME types and aliases
+  fn folded_names_init(
+    checker_support: &FnvHashMap<String, Vec<Arc<dyn Checker>>>,
+    aliases: &FnvHashMap<String, String>,
+  ) -> FnvHashMap<String, String> {
+    let mut out = FnvHashMap::<String, String>::default();
+    for (alias, name) in aliases {
+      out.insert(alias.to_ascii_lowercase(), name.clone());
+    }
+    // Real types win over aliases that only differ in case
+    for name in checker_support.keys() {
+      out.insert(name.to_ascii_lowercase(), name.clone());
+    }
+    out
+  }
   /// Mappings between modules and supported mimes
-  fn checker_support_init(checkers: &[Rc<dyn Checker>]) -> FnvHashMap<String, Rc<dyn Checker>> {
-    let mut out = FnvHashMap::<String, Rc<dyn Checker>>::default(); // TODO: restore &str
+  fn checker_support_init(
+    checkers: &[Arc<dyn Checker>],
+  ) -> FnvHashMap<String, Vec<Arc<dyn Checker>>> {
+    let mut out = FnvHashMap::<String, Vec<Arc<dyn Checker>>>::default(); // TODO: restore &str
     for c in checkers {
       for m in c.get_supported() {
-        out.insert(m.to_string(), c.to_owned());
+        out.entry(m.to_string()).or_default().push(c.to_owned());
       }
     }
     out
   }
 
-  /// Creates a new MimeDatabase from a magic file blob.
+  /// Creates a new MimeDatabase from the shared MIME info database.
+  ///
+  /// With the `bundled` feature the bundled rule set is used
+  /// if no database is installed.
+  /// The `globs2` files of the database are loaded for [`from_filename`](Self::from_filename),
+  /// and its `XMLnamespaces` files to tell XML types apart by their root element.
   pub fn new(/*magic_blob: &[u8]*/) -> MimeDatabase {
-    let ldb = Arc::new(LoadedDatabase::load_xdg_shared_magic().unwrap());
-    let checkers: Vec<Rc<dyn Checker>> = vec![
-      Rc::new(fdo_magic::builtin::check::FdoMagic::new(ldb.to_owned())),
-      Rc::new(basetype::check::BaseType::new(ldb)),
+    // The error arm depends on the `bundled` feature
+    #[allow(clippy::option_if_let_else)]
+    let mut db = match LoadedDatabase::load_xdg_shared_magic() {
+      Ok(ldb) => {
+        let fingerprint = ldb.checksum();
+        Self::from_magic(
+          Arc::new(fdo_magic::builtin::check::FdoMagic::new(Arc::new(ldb))),
+          fingerprint,
+        )
+      },
+      #[cfg(feature = "bundled")]
+      Err(_) => Self::from_provider(&crate::db::BUNDLED_DB),
+      #[cfg(not(feature = "bundled"))]
+      Err(err) => panic!("{err}"),
+    };
+    // Malformed files are skipped, the globs of the others are kept
+    let _ = db.globs.load_xdg_globs_default();
+    let mut xml_namespaces = XmlNamespaceDatabase::new();
+    let _ = xml_namespaces.load_xdg_xml_namespaces_default();
+    if xml_namespaces.rules().is_empty() {
+      db
+    } else {
+      db.with_xml_namespaces(xml_namespaces)
+    }
+  }
+
+  /// Creates a new MimeDatabase from the magic rules, aliases and subclasses
+  /// of any [`DbProvider`].
+  ///
+  /// The entries are copied, so the provider does not need to outlive the database.
+  #[must_use]
+  pub fn from_provider<'a, P: DbProvider<'a> + ?Sized>(provider: &'a P) -> Self {
+    Self::from_magic(
+      Arc::new(fdo_magic::provider::ProviderMagic::new(provider)),
+      provider_checksum(provider),
+    )
+  }
+
+  fn from_magic(magic: Arc<dyn Checker>, magic_fingerprint: u64) -> Self {
+    let mut db = Self {
+      graph: TypeStruct::new(),
+      checker_support: FnvHashMap::default(),
+      aliases: FnvHashMap::default(),
+      folded_names: FnvHashMap::default(),
+      parents: FnvHashMap::default(),
+      globs: GlobDatabase::new(),
+      magic,
+      magic_fingerprint,
+      xml_namespaces: XmlNamespaceDatabase::new(),
+      fingerprint: magic_fingerprint,
+      follow_symlinks: true,
+      text: TextClassifier::new(),
+    };
+    db.types_init();
+    db
+  }
+
+  /// Builds the type graph and lookup tables from the magic rules,
+  /// the XML root elements, the zip and compound file containers and the base types
+  fn types_init(&mut self) {
+    let mut checkers: Vec<Arc<dyn Checker>> = vec![
+      Arc::clone(&self.magic),
+      Arc::new(zip_container::check::ZipContainer),
+      Arc::new(ole_storage::check::OleStorage),
+      Arc::new(basetype::check::BaseType::new(self.text)),
     ];
-    let graph = Self::graph_init(&checkers);
-    let checker_support = Self::checker_support_init(&checkers);
-    let aliases = Self::aliases_init(&checkers);
-    MimeDatabase {
-      graph,
-      checker_support,
-      aliases,
+    if !self.xml_namespaces.rules().is_empty() {
+      checkers.push(Arc::new(xml_root::check::XmlRoot::new(
+        self.xml_namespaces.clone(),
+      )));
     }
+    self.fingerprint_init();
+    self.graph = Self::graph_init(&checkers);
+    self.checker_support = Self::checker_support_init(&checkers);
+    self.aliases = Self::aliases_init(&checkers);
+    self.folded_names = Self::folded_names_init(&self.checker_support, &self.aliases);
+    self.parents = Self::parents_init(&checkers);
   }
 
-  /// Just the part of from_*_node that walks the graph
-  fn typegraph_walker<T, F>(&self, parentnode: NodeIndex, input: &T, matchfn: F) -> Option<MIME>
-  where
-    T: ?Sized,
-    F: Fn(&str, &T) -> bool,
-  {
-    // Pull most common types towards top
+  /// Mixes the XML root elements and the settings that change detection results
+  /// into the checksum of the rules
+  fn fingerprint_init(&mut self) {
+    self.fingerprint = self.magic_fingerprint;
+    if self.xml_namespaces.rules().is_empty()
+      && self.follow_symlinks
+      && self.text == TextClassifier::new()
+    {
+      return;
+    }
+    let mut hasher = FnvHasher::with_key(self.magic_fingerprint);
+    self.xml_namespaces.hash(&mut hasher);
+    self.follow_symlinks.hash(&mut hasher);
+    self.text.max_control_ratio().to_bits().hash(&mut hasher);
+    self.fingerprint = hasher.finish();
+  }
+
+  /// A checksum of the magic rules, aliases, subclasses, XML root elements,
+  /// [text classifier](Self::with_text_classifier) and
+  /// [symbolic link handling](Self::with_follow_symlinks) that changes when they do,
+  /// to invalidate results stored for an older database.
+  ///
+  /// Databases created from a provider without XML root elements and with the default settings
+  /// have the [`checksum`](crate::db::Snapshot::checksum) that a snapshot of the provider has.
+  /// The globs are not included.
+  #[must_use]
+  pub const fn fingerprint(&self) -> u64 {
+    self.fingerprint
+  }
+
+  /// Replaces the file name globs.
+  #[must_use]
+  pub fn with_globs(mut self, globs: GlobDatabase) -> Self {
+    self.globs = globs;
+    self
+  }
+
+  /// Sets whether [`from_filepath`](Self::from_filepath) and
+  /// [`match_filepath`](Self::match_filepath) look at the targets of symbolic links,
+  /// which is the default, or report links as `inode/symlink`.
+  ///
+  /// Broken links are always `inode/symlink`.
+  #[must_use]
+  pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+    self.follow_symlinks = follow_symlinks;
+    self.fingerprint_init();
+    self
+  }
+
+  /// Whether the targets of symbolic links are looked at.
+  #[must_use]
+  pub const fn follow_symlinks(&self) -> bool {
+    self.follow_symlinks
+  }
+
+  /// Replaces how `text/plain` is told apart from binary data.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use tree_magic_rs::{db::OwnedBuildableDb, MimeDatabase, TextClassifier};
+  ///
+  /// let db = MimeDatabase::from_provider(&OwnedBuildableDb::new());
+  /// assert_eq!(db.from_u8(b"ring\x07ring\x07"), "application/octet-stream");
+  /// let db = db.with_text_classifier(TextClassifier::new().with_max_control_ratio(0.2));
+  /// assert_eq!(db.from_u8(b"ring\x07ring\x07"), "text/plain");
+  /// ```
+  #[must_use]
+  pub fn with_text_classifier(mut self, text: TextClassifier) -> Self {
+    self.text = text;
+    self.types_init();
+    self
+  }
+
+  /// How `text/plain` is told apart from binary data.
+  #[must_use]
+  pub const fn text_classifier(&self) -> TextClassifier {
+    self.text
+  }
+
+  /// Replaces the root elements that identify XML types.
+  ///
+  /// Their types become subclasses of `application/xml` and are detected in documents
+  /// of that type by the namespace and local name of the root element.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use tree_magic_rs::{db::XmlNamespaceDatabase, MimeDatabase};
+  ///
+  /// let mut xml_namespaces = XmlNamespaceDatabase::new();
+  /// xml_namespaces
+  ///   .load_xml_namespaces_str("urn:example:recipes recipe application/x-recipe+xml")
+  ///   .unwrap();
+  /// let db = MimeDatabase::new().with_xml_namespaces(xml_namespaces);
+  /// let recipe = b"<?xml version=\"1.0\"?>\n<r:recipe xmlns:r=\"urn:example:recipes\"/>";
+  /// assert_eq!(db.from_u8(recipe), "application/x-recipe+xml");
+  /// ```
+  #[must_use]
+  pub fn with_xml_namespaces(mut self, xml_namespaces: XmlNamespaceDatabase) -> Self {
+    self.xml_namespaces = xml_namespaces;
+    self.types_init();
+    self
+  }
+
+  /// The root elements that identify XML types.
+  #[must_use]
+  pub const fn xml_namespaces(&self) -> &XmlNamespaceDatabase {
+    &self.xml_namespaces
+  }
+
+  /// The file name globs.
+  #[must_use]
+  pub const fn globs(&self) -> &GlobDatabase {
+    &self.globs
+  }
+
+  /// The file name globs, to load more sources like `mime.types` files.
+  pub const fn globs_mut(&mut self) -> &mut GlobDatabase {
+    &mut self.globs
+  }
+
+  /// Gets the type of a file from its name, using the loaded globs.
+  ///
+  /// Does not look at the contents. Aliases are resolved to their real type.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use tree_magic_rs::{db::GlobDatabase, db::OwnedBuildableDb, MimeDatabase};
+  ///
+  /// let mut globs = GlobDatabase::new();
+  /// globs.load_mime_types_str("image/webp webp").unwrap();
+  /// let db = MimeDatabase::from_provider(&OwnedBuildableDb::new()).with_globs(globs);
+  /// assert_eq!(db.from_filename("photos/cat.WEBP"), Some("image/webp"));
+  /// ```
+  #[must_use]
+  pub fn from_filename(&self, file_name: &str) -> Option<MIME<'_>> {
+    self
+      .globs
+      .from_filename(file_name)
+      .map(|mimetype| self.get_alias(mimetype))
+  }
+
+  /// Returns the file extensions of a MIME type in all loaded globs,
+  /// highest weight first.
+  ///
+  /// Globs of aliases of the MIME type are included.
+  #[must_use]
+  pub fn extensions_for<M: MimeLike + ?Sized>(&self, mimetype: &M) -> Vec<&str> {
+    let essence = mimetype.essence();
+    let mimetype = self.get_alias(&essence);
+    let mut aliases: Vec<&str> = self
+      .aliases
+      .iter()
+      .filter(|(_, name)| *name == mimetype)
+      .map(|(alias, _)| alias.as_str())
+      .collect();
+    aliases.sort_unstable();
+    let mut extensions = self.globs.extensions_for(mimetype);
+    for alias in aliases {
+      for extension in self.globs.extensions_for(alias) {
+        if !extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)) {
+          extensions.push(extension);
+        }
+      }
+    }
+    extensions
+  }
+
+  /// The direct supertypes of a MIME type.
+  ///
+  /// Like in the type graph, types without a known parent are children of `text/plain`,
+  /// `all/all` or `application/octet-stream`, depending on their top-level type.
+  fn parents_of(&self, mimetype: &str) -> Vec<&str> {
+    let mut parents: Vec<&str> = self.parents.get(mimetype).map_or_else(Vec::new, |parents| {
+      parents.iter().map(String::as_str).collect()
+    });
+    if let Some(parent) = MediaType::from_essence(mimetype).suffix_parent() {
+      if !parents.contains(&parent) {
+        parents.push(parent);
+      }
+    }
+    if parents.is_empty() {
+      let generic = match mimetype.split('/').next() {
+        _ if ["text/plain", "application/octet-stream"].contains(&mimetype) => return parents,
+        Some("all") => return parents,
+        Some("text") => "text/plain",
+        Some("inode") => "all/all",
+        _ => "application/octet-stream",
+      };
+      parents.push(generic);
+    }
+    parents
+  }
+
+  /// The supertypes of a MIME type, grouped by distance, nearest first.
+  fn supertype_levels(&self, mimetype: &str) -> Vec<Vec<&str>> {
+    let mut seen = FnvHashSet::default();
+    seen.insert(mimetype.to_string());
+    let mut levels = Vec::<Vec<&str>>::new();
+    let mut level = self.parents_of(mimetype);
+    while !level.is_empty() {
+      level.retain(|parent| seen.insert((*parent).to_string()));
+      let next = level
+        .iter()
+        .flat_map(|parent| self.parents_of(parent))
+        .collect();
+      if !level.is_empty() {
+        levels.push(level);
+      }
+      level = next;
+    }
+    levels
+  }
+
+  /// Whether `parent` is a direct or indirect supertype of `mimetype`.
+  fn is_subtype(&self, mimetype: &str, parent: &str) -> bool {
+    self
+      .supertype_levels(mimetype)
+      .iter()
+      .flatten()
+      .any(|&supertype| supertype == parent)
+  }
+
+  /// Checks if a MIME type is the same as or a subclass of another one.
+  ///
+  /// Aliases are resolved and types compare case-insensitively.
+  /// Types without a known parent are subclasses of `text/plain` or `application/octet-stream`,
+  /// depending on their top-level type.
+  #[must_use]
+  pub fn is_a<M: MimeLike + ?Sized, P: MimeLike + ?Sized>(&self, mimetype: &M, parent: &P) -> bool {
+    let (mimetype, parent) = (mimetype.essence(), parent.essence());
+    let (mimetype, parent) = (self.get_alias(&mimetype), self.get_alias(&parent));
+    mimetype.eq_ignore_ascii_case(parent)
+      || self
+        .supertype_levels(mimetype)
+        .iter()
+        .flatten()
+        .any(|supertype| supertype.eq_ignore_ascii_case(parent))
+  }
+
+  /// The direct supertypes of a MIME type, after resolving aliases.
+  ///
+  /// Types without a known parent get the generic parent that [`is_a`](Self::is_a) uses,
+  /// up to the `all/all` root.
+  #[must_use]
+  pub fn parents<M: MimeLike + ?Sized>(&self, mimetype: &M) -> Vec<&str> {
+    let mimetype = mimetype.essence();
+    self.parents_of(self.get_alias(&mimetype))
+  }
+
+  /// The direct subtypes of a MIME type, after resolving aliases, sorted by name.
+  ///
+  /// The types that have it among their [`parents`](Self::parents), out of those with
+  /// magic rules or subclass entries.
+  #[must_use]
+  pub fn children<M: MimeLike + ?Sized>(&self, mimetype: &M) -> Vec<&str> {
+    let mimetype = mimetype.essence();
+    let mimetype = self.get_alias(&mimetype);
+    let mut children: Vec<&str> = self
+      .checker_support
+      .keys()
+      .chain(self.parents.keys())
+      .map(String::as_str)
+      .filter(|child| {
+        self
+          .parents_of(child)
+          .iter()
+          .any(|parent| parent.eq_ignore_ascii_case(mimetype))
+      })
+      .collect();
+    children.sort_unstable();
+    children.dedup();
+    children
+  }
+
+  /// Returns the file extensions to use for a MIME type, highest weight first.
+  ///
+  /// Like [`extensions_for`](Self::extensions_for), but if neither the type nor its aliases
+  /// have extensions, those of the nearest parent types are used, e.g. `txt` for text formats.
+  /// The generic `application/octet-stream` and `all/*` types are not used as fallbacks.
+  #[must_use]
+  pub fn extensions<M: MimeLike + ?Sized>(&self, mimetype: &M) -> Vec<&str> {
+    let essence = mimetype.essence();
+    let extensions = self.extensions_for(essence.as_ref());
+    if !extensions.is_empty() {
+      return extensions;
+    }
+    let mimetype = self.get_alias(&essence);
+    for level in self.supertype_levels(mimetype) {
+      let mut extensions = Vec::<&str>::new();
+      for parent in level {
+        if parent == "application/octet-stream" || parent.starts_with("all/") {
+          continue;
+        }
+        for extension in self.extensions_for(parent) {
+          if !extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)) {
+            extensions.push(extension);
+          }
+        }
+      }
+      if !extensions.is_empty() {
+        return extensions;
+      }
+    }
+    Vec::new()
+  }
+
+  /// Returns the preferred file extension of a MIME type, see [`extensions`](Self::extensions).
+  ///
+  /// # Examples
+  /// ```rust
+  /// use tree_magic_rs::{db::GlobDatabase, db::OwnedBuildableDb, MimeDatabase};
+  ///
+  /// let mut globs = GlobDatabase::new();
+  /// globs.load_mime_types_str("image/jpeg jpeg jpg jpe").unwrap();
+  /// let db = MimeDatabase::from_provider(&OwnedBuildableDb::new()).with_globs(globs);
+  /// assert_eq!(db.preferred_extension("image/jpeg"), Some("jpeg"));
+  /// ```
+  #[must_use]
+  pub fn preferred_extension<M: MimeLike + ?Sized>(&self, mimetype: &M) -> Option<&str> {
+    self.extensions(mimetype).first().copied()
+  }
+
+  /// Suggests a file name whose extension agrees with the MIME type of the contents.
+  ///
+  /// Returns `None` if the name already agrees, meaning its extension is one of the
+  /// [`extensions`](Self::extensions) of the type or its globs match the type or a subtype,
+  /// or if the type has no extensions. Otherwise the last extension is replaced,
+  /// or the preferred extension appended if the name has none.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use tree_magic_rs::{db::GlobDatabase, db::OwnedBuildableDb, MimeDatabase};
+  ///
+  /// let mut globs = GlobDatabase::new();
+  /// globs.load_mime_types_str("image/png png\nimage/jpeg jpeg jpg").unwrap();
+  /// let db = MimeDatabase::from_provider(&OwnedBuildableDb::new()).with_globs(globs);
+  /// assert_eq!(
+  ///   db.suggest_filename("downloads/cat.jpg", "image/png"),
+  ///   Some("downloads/cat.png".to_string())
+  /// );
+  /// assert_eq!(db.suggest_filename("cat.JPG", "image/jpeg"), None);
+  /// ```
+  #[must_use]
+  pub fn suggest_filename<M: MimeLike + ?Sized>(
+    &self,
+    file_name: &str,
+    mimetype: &M,
+  ) -> Option<String> {
+    let essence = mimetype.essence();
+    let mimetype = self.get_alias(&essence);
+    let (dir, base) = file_name
+      .rfind('/')
+      .map_or(("", file_name), |i| file_name.split_at(i + 1));
+    // A leading dot marks a hidden file, not an extension
+    let dot = base.rfind('.').filter(|&i| i > 0);
+    let extensions = self.extensions(mimetype);
+    if let Some(dot) = dot {
+      let extension = &base[dot + 1..];
+      if extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)) {
+        return None;
+      }
+    }
+    if let Some(name_type) = self.from_filename(base) {
+      if name_type == mimetype || self.is_subtype(name_type, mimetype) {
+        return None;
+      }
+    }
+    let preferred = extensions.first()?;
+    let stem = dot.map_or(base, |dot| &base[..dot]);
+    Some(format!("{dir}{stem}.{preferred}"))
+  }
+
+  /// Compares the type of a file name with the type of its contents,
+  /// to catch renamed files like an executable uploaded as `photo.jpg`.
+  ///
+  /// Every glob matching the name is compared with the detected content type
+  /// through the subclass graph and the closest relation is reported.
+  /// Contents without a specific type, like `application/octet-stream`, are undecidable.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use tree_magic_rs::{db::GlobDatabase, db::OwnedBuildableDb, MimeDatabase, VerdictKind};
+  ///
+  /// let mut globs = GlobDatabase::new();
+  /// globs.load_mime_types_str("image/jpeg jpg").unwrap();
+  /// let db = MimeDatabase::from_provider(&OwnedBuildableDb::new()).with_globs(globs);
+  /// let verdict = db.validate("uploads/photo.jpg", b"hello");
+  /// assert_eq!(verdict.kind, VerdictKind::Mismatched);
+  /// assert_eq!(verdict.name_type, Some("image/jpeg"));
+  /// assert_eq!(verdict.content_type, "text/plain");
+  /// ```
+  #[must_use]
+  pub fn validate<P: AsRef<Path> + ?Sized>(&self, path_or_name: &P, bytes: &[u8]) -> Verdict<'_> {
+    let content_type = self.from_u8(bytes);
+    let file_name = path_or_name
+      .as_ref()
+      .file_name()
+      .map_or_else(Default::default, |name| name.to_string_lossy());
+    let name_types: Vec<&str> = self
+      .globs
+      .match_filename(&file_name)
+      .iter()
+      .map(|glob| self.get_alias(&glob.mime_type))
+      .collect();
+    let Some(&first) = name_types.first() else {
+      return Verdict {
+        kind: VerdictKind::Undecidable,
+        name_type: None,
+        content_type,
+      };
+    };
+    if content_type == "application/octet-stream" || content_type.starts_with("all/") {
+      return Verdict {
+        kind: VerdictKind::Undecidable,
+        name_type: Some(first),
+        content_type,
+      };
+    }
+    let classify = |name_type: &str| {
+      if name_type == content_type {
+        VerdictKind::Consistent
+      } else if self.is_subtype(name_type, content_type) || self.is_subtype(content_type, name_type)
+      {
+        VerdictKind::Compatible
+      } else {
+        VerdictKind::Mismatched
+      }
+    };
+    // The best glob for the closest relation wins
+    let (kind, name_type) = [VerdictKind::Consistent, VerdictKind::Compatible]
+      .into_iter()
+      .find_map(|kind| {
+        name_types
+          .iter()
+          .find(|name_type| classify(name_type) == kind)
+          .map(|&name_type| (kind, name_type))
+      })
+      .unwrap_or((VerdictKind::Mismatched, first));
+    Verdict {
+      kind,
+      name_type: Some(name_type),
+      content_type,
+    }
+  }
+
+  /// The children of a node in the order they are checked,
+  /// with the most common types pulled towards the top
+  /// and `text/plain` last, so magic wins over telling text from binary data
+  fn ordered_children(&self, parentnode: NodeIndex) -> Vec<NodeIndex> {
     let mut children: Vec<NodeIndex> = self
       .graph
       .neighbors_directed(parentnode, Outgoing)
@@ -197,9 +786,21 @@ impl MimeDatabase {
         children.insert(0, x);
       }
     }
+    if let Some(i) = children.iter().position(|&x| self.graph[x] == "text/plain") {
+      let x = children.remove(i);
+      children.push(x);
+    }
+    children
+  }
 
+  /// Just the part of from_*_node that walks the graph
+  fn typegraph_walker<T, F>(&self, parentnode: NodeIndex, input: &T, matchfn: F) -> Option<MIME<'_>>
+  where
+    T: ?Sized,
+    F: Fn(&str, &T) -> bool,
+  {
     // Walk graph
-    for childnode in children {
+    for childnode in self.ordered_children(parentnode) {
       let mimetype = &self.graph[childnode];
 
       let result = matchfn(mimetype, input);
@@ -218,17 +819,33 @@ impl MimeDatabase {
   /// Internal function. Checks if an alias exists, and if it does,
   /// then runs `from_u8`.
   fn match_u8_noalias(&self, mimetype: &str, bytes: &[u8]) -> bool {
-    match self.checker_support.get(mimetype) {
-      None => false,
-      Some(y) => y.from_u8(bytes, mimetype),
-    }
+    self
+      .checker_support
+      .get(mimetype)
+      .is_some_and(|checkers| checkers.iter().any(|c| c.from_u8(bytes, mimetype)))
+  }
+  /// The name of a type of the graph, with the lifetime of the database
+  #[cfg(feature = "cache")]
+  pub(crate) fn graph_type(&self, mimetype: &str) -> Option<MIME<'_>> {
+    self
+      .checker_support
+      .get_key_value(mimetype)
+      .map(|(name, _)| name.as_str())
   }
   /// Transforms an alias into it's real type
+  ///
+  /// Falls back to a case-insensitive lookup as MIME types are case-insensitive.
   fn get_alias<'a>(&'a self, mimetype: &'a str) -> &'a str {
-    match self.aliases.get(mimetype) {
-      Some(x) => x,
-      None => mimetype,
+    if let Some(x) = self.aliases.get(mimetype) {
+      return x;
+    }
+    if self.checker_support.contains_key(mimetype) {
+      return mimetype;
     }
+    self
+      .folded_names
+      .get(&mimetype.to_ascii_lowercase())
+      .map_or(mimetype, |x| x)
   }
   /// Gets the type of a file from a raw bytestream, starting at a certain node
   /// in the type graph.
@@ -242,8 +859,8 @@ impl MimeDatabase {
   /// As the graph is immutable, this should not happen if the node index comes from
   /// TYPE.hash.
   fn from_u8_node(&self, parentnode: NodeIndex, bytes: &[u8]) -> Option<MIME> {
-    self.typegraph_walker(parentnode, bytes, |mimetype, bytes| {
-      self.match_u8_noalias(mimetype, bytes)
+    self.typegraph_walker(parentnode, &Sniffed::new(bytes, None), |mimetype, input| {
+      self.match_sniffed_noalias(mimetype, input)
     })
   }
   /// Checks if the given bytestream matches the given MIME type.
@@ -251,6 +868,7 @@ impl MimeDatabase {
   /// Returns true or false if it matches or not. If the given MIME type is not known,
   /// the function will always return false.
   /// If mimetype is an alias of a known MIME, the file will be checked agains that MIME.
+  /// The MIME type may be given as a string or a [`MediaType`]; parameters are ignored.
   ///
   /// # Examples
   /// ```rust
@@ -261,8 +879,9 @@ impl MimeDatabase {
   /// let result = tree_magic_rs::match_u8("image/gif", input);
   /// assert_eq!(result, true);
   /// ```
-  pub fn match_u8(&self, mimetype: &str, bytes: &[u8]) -> bool {
-    self.match_u8_noalias(self.get_alias(mimetype), bytes)
+  pub fn match_u8<M: MimeLike + ?Sized>(&self, mimetype: &M, bytes: &[u8]) -> bool {
+    let mimetype = mimetype.essence();
+    self.match_u8_noalias(self.get_alias(&mimetype), bytes)
   }
   /// Gets the type of a file from a byte stream.
   ///
@@ -284,13 +903,51 @@ impl MimeDatabase {
     };
     self.from_u8_node(node, bytes).unwrap()
   }
+  /// Gets the type of a file from a byte stream as a [`MediaType`].
+  ///
+  /// See [`from_u8`](Self::from_u8).
+  #[must_use]
+  pub fn media_type_from_u8(&self, bytes: &[u8]) -> MediaType<'_> {
+    MediaType::from_essence(self.from_u8(bytes))
+  }
+  /// Gets the types that matched on the way to the type of a byte stream, most generic first.
+  ///
+  /// The last type is the result of [`from_u8`](Self::from_u8); the ones before it are
+  /// the supertypes whose rules had to match first, which explains how it was found.
+  ///
+  /// # Panics
+  /// Will panic if no filetype definitions are loaded.
+  #[must_use]
+  pub fn path_from_u8(&self, bytes: &[u8]) -> Vec<MIME<'_>> {
+    let Some(mut node) = self.graph.externals(Incoming).next() else {
+      panic!("No filetype definitions are loaded.");
+    };
+    let input = Sniffed::new(bytes, None);
+    let mut path = Vec::new();
+    while let Some(childnode) = self
+      .ordered_children(node)
+      .into_iter()
+      .find(|&childnode| self.match_sniffed_noalias(&self.graph[childnode], &input))
+    {
+      path.push(self.graph[childnode].as_str());
+      node = childnode;
+    }
+    path
+  }
   /// Internal function. Checks if an alias exists, and if it does,
   /// then runs `from_filepath`.
   fn match_filepath_noalias(&self, mimetype: &str, filepath: &Path) -> bool {
-    match self.checker_support.get(mimetype) {
-      None => false,
-      Some(c) => c.from_filepath(&filepath, mimetype),
-    }
+    self
+      .checker_support
+      .get(mimetype)
+      .is_some_and(|checkers| checkers.iter().any(|c| c.from_filepath(filepath, mimetype)))
+  }
+  /// Checks bytes that all types of a walk are matched against
+  fn match_sniffed_noalias(&self, mimetype: &str, input: &Sniffed<'_>) -> bool {
+    self
+      .checker_support
+      .get(mimetype)
+      .is_some_and(|checkers| checkers.iter().any(|c| input.matches(c.as_ref(), mimetype)))
   }
   /// Check if the given filepath matches the given MIME type.
   ///
@@ -308,8 +965,25 @@ impl MimeDatabase {
   /// let result = tree_magic_mini::match_filepath("image/gif", path);
   /// assert_eq!(result, true);
   /// ```
-  pub fn match_filepath(&self, mimetype: &str, filepath: &Path) -> bool {
-    self.match_filepath_noalias(self.get_alias(mimetype), filepath)
+  pub fn match_filepath<M: MimeLike + ?Sized>(&self, mimetype: &M, filepath: &Path) -> bool {
+    let mimetype = mimetype.essence();
+    let mimetype = self.get_alias(&mimetype);
+    let filepath = self.resolve_symlink(filepath);
+    // The contents behind links that are not followed are not looked at
+    if filepath.is_symlink() {
+      return self.is_a("inode/symlink", mimetype);
+    }
+    self.match_filepath_noalias(mimetype, &filepath)
+  }
+
+  /// The target of a symbolic link if links are followed and the target exists
+  fn resolve_symlink<'p>(&self, filepath: &'p Path) -> Cow<'p, Path> {
+    if self.follow_symlinks && filepath.is_symlink() {
+      if let Ok(target) = fs::canonicalize(filepath) {
+        return Cow::Owned(target);
+      }
+    }
+    Cow::Borrowed(filepath)
   }
 
   /// Gets the type of a file from a filepath, starting at a certain node
@@ -343,7 +1017,11 @@ impl MimeDatabase {
       Err(_) => return None,
     };
 
-    self.from_u8_node(parentnode, b.as_slice())
+    self.typegraph_walker(
+      parentnode,
+      &Sniffed::new(&b, Some(filepath)),
+      |mimetype, input| self.match_sniffed_noalias(mimetype, input),
+    )
   }
   /// Gets the type of a file from a filepath.
   ///
@@ -351,6 +1029,11 @@ impl MimeDatabase {
   /// Returns MIME as string wrapped in Some if a type matches, or
   /// None if the file is not found or cannot be opened.
   ///
+  /// Directories, devices, FIFOs and sockets get an `inode/*` type without being opened
+  /// and empty files are `application/x-zerosize`.
+  /// Symbolic links are followed unless disabled with
+  /// [`with_follow_symlinks`](Self::with_follow_symlinks).
+  ///
   /// # Examples
   /// ```rust
   /// use std::path::Path;
@@ -368,13 +1051,21 @@ impl MimeDatabase {
       None => panic!("No filetype definitions are loaded."),
     };
 
-    self.from_filepath_node(node, filepath)
+    self.from_filepath_node(node, &self.resolve_symlink(filepath))
+  }
+  /// Gets the type of a file from a filepath as a [`MediaType`].
+  ///
+  /// See [`from_filepath`](Self::from_filepath).
+  #[must_use]
+  pub fn media_type_from_filepath(&self, filepath: &Path) -> Option<MediaType<'_>> {
+    self.from_filepath(filepath).map(MediaType::from_essence)
   }
 }
 
 /// Check these types first
 /// TODO: Poll these from the checkers? Feels a bit arbitrary
-const TYPEORDER: [&str; 6] = [
+const TYPEORDER: [&str; 7] = [
+  "application/x-zerosize",
   "image/png",
   "image/jpeg",
   "image/gif",
@@ -383,14 +1074,77 @@ const TYPEORDER: [&str; 6] = [
   "application/pdf",
 ];
 
+// The methods are named after what they match, like the `from_*` methods of the database
+#[allow(clippy::wrong_self_convention)]
 pub(crate) trait Checker: Send + Sync {
   fn from_u8(&self, file: &[u8], mimetype: &str) -> bool;
   fn from_filepath(&self, filepath: &Path, mimetype: &str) -> bool;
+  /// Matches a file whose first bytes were already read for all checkers.
+  ///
+  /// Checkers that look past the start of a file override this to read it themselves.
+  fn from_prefix(&self, prefix: &[u8], _filepath: &Path, mimetype: &str) -> bool {
+    self.from_u8(prefix, mimetype)
+  }
+  /// The checker as an [`Identifier`], if its types exclude each other
+  fn identifier(&self) -> Option<&dyn Identifier> {
+    None
+  }
   fn get_supported(&self) -> Vec<MIME>;
   fn get_subclasses(&self) -> Vec<(MIME, MIME)>;
   fn get_aliaslist(&self) -> FnvHashMap<MIME, MIME>;
 }
 
+/// A checker that finds the one type of a file in a single read, instead of matching type by type.
+///
+/// Detection asks it once per file and compares the result with each of its types.
+pub trait Identifier {
+  fn identify_u8(&self, file: &[u8]) -> Option<MIME<'static>>;
+  /// Identifies a file whose first bytes were already read for all checkers
+  fn identify_prefix(&self, prefix: &[u8], filepath: &Path) -> Option<MIME<'static>>;
+}
+
+/// The bytes that a type graph walk matches, with the types identifiers found in them
+struct Sniffed<'a> {
+  bytes: &'a [u8],
+  /// The file that `bytes` are the start of
+  filepath: Option<&'a Path>,
+  /// The type found by each identifier, by its address
+  identified: RefCell<Vec<(*const (), Option<MIME<'static>>)>>,
+}
+
+impl<'a> Sniffed<'a> {
+  const fn new(bytes: &'a [u8], filepath: Option<&'a Path>) -> Self {
+    Self {
+      bytes,
+      filepath,
+      identified: RefCell::new(Vec::new()),
+    }
+  }
+
+  fn matches(&self, checker: &dyn Checker, mimetype: &str) -> bool {
+    if let Some(identifier) = checker.identifier() {
+      return self.identified(identifier) == Some(mimetype);
+    }
+    self.filepath.map_or_else(
+      || checker.from_u8(self.bytes, mimetype),
+      |filepath| checker.from_prefix(self.bytes, filepath, mimetype),
+    )
+  }
+
+  fn identified(&self, identifier: &dyn Identifier) -> Option<MIME<'static>> {
+    let key = std::ptr::from_ref(identifier).cast::<()>();
+    if let Some(&(_, found)) = self.identified.borrow().iter().find(|(k, _)| *k == key) {
+      return found;
+    }
+    let found = self.filepath.map_or_else(
+      || identifier.identify_u8(self.bytes),
+      |filepath| identifier.identify_prefix(self.bytes, filepath),
+    );
+    self.identified.borrow_mut().push((key, found));
+    found
+  }
+}
+
 /// Reads the given number of bytes from a file
 pub fn read_bytes(filepath: &Path, bytecount: usize) -> Result<Vec<u8>, std::io::Error> {
   use std::fs::File;
@@ -0,0 +1,36 @@
+//! Results of comparing the type of a file name with the type of its contents.
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// How the type of a file name relates to the type of its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum VerdictKind {
+  /// The name and the contents have the same type
+  Consistent,
+  /// One type is a subclass of the other, like a `.docx` name for ZIP contents
+  Compatible,
+  /// The types are unrelated, like an `.jpg` name for an executable
+  Mismatched,
+  /// The name matches no glob or the contents have no specific type
+  Undecidable,
+}
+
+/// The result of [`MimeDatabase::validate`](crate::MimeDatabase::validate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Verdict<'a> {
+  /// How the types relate
+  pub kind: VerdictKind,
+  /// The type derived from the file name, if any glob matched
+  pub name_type: Option<&'a str>,
+  /// The type derived from the contents
+  pub content_type: &'a str,
+}
+impl Verdict<'_> {
+  /// Whether the name agrees with the contents, exactly or through a subclass.
+  #[must_use]
+  pub const fn is_acceptable(&self) -> bool {
+    matches!(self.kind, VerdictKind::Consistent | VerdictKind::Compatible)
+  }
+}
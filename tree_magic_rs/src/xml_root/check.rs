@@ -0,0 +1,161 @@
+use crate::db::XmlNamespaceDatabase;
+use crate::{read_bytes, MIME};
+use fnv::FnvHashMap;
+use std::borrow::Cow;
+use std::path::Path;
+
+/// How many bytes are scanned for the root element, which doctypes and comments can push back
+const READ_LEN: usize = 4096;
+
+pub struct XmlRoot {
+  namespaces: XmlNamespaceDatabase,
+}
+
+impl XmlRoot {
+  pub const fn new(namespaces: XmlNamespaceDatabase) -> Self {
+    Self { namespaces }
+  }
+}
+
+impl crate::Checker for XmlRoot {
+  fn from_u8(&self, file: &[u8], mimetype: &str) -> bool {
+    let text = decode(&file[..file.len().min(READ_LEN)]);
+    root_element(&text).is_some_and(|(namespace_uri, local_name)| {
+      self.namespaces.match_root(namespace_uri, local_name) == Some(mimetype)
+    })
+  }
+
+  fn from_filepath(&self, filepath: &Path, mimetype: &str) -> bool {
+    read_bytes(filepath, READ_LEN).is_ok_and(|b| self.from_u8(&b, mimetype))
+  }
+
+  fn get_supported(&self) -> Vec<MIME<'_>> {
+    let mut supported: Vec<MIME<'_>> = self
+      .namespaces
+      .rules()
+      .iter()
+      .map(|rule| rule.mime_type.as_str())
+      .collect();
+    supported.sort_unstable();
+    supported.dedup();
+    supported
+  }
+
+  fn get_subclasses(&self) -> Vec<(MIME<'_>, MIME<'_>)> {
+    self
+      .get_supported()
+      .into_iter()
+      .map(|mimetype| ("application/xml", mimetype))
+      .collect()
+  }
+
+  fn get_aliaslist(&self) -> FnvHashMap<MIME<'_>, MIME<'_>> {
+    FnvHashMap::default()
+  }
+}
+
+/// The text of a document, in UTF-8 or in UTF-16 with or without byte order mark
+fn decode(b: &[u8]) -> Cow<'_, str> {
+  let utf16 = |b: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+    let units = b.as_chunks().0.iter().map(|&unit| from_bytes(unit));
+    Cow::Owned(
+      char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect(),
+    )
+  };
+  match b {
+    [0xef, 0xbb, 0xbf, rest @ ..] => String::from_utf8_lossy(rest),
+    [0xff, 0xfe, rest @ ..] => utf16(rest, u16::from_le_bytes),
+    [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+    [b'<', 0, ..] => utf16(b, u16::from_le_bytes),
+    [0, b'<', ..] => utf16(b, u16::from_be_bytes),
+    _ => String::from_utf8_lossy(b),
+  }
+}
+
+const fn is_xml_space(c: char) -> bool {
+  matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+/// The text after the first `end`
+fn skip_past<'a>(text: &'a str, end: &str) -> Option<&'a str> {
+  text.find(end).map(|i| &text[i + end.len()..])
+}
+
+/// The text after a doctype, whose internal subset may contain `>`
+fn skip_doctype(text: &str) -> Option<&str> {
+  let mut quote = None;
+  let mut subset = false;
+  for (i, c) in text.char_indices() {
+    match (quote, c) {
+      (Some(q), c) if c == q => quote = None,
+      (None, '"' | '\'') => quote = Some(c),
+      (None, '[') => subset = true,
+      (None, ']') => subset = false,
+      (None, '>') if !subset => return Some(&text[i + 1..]),
+      _ => {},
+    }
+  }
+  None
+}
+
+/// The namespace and local name of the root element.
+///
+/// Only the prologue and the root start tag are parsed. `None` if the text does not
+/// start like an XML document or is cut off before the end of the start tag.
+fn root_element(text: &str) -> Option<(&str, &str)> {
+  let mut rest = text;
+  loop {
+    rest = rest.trim_start_matches(is_xml_space);
+    if let Some(after) = rest.strip_prefix("<?") {
+      rest = skip_past(after, "?>")?;
+    } else if let Some(after) = rest.strip_prefix("<!--") {
+      rest = skip_past(after, "-->")?;
+    } else if let Some(after) = rest.strip_prefix("<!DOCTYPE") {
+      rest = skip_doctype(after)?;
+    } else {
+      return start_tag(rest.strip_prefix('<')?);
+    }
+  }
+}
+
+/// Resolves the name of a start tag against the namespaces it declares
+fn start_tag(text: &str) -> Option<(&str, &str)> {
+  let name_end = text.find(|c| is_xml_space(c) || c == '/' || c == '>')?;
+  let name = &text[..name_end];
+  let mut rest = &text[name_end..];
+  // Declared prefixes and their namespaces, the default namespace has an empty prefix
+  let mut namespaces = Vec::<(&str, &str)>::new();
+  loop {
+    rest = rest.trim_start_matches(is_xml_space);
+    if rest.starts_with('>') || rest.starts_with("/>") {
+      break;
+    }
+    let (attribute, after) = rest.split_once('=')?;
+    let attribute = attribute.trim_end_matches(is_xml_space);
+    if attribute.is_empty() || attribute.contains(|c| is_xml_space(c) || c == '>') {
+      return None;
+    }
+    let after = after.trim_start_matches(is_xml_space);
+    let quote = after.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let (value, after) = after[1..].split_once(quote)?;
+    if attribute == "xmlns" {
+      namespaces.push(("", value));
+    } else if let Some(prefix) = attribute.strip_prefix("xmlns:") {
+      namespaces.push((prefix, value));
+    }
+    rest = after;
+  }
+  let (prefix, local_name) = name.split_once(':').unwrap_or(("", name));
+  if local_name.is_empty() {
+    return None;
+  }
+  let namespace_uri = match namespaces.iter().find(|(p, _)| *p == prefix) {
+    Some((_, namespace_uri)) => namespace_uri,
+    // Undeclared prefixes are not well-formed
+    None if prefix.is_empty() => "",
+    None => return None,
+  };
+  Some((namespace_uri, local_name))
+}
@@ -0,0 +1,2 @@
+//! Handles XML types identified by the namespace and name of their root element
+pub mod check;
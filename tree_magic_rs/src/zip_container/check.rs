@@ -0,0 +1,194 @@
+use super::{APK_TYPE, JAR_TYPE, MIMETYPE_ENTRY_TYPES, OOXML_TYPES};
+use crate::{read_bytes, Identifier, MIME};
+use fnv::FnvHashMap;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// How much of a file is read for the local headers if it was not read before
+const READ_LEN: usize = 4096;
+/// The end of central directory record and the longest comment
+const EOCD_SEARCH_LEN: u64 = 22 + 0xffff;
+/// Larger central directories are not read
+const MAX_CENTRAL_DIRECTORY_LEN: u64 = 1 << 22;
+
+pub struct ZipContainer;
+
+impl crate::Checker for ZipContainer {
+  fn from_u8(&self, file: &[u8], mimetype: &str) -> bool {
+    self.identify_u8(file) == Some(mimetype)
+  }
+
+  fn from_filepath(&self, filepath: &Path, mimetype: &str) -> bool {
+    read_bytes(filepath, READ_LEN).is_ok_and(|prefix| self.from_prefix(&prefix, filepath, mimetype))
+  }
+
+  fn from_prefix(&self, prefix: &[u8], filepath: &Path, mimetype: &str) -> bool {
+    self.identify_prefix(prefix, filepath) == Some(mimetype)
+  }
+
+  fn identifier(&self) -> Option<&dyn Identifier> {
+    Some(self)
+  }
+
+  fn get_supported(&self) -> Vec<MIME<'_>> {
+    MIMETYPE_ENTRY_TYPES
+      .into_iter()
+      .chain(OOXML_TYPES.map(|(_, mimetype)| mimetype))
+      .chain([JAR_TYPE, APK_TYPE])
+      .collect()
+  }
+
+  fn get_subclasses(&self) -> Vec<(MIME<'_>, MIME<'_>)> {
+    self
+      .get_supported()
+      .into_iter()
+      .map(|mimetype| ("application/zip", mimetype))
+      .collect()
+  }
+
+  fn get_aliaslist(&self) -> FnvHashMap<MIME<'_>, MIME<'_>> {
+    FnvHashMap::default()
+  }
+}
+
+impl Identifier for ZipContainer {
+  fn identify_u8(&self, file: &[u8]) -> Option<MIME<'static>> {
+    ZipListing::from_u8(file).container_type()
+  }
+
+  fn identify_prefix(&self, prefix: &[u8], filepath: &Path) -> Option<MIME<'static>> {
+    ZipListing::from_prefix(prefix, filepath)
+      .ok()?
+      .container_type()
+  }
+}
+
+/// The entries found in the local headers and the central directory of a zip file
+#[derive(Default)]
+struct ZipListing {
+  /// The contents of a stored `mimetype` first entry
+  mimetype: Option<String>,
+  names: Vec<String>,
+}
+
+impl ZipListing {
+  fn from_u8(b: &[u8]) -> Self {
+    let mut listing = Self::default();
+    listing.add_local_entries(b);
+    // Reading from memory only fails if the archive is cut off
+    let _ = listing.add_central_directory(&mut Cursor::new(b));
+    listing
+  }
+
+  /// Seeks to the central directory of a file whose start was already read
+  fn from_prefix(prefix: &[u8], filepath: &Path) -> io::Result<Self> {
+    let mut listing = Self::default();
+    listing.add_local_entries(prefix);
+    listing.add_central_directory(&mut File::open(filepath)?)?;
+    Ok(listing)
+  }
+
+  /// The format stored in the archive, if it is one of the known ones
+  fn container_type(&self) -> Option<&'static str> {
+    if let Some(mimetype) = &self.mimetype {
+      if let Some(found) = MIMETYPE_ENTRY_TYPES.iter().find(|t| *t == mimetype) {
+        return Some(found);
+      }
+    }
+    let has = |name: &str| self.names.iter().any(|n| n == name);
+    if has("[Content_Types].xml") {
+      if let Some((_, mimetype)) = OOXML_TYPES
+        .iter()
+        .find(|(dir, _)| self.names.iter().any(|n| n.starts_with(dir)))
+      {
+        return Some(mimetype);
+      }
+    }
+    // APKs are JARs too
+    if has("AndroidManifest.xml") {
+      return Some(APK_TYPE);
+    }
+    if has("META-INF/MANIFEST.MF") {
+      return Some(JAR_TYPE);
+    }
+    None
+  }
+
+  /// Walks the local headers from the start until one is cut off
+  fn add_local_entries(&mut self, b: &[u8]) {
+    let mut pos = 0;
+    while let Some(header) = b
+      .get(pos..pos + 30)
+      .filter(|h| h.starts_with(b"PK\x03\x04"))
+    {
+      let flags = u16_le(header, 6);
+      let method = u16_le(header, 8);
+      let data_len = usize::try_from(u32_le(header, 18)).unwrap_or(usize::MAX);
+      let name_start = pos + 30;
+      let name_end = name_start + usize::from(u16_le(header, 26));
+      let Some(name) = b.get(name_start..name_end) else {
+        return;
+      };
+      let data_start = name_end + usize::from(u16_le(header, 28));
+      if pos == 0 && name == b"mimetype" && method == 0 {
+        self.mimetype = b
+          .get(data_start..data_start.saturating_add(data_len))
+          .and_then(|data| std::str::from_utf8(data).ok())
+          .map(|mimetype| mimetype.trim().to_string());
+      }
+      self.names.push(String::from_utf8_lossy(name).into_owned());
+      // The sizes of entries with a data descriptor follow their data
+      if flags & 0x08 != 0 {
+        return;
+      }
+      pos = data_start.saturating_add(data_len);
+    }
+  }
+
+  /// Reads the names of all entries from the central directory, if the end of the archive
+  /// has one
+  fn add_central_directory<R: Read + Seek>(&mut self, reader: &mut R) -> io::Result<()> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    let tail_len = len.min(EOCD_SEARCH_LEN);
+    reader.seek(SeekFrom::Start(len - tail_len))?;
+    let mut tail = Vec::new();
+    reader.by_ref().take(tail_len).read_to_end(&mut tail)?;
+    let Some(eocd) = tail
+      .windows(4)
+      .rposition(|w| w == b"PK\x05\x06")
+      .and_then(|pos| tail.get(pos..pos + 22))
+    else {
+      return Ok(());
+    };
+    let (size, offset) = (u64::from(u32_le(eocd, 12)), u64::from(u32_le(eocd, 16)));
+    if size > MAX_CENTRAL_DIRECTORY_LEN || offset + size > len {
+      return Ok(());
+    }
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut directory = Vec::new();
+    reader.by_ref().take(size).read_to_end(&mut directory)?;
+
+    let mut pos = 0;
+    while let Some(header) = directory
+      .get(pos..pos + 46)
+      .filter(|h| h.starts_with(b"PK\x01\x02"))
+    {
+      let name_end = pos + 46 + usize::from(u16_le(header, 28));
+      let Some(name) = directory.get(pos + 46..name_end) else {
+        break;
+      };
+      self.names.push(String::from_utf8_lossy(name).into_owned());
+      pos = name_end + usize::from(u16_le(header, 30)) + usize::from(u16_le(header, 32));
+    }
+    Ok(())
+  }
+}
+
+const fn u16_le(b: &[u8], offset: usize) -> u16 {
+  u16::from_le_bytes([b[offset], b[offset + 1]])
+}
+
+const fn u32_le(b: &[u8], offset: usize) -> u32 {
+  u32::from_le_bytes([b[offset], b[offset + 1], b[offset + 2], b[offset + 3]])
+}
@@ -0,0 +1,41 @@
+//! Handles formats stored in zip files, told apart by the entries of the archive
+
+/// Types named by a stored `mimetype` entry at the start of the archive
+const MIMETYPE_ENTRY_TYPES: [&str; 15] = [
+  "application/epub+zip",
+  "application/vnd.oasis.opendocument.chart",
+  "application/vnd.oasis.opendocument.database",
+  "application/vnd.oasis.opendocument.formula",
+  "application/vnd.oasis.opendocument.graphics",
+  "application/vnd.oasis.opendocument.graphics-template",
+  "application/vnd.oasis.opendocument.image",
+  "application/vnd.oasis.opendocument.presentation",
+  "application/vnd.oasis.opendocument.presentation-template",
+  "application/vnd.oasis.opendocument.spreadsheet",
+  "application/vnd.oasis.opendocument.spreadsheet-template",
+  "application/vnd.oasis.opendocument.text",
+  "application/vnd.oasis.opendocument.text-master",
+  "application/vnd.oasis.opendocument.text-template",
+  "application/vnd.oasis.opendocument.text-web",
+];
+
+/// Office Open XML types by the directory of their main part
+const OOXML_TYPES: [(&str, &str); 3] = [
+  (
+    "word/",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+  ),
+  (
+    "xl/",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+  ),
+  (
+    "ppt/",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+  ),
+];
+
+const JAR_TYPE: &str = "application/x-java-archive";
+const APK_TYPE: &str = "application/vnd.android.package-archive";
+
+pub mod check;
@@ -0,0 +1,73 @@
+#![cfg(feature = "bundled")]
+mod bundled {
+  use tree_magic_rs::db::BUNDLED_DB;
+  use tree_magic_rs::MimeDatabase;
+
+  fn db() -> MimeDatabase {
+    MimeDatabase::from_provider(&BUNDLED_DB)
+  }
+
+  #[test]
+  fn test_samples() {
+    let db = db();
+    let samples: [(&[u8], &str); 12] = [
+      (include_bytes!("image/png"), "image/png"),
+      (include_bytes!("image/gif"), "image/gif"),
+      (include_bytes!("image/bmp"), "image/bmp"),
+      (include_bytes!("image/tiff"), "image/tiff"),
+      (include_bytes!("application/zip"), "application/zip"),
+      (
+        include_bytes!("application/x-7z-compressed"),
+        "application/x-7z-compressed",
+      ),
+      (include_bytes!("application/x-tar"), "application/x-tar"),
+      (include_bytes!("audio/flac"), "audio/flac"),
+      (include_bytes!("audio/mpeg"), "audio/mpeg"),
+      (include_bytes!("audio/ogg"), "audio/x-vorbis+ogg"),
+      (include_bytes!("audio/opus"), "audio/x-opus+ogg"),
+      (include_bytes!("text/plain"), "text/plain"),
+    ];
+    for (bytes, expected) in samples {
+      assert_eq!(db.from_u8(bytes), expected);
+    }
+  }
+
+  #[test]
+  fn inline_samples() {
+    let db = db();
+    let samples: [(&[u8], &str); 8] = [
+      (b"\xff\xd8\xff\xe0\0\x10JFIF\0", "image/jpeg"),
+      (b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n", "application/pdf"),
+      (b"\x1f\x8b\x08\0\0\0\0\0\0\x03", "application/gzip"),
+      (b"\x7fELF\x02\x01\x01\0\0\0\0\0", "application/x-executable"),
+      (
+        b"MZ\x90\0\x03\0\0\0\x04\0",
+        "application/x-ms-dos-executable",
+      ),
+      (b"RIFF\x24\0\0\0WAVEfmt ", "audio/x-wav"),
+      (b"RIFF\x24\0\0\0WEBPVP8 ", "image/webp"),
+      (b"<?xml version=\"1.0\"?><a/>", "application/xml"),
+    ];
+    for (bytes, expected) in samples {
+      assert_eq!(db.from_u8(bytes), expected);
+    }
+  }
+
+  #[test]
+  fn nested_rules() {
+    let db = db();
+    // "BM" without a valid header size
+    assert_eq!(db.from_u8(b"BMxxxxxxxxxxxxxxxxxxxxxx"), "text/plain");
+    // An Ogg stream of an unknown codec
+    let mut ogg = include_bytes!("audio/ogg").to_vec();
+    ogg[29] = b'X';
+    assert_eq!(db.from_u8(&ogg), "application/ogg");
+  }
+
+  #[test]
+  fn aliases() {
+    let db = db();
+    assert!(db.match_u8("audio/wav", b"RIFF\x24\0\0\0WAVEfmt "));
+    assert!(db.match_u8("text/xml", b"<?xml version=\"1.0\"?><a/>"));
+  }
+}
@@ -0,0 +1,178 @@
+#![cfg(feature = "cache")]
+mod common;
+
+mod cache {
+  use crate::common::tempdir;
+  use std::fs::{self, File};
+  use std::num::NonZeroUsize;
+  use std::path::{Path, PathBuf};
+  use std::time::{Duration, SystemTime};
+  use tree_magic_rs::{DetectionCache, DetectionCacheError, MimeDatabase, TextClassifier};
+
+  const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+  const BINARY: &[u8] = b"\x00\x01\x02\x03\x04\x05\x06\x07";
+
+  fn db(mime_type: &str) -> MimeDatabase {
+    crate::common::db(&[(mime_type, b"\x89PNG")])
+  }
+
+  fn capacity(capacity: usize) -> NonZeroUsize {
+    NonZeroUsize::new(capacity).unwrap()
+  }
+
+  /// Replaces the contents of a file, keeping its inode, size and modification time
+  fn overwrite_unnoticed(path: &Path, contents: &[u8]) {
+    let modified = fs::metadata(path).unwrap().modified().unwrap();
+    fs::write(path, contents).unwrap();
+    File::options()
+      .write(true)
+      .open(path)
+      .unwrap()
+      .set_modified(modified)
+      .unwrap();
+  }
+
+  fn touch(path: &Path) {
+    File::options()
+      .write(true)
+      .open(path)
+      .unwrap()
+      .set_modified(SystemTime::now() + Duration::from_secs(10))
+      .unwrap();
+  }
+
+  #[test]
+  fn reuse_unchanged_files() {
+    let db = db("image/png");
+    let dir = tempdir();
+    let path = dir.join("picture");
+    fs::write(&path, PNG).unwrap();
+    let cache = DetectionCache::new(capacity(8));
+    assert_eq!(cache.from_filepath(&db, &path), Some("image/png"));
+    assert_eq!(cache.len(), 1);
+
+    // The file is not opened again
+    overwrite_unnoticed(&path, BINARY);
+    assert_eq!(db.from_filepath(&path), Some("application/octet-stream"));
+    assert_eq!(cache.from_filepath(&db, &path), Some("image/png"));
+    // A new modification time is noticed
+    touch(&path);
+    assert_eq!(
+      cache.from_filepath(&db, &path),
+      Some("application/octet-stream")
+    );
+
+    assert_eq!(
+      cache.from_filepath(&db, &path.with_file_name("missing")),
+      None
+    );
+    cache.clear();
+    assert!(cache.is_empty());
+  }
+
+  #[test]
+  fn evict_least_recently_used() {
+    let db = db("image/png");
+    let dir = tempdir();
+    let paths: Vec<PathBuf> = (0..3).map(|i| dir.join(format!("{i}.png"))).collect();
+    for path in &paths {
+      fs::write(path, PNG).unwrap();
+    }
+    let cache = DetectionCache::new(capacity(2));
+    for path in &paths {
+      cache.from_filepath(&db, path);
+    }
+    assert_eq!(cache.len(), 2);
+    overwrite_unnoticed(&paths[0], BINARY);
+    assert_eq!(
+      cache.from_filepath(&db, &paths[0]),
+      Some("application/octet-stream")
+    );
+  }
+
+  #[test]
+  fn invalidate_on_database_change() {
+    let dir = tempdir();
+    let path = dir.join("picture");
+    fs::write(&path, PNG).unwrap();
+    let cache = DetectionCache::new(capacity(8));
+    assert_eq!(
+      cache.from_filepath(&db("image/png"), &path),
+      Some("image/png")
+    );
+    let other = db("image/x-test-png");
+    assert_eq!(cache.from_filepath(&other, &path), Some("image/x-test-png"));
+    assert_eq!(cache.len(), 1);
+  }
+
+  #[test]
+  fn invalidate_on_settings_change() {
+    let dir = tempdir();
+    let path = dir.join("bell");
+    fs::write(&path, b"ring\x07ring\x07").unwrap();
+    let cache = DetectionCache::new(capacity(8));
+    assert_eq!(
+      cache.from_filepath(&db("image/png"), &path),
+      Some("application/octet-stream")
+    );
+    let lenient =
+      db("image/png").with_text_classifier(TextClassifier::new().with_max_control_ratio(0.2));
+    assert_eq!(cache.from_filepath(&lenient, &path), Some("text/plain"));
+
+    let fingerprint = db("image/png").fingerprint();
+    let links = db("image/png").with_follow_symlinks(false);
+    assert_ne!(links.fingerprint(), fingerprint);
+    assert_eq!(links.with_follow_symlinks(true).fingerprint(), fingerprint);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn symlinks_not_followed() {
+    let dir = tempdir();
+    let (target, link) = (dir.join("notes"), dir.join("link"));
+    fs::write(&target, "some notes").unwrap();
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+    let db = db("image/png").with_follow_symlinks(false);
+    let cache = DetectionCache::new(capacity(8));
+    assert_eq!(cache.from_filepath(&db, &link), Some("inode/symlink"));
+    assert_eq!(cache.from_filepath(&db, &target), Some("text/plain"));
+    assert_eq!(cache.from_filepath(&db, &link), Some("inode/symlink"));
+    assert_eq!(cache.len(), 2);
+  }
+
+  #[test]
+  fn store_on_disk() {
+    let db = db("image/png");
+    let dir = tempdir();
+    let (path, store) = (dir.join("picture"), dir.join("cache"));
+    fs::write(&path, PNG).unwrap();
+    assert!(DetectionCache::load(&store, capacity(8))
+      .unwrap()
+      .is_empty());
+
+    let cache = DetectionCache::new(capacity(8));
+    cache.from_filepath(&db, &path);
+    cache.save(&store).unwrap();
+    overwrite_unnoticed(&path, BINARY);
+    let loaded = DetectionCache::load(&store, capacity(8)).unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded.from_filepath(&db, &path), Some("image/png"));
+    // Entries of another database are dropped
+    let loaded = DetectionCache::load(&store, capacity(8)).unwrap();
+    assert_eq!(
+      loaded.from_filepath(&self::db("image/x-test-png"), &path),
+      Some("application/octet-stream")
+    );
+
+    fs::write(&store, "tree_magic_rs detection cache 1 0\n1 2 3\n").unwrap();
+    assert!(matches!(
+      DetectionCache::load(&store, capacity(8)),
+      Err(DetectionCacheError::Malformed { line: 2 })
+    ));
+    fs::write(&store, "something else\n").unwrap();
+    assert!(matches!(
+      DetectionCache::load(&store, capacity(8)),
+      Err(DetectionCacheError::Malformed { line: 1 })
+    ));
+  }
+}
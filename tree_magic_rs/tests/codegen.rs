@@ -0,0 +1,95 @@
+mod common;
+
+mod codegen {
+  use crate::common::tempdir;
+  use std::path::Path;
+  use tree_magic_rs::codegen::{generate, generate_from_dir, CodegenError};
+  use tree_magic_rs::db::{
+    LoadResult, OwnedBuildableDb, SharedMimeDbProviderError, SharedMimeDbProviderExt,
+  };
+  use tree_magic_rs::MimeDatabase;
+
+  include!("codegen/embedded.rs");
+
+  fn fixture_dir() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/codegen"))
+  }
+
+  #[test]
+  fn load_fixture_dir() {
+    let mut db = OwnedBuildableDb::new();
+    let load_result = db.load_from_xdg_shared_magic_dir(fixture_dir()).unwrap();
+    assert_eq!(
+      load_result,
+      LoadResult {
+        magic_rules_num: 6,
+        aliases_num: 1,
+        subclasses_num: 1,
+      }
+    );
+  }
+
+  #[test]
+  fn generated_code_is_up_to_date() {
+    let mut db = OwnedBuildableDb::new();
+    db.load_from_xdg_shared_magic_dir(fixture_dir()).unwrap();
+    let mut generated = Vec::new();
+    generate(&db, "EMBEDDED", &mut generated).unwrap();
+    assert_eq!(
+      String::from_utf8(generated).unwrap(),
+      include_str!("codegen/embedded.rs")
+    );
+  }
+
+  #[test]
+  fn generated_code_matches_source() {
+    let mut db = OwnedBuildableDb::new();
+    db.load_from_xdg_shared_magic_dir(fixture_dir()).unwrap();
+    assert_eq!(OwnedBuildableDb::from_provider(&EMBEDDED), db);
+  }
+
+  #[test]
+  fn detect_with_embedded() {
+    let db = MimeDatabase::from_provider(&EMBEDDED);
+    assert_eq!(db.from_u8(include_bytes!("image/png")), "image/x-test-png");
+    assert_eq!(db.from_u8(include_bytes!("image/gif")), "image/gif");
+    assert!(db.match_u8("image/x-png", include_bytes!("image/png")));
+    assert_eq!(db.from_u8(b"  \"\\quote"), "text/x-test-quote");
+  }
+
+  #[test]
+  fn generate_to_file() {
+    let dir = tempdir();
+    let out_file = dir.join("embedded.rs");
+    let load_result = generate_from_dir(fixture_dir(), "EMBEDDED", &out_file).unwrap();
+    assert_eq!(load_result.magic_rules_num, 6);
+    assert_eq!(
+      std::fs::read_to_string(&out_file).unwrap(),
+      include_str!("codegen/embedded.rs")
+    );
+
+    assert!(matches!(
+      generate_from_dir(&fixture_dir().join("missing"), "EMBEDDED", &out_file),
+      Err(CodegenError::NotFound { .. })
+    ));
+  }
+
+  #[test]
+  fn malformed_magic_file() {
+    let dir = tempdir();
+    let magic = std::fs::read(fixture_dir().join("magic")).unwrap();
+    std::fs::write(dir.join("magic"), &magic[..magic.len() - 20]).unwrap();
+    std::fs::write(dir.join("aliases"), "image/x-png\n").unwrap();
+
+    let mut db = OwnedBuildableDb::new();
+    let (load_result, errs) = db.load_from_xdg_shared_magic_dir(&dir).unwrap_err();
+    assert_eq!(load_result.magic_rules_num, 6);
+    assert!(matches!(
+      errs[..],
+      [
+        SharedMimeDbProviderError::MagicRuleParseError { .. },
+        SharedMimeDbProviderError::LineParseError { line: 1, .. },
+      ]
+    ));
+  }
+}
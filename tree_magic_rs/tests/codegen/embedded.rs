@@ -0,0 +1,18 @@
+// @generated by tree_magic_rs::codegen
+pub static EMBEDDED: ::tree_magic_rs::db::StaticDbProvider<'static> =
+  ::tree_magic_rs::db::StaticDbProvider::new(
+    &[
+      ::tree_magic_rs::db::BorrowedMagicRule::new(50, "image/png", 0, 0, b"\x89PNG", b"", 1, 0),
+      ::tree_magic_rs::db::BorrowedMagicRule::new(50, "image/x-test-png", 0, 0, b"\x89PNG", b"", 1, 0),
+      ::tree_magic_rs::db::BorrowedMagicRule::new(50, "image/x-test-png", 1, 12, b"IHDR", b"", 1, 0),
+      ::tree_magic_rs::db::BorrowedMagicRule::new(50, "image/gif", 0, 0, b"GIF87a", b"", 1, 0),
+      ::tree_magic_rs::db::BorrowedMagicRule::new(50, "image/gif", 0, 0, b"GIF89a", b"", 1, 0),
+      ::tree_magic_rs::db::BorrowedMagicRule::new(40, "text/x-test-quote", 0, 0, b"\"\\Q", b"\xff\xff\xdf", 1, 4),
+    ],
+    &[
+      ::tree_magic_rs::db::BorrowedAlias::new("image/x-png", "image/png"),
+    ],
+    &[
+      ::tree_magic_rs::db::BorrowedSubclass::new("image/x-test-png", "image/png"),
+    ],
+  );
@@ -0,0 +1,145 @@
+//! Fixtures shared by the integration tests.
+//!
+//! Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tree_magic_rs::db::{OwnedAlias, OwnedBuildableDb, OwnedMagicRule, OwnedSubclass};
+use tree_magic_rs::MimeDatabase;
+
+/// A magic rule with priority 50 that compares one word at `start_off`
+pub fn rule(
+  mime_type: &str,
+  indent_level: u32,
+  start_off: u32,
+  val: &[u8],
+  mask: &[u8],
+) -> OwnedMagicRule {
+  word_rule(mime_type, indent_level, start_off, val, mask, 1, 0)
+}
+
+/// A magic rule with priority 50 that compares words of `word_len` bytes at `start_off`
+/// or one of the `region_len` offsets after it
+pub fn word_rule(
+  mime_type: &str,
+  indent_level: u32,
+  start_off: u32,
+  val: &[u8],
+  mask: &[u8],
+  word_len: u32,
+  region_len: u32,
+) -> OwnedMagicRule {
+  OwnedMagicRule::new(
+    50,
+    mime_type.to_string(),
+    indent_level,
+    start_off,
+    val.into(),
+    mask.into(),
+    word_len,
+    region_len,
+  )
+}
+
+/// A top level magic rule that matches `val` at the start of a file
+pub fn magic_rule(mime_type: &str, val: &[u8]) -> OwnedMagicRule {
+  rule(mime_type, 0, 0, val, &[])
+}
+
+/// A top level magic rule that matches `val` at one of the first `region_len + 1` offsets
+pub fn ranged_rule(mime_type: &str, val: &[u8], region_len: u32) -> OwnedMagicRule {
+  OwnedMagicRule::new(
+    50,
+    mime_type.to_string(),
+    0,
+    0,
+    val.into(),
+    Box::new([]),
+    1,
+    region_len,
+  )
+}
+
+/// A provider with a top level magic rule for each type
+pub fn provider(rules: &[(&str, &[u8])]) -> OwnedBuildableDb {
+  let mut provider = OwnedBuildableDb::new();
+  provider.magic_rules_mut().extend(
+    rules
+      .iter()
+      .map(|&(mime_type, val)| magic_rule(mime_type, val)),
+  );
+  provider
+}
+
+/// A database with a top level magic rule for each type
+pub fn db(rules: &[(&str, &[u8])]) -> MimeDatabase {
+  MimeDatabase::from_provider(&provider(rules))
+}
+
+/// PNG and GIF rules with a nested rule, a mask, an alias and a subclass
+pub fn test_provider() -> OwnedBuildableDb {
+  let mut db = OwnedBuildableDb::new();
+  db.magic_rules_mut().extend([
+    rule("image/png", 0, 0, b"\x89PNG", &[]),
+    rule("image/x-test-png", 0, 0, b"\x89PNG", &[]),
+    rule("image/x-test-png", 1, 12, b"IHDR", &[]),
+    rule("image/gif", 0, 0, b"GIF87a", &[]),
+  ]);
+  db.magic_rules_mut().push(OwnedMagicRule::new(
+    40,
+    "image/gif".to_string(),
+    0,
+    0,
+    b"GIF89a".as_slice().into(),
+    b"\xff\xff\xff\xff\xff\xff".as_slice().into(),
+    1,
+    4,
+  ));
+  db.aliases_mut().push(OwnedAlias::new(
+    "image/x-png".to_string(),
+    "image/png".to_string(),
+  ));
+  db.subclasses_mut().push(OwnedSubclass::new(
+    "image/x-test-png".to_string(),
+    "image/png".to_string(),
+  ));
+  db
+}
+
+/// A new directory below the system temporary directory, removed with its contents when dropped
+pub struct TempDir(PathBuf);
+
+impl Deref for TempDir {
+  type Target = Path;
+
+  fn deref(&self) -> &Path {
+    &self.0
+  }
+}
+
+impl AsRef<Path> for TempDir {
+  fn as_ref(&self) -> &Path {
+    self
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
+
+/// Creates a [`TempDir`] that is unique to the test process and call
+pub fn tempdir() -> TempDir {
+  static COUNTER: AtomicUsize = AtomicUsize::new(0);
+  let dir = std::env::temp_dir().join(format!(
+    "tree_magic_rs-{}-{}-{}",
+    env!("CARGO_CRATE_NAME"),
+    std::process::id(),
+    COUNTER.fetch_add(1, Ordering::Relaxed)
+  ));
+  std::fs::create_dir_all(&dir).unwrap();
+  TempDir(dir)
+}
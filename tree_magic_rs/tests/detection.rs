@@ -0,0 +1,90 @@
+mod common;
+
+mod detection {
+  use crate::common::tempdir;
+  use std::path::Path;
+  use tree_magic_rs::{detect_charset, Charset, Detection, MimeDatabase, TextClassifier};
+
+  fn db() -> MimeDatabase {
+    crate::common::db(&[("image/png", b"\x89PNG")])
+  }
+
+  #[test]
+  fn charsets() {
+    assert_eq!(detect_charset(b"plain"), Some(Charset::UsAscii));
+    assert_eq!(detect_charset("Grüße".as_bytes()), Some(Charset::Utf8));
+    assert_eq!(
+      detect_charset(b"\xef\xbb\xbfGr\xc3\xbc\xc3\x9fe"),
+      Some(Charset::Utf8)
+    );
+    assert_eq!(detect_charset(b"\xff\xfeh\0i\0"), Some(Charset::Utf16Le));
+    assert_eq!(detect_charset(b"\0h\0i\0\n"), Some(Charset::Utf16Be));
+    assert_eq!(detect_charset(b"\x00\x01\x02\x03"), None);
+    assert_eq!(Charset::Utf16Be.to_string(), "utf-16be");
+  }
+
+  #[cfg(not(feature = "encoding"))]
+  #[test]
+  fn latin() {
+    assert_eq!(
+      detect_charset(b"caf\xe9 cr\xe8me br\xfbl\xe9e"),
+      Some(Charset::Iso8859_1)
+    );
+    assert_eq!(
+      detect_charset(b"\x93caf\xe9\x94 \x80 5"),
+      Some(Charset::Windows1252)
+    );
+  }
+
+  #[cfg(feature = "encoding")]
+  #[test]
+  fn legacy() {
+    // "日本語のテキストです。" in Shift_JIS
+    let shift_jis =
+      b"\x93\xfa\x96\x7b\x8c\xea\x82\xcc\x83\x65\x83\x4c\x83\x58\x83\x67\x82\xc5\x82\xb7\x81\x42";
+    assert_eq!(detect_charset(shift_jis), Some(Charset::Other("Shift_JIS")));
+  }
+
+  #[test]
+  fn detect() {
+    let db = db();
+    assert_eq!(
+      db.detection_from_u8("¡Hola!".as_bytes()),
+      Detection {
+        mime_type: "text/plain",
+        charset: Some(Charset::Utf8)
+      }
+    );
+    let png = db.detection_from_u8(include_bytes!("image/png"));
+    assert_eq!(png.charset, None);
+    assert_eq!(png.to_string(), "image/png");
+
+    let text = db
+      .detection_from_filepath(Path::new("tests/text/plain"))
+      .unwrap();
+    assert_eq!(text.media_type(), "text/plain; charset=utf-8");
+    assert_eq!(db.detection_from_filepath(Path::new("tests/missing")), None);
+
+    // The classifier of the database decides
+    let lenient = db.with_text_classifier(TextClassifier::new().with_max_control_ratio(0.5));
+    assert_eq!(
+      lenient.detection_from_u8(b"a\x01").charset,
+      Some(Charset::Utf8)
+    );
+  }
+
+  #[test]
+  fn ascii_prefix() {
+    // Only the start of the file is ASCII
+    let mut bytes = vec![b'a'; 3000];
+    bytes.extend("é".as_bytes());
+    let dir = tempdir();
+    let path = dir.join("text");
+    std::fs::write(&path, &bytes).unwrap();
+    let db = db();
+    let detection = db.detection_from_filepath(&path).unwrap();
+    assert_eq!(detection.to_string(), "text/plain; charset=utf-8");
+    assert_eq!(db.detection_from_u8(&bytes), detection);
+    assert_eq!(db.detection_from_u8(&bytes[..3000]), detection);
+  }
+}
@@ -1,6 +1,11 @@
+mod common;
+
 mod from_filepath {
+  use crate::common::tempdir;
   use std::path::Path;
   use tree_magic_rs as tree_magic;
+  use tree_magic_rs::db::OwnedBuildableDb;
+  use tree_magic_rs::MimeDatabase;
 
   #[test]
   fn nonexistent_file_returns_none() {
@@ -9,4 +14,56 @@ mod from_filepath {
       None
     );
   }
+
+  #[test]
+  fn inode_types() {
+    let db = MimeDatabase::from_provider(&OwnedBuildableDb::new());
+    let dir = tempdir();
+    std::fs::write(dir.join("empty"), b"").unwrap();
+    assert_eq!(db.from_filepath(&dir), Some("inode/directory"));
+    assert_eq!(
+      db.from_filepath(&dir.join("empty")),
+      Some("application/x-zerosize")
+    );
+    assert_eq!(db.from_u8(b""), "application/x-zerosize");
+    assert!(db.is_a("application/x-zerosize", "application/octet-stream"));
+    assert!(db.is_a("inode/mount-point", "inode/directory"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn unix_inode_types() {
+    let db = MimeDatabase::from_provider(&OwnedBuildableDb::new());
+    assert_eq!(db.from_filepath(Path::new("/")), Some("inode/mount-point"));
+    assert_eq!(
+      db.from_filepath(Path::new("/dev/null")),
+      Some("inode/chardevice")
+    );
+    let dir = tempdir();
+    let socket = dir.join("socket");
+    let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+    assert_eq!(db.from_filepath(&socket), Some("inode/socket"));
+    assert!(db.match_filepath("inode/socket", &socket));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn symlinks() {
+    use std::os::unix::fs::symlink;
+    let db = MimeDatabase::from_provider(&OwnedBuildableDb::new());
+    let dir = tempdir();
+    std::fs::write(dir.join("text"), b"hello").unwrap();
+    symlink(dir.join("text"), dir.join("link")).unwrap();
+    symlink(dir.join("missing"), dir.join("broken")).unwrap();
+
+    assert_eq!(db.from_filepath(&dir.join("link")), Some("text/plain"));
+    assert!(db.match_filepath("text/plain", &dir.join("link")));
+    assert_eq!(db.from_filepath(&dir.join("broken")), Some("inode/symlink"));
+
+    let db = db.with_follow_symlinks(false);
+    assert_eq!(db.from_filepath(&dir.join("link")), Some("inode/symlink"));
+    assert!(!db.match_filepath("text/plain", &dir.join("link")));
+    assert!(db.match_filepath("inode/symlink", &dir.join("link")));
+    assert_eq!(db.from_filepath(&dir.join("text")), Some("text/plain"));
+  }
 }
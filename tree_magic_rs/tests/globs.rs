@@ -0,0 +1,268 @@
+mod common;
+
+mod globs {
+  use crate::common::provider;
+  use tree_magic_rs::db::{
+    parse_globs2, parse_mime_types, parse_nginx_types, GlobDatabase, GlobRule, GlobsParseError,
+    OwnedAlias, OwnedSubclass, EXTENSION_MAP_WEIGHT,
+  };
+  use tree_magic_rs::MimeDatabase;
+
+  const GLOBS2: &str = "# This file was automatically generated
+50:text/x-makefile:makefile
+50:image/png:*.png
+50:text/x-c++src:*.C:cs
+50:text/x-c++src:*.C
+50:text/x-csrc:*.c:cs
+50:text/x-csrc:*.c
+45:application/x-compressed-tar:*.tar.gz
+40:application/gzip:*.gz
+10:text/x-readme:README*
+";
+
+  const MIME_TYPES: &str = "# Apache mime.types
+application/pdf\t\t\t\tpdf
+image/png\t\t\t\tpng
+image/x-test\t\t\t\ttst tsti
+application/vnd.example
+";
+
+  const NGINX_TYPES: &str = "types {
+    text/html                                        html htm shtml;
+    image/avif                                       avif;
+    # comment
+    application/x-weird \"wrd\"   wrd2;
+    image/png png;
+}
+";
+
+  fn glob(weight: u32, mime_type: &str, pattern: &str, case_sensitive: bool) -> GlobRule {
+    GlobRule {
+      weight,
+      mime_type: mime_type.to_string(),
+      pattern: pattern.to_string(),
+      case_sensitive,
+    }
+  }
+
+  fn glob_db() -> GlobDatabase {
+    let mut db = GlobDatabase::new();
+    db.add_globs(parse_globs2(GLOBS2).unwrap());
+    db.load_mime_types_str(MIME_TYPES).unwrap();
+    db.load_nginx_types_str(NGINX_TYPES).unwrap();
+    db
+  }
+
+  #[test]
+  fn parse_sources() {
+    let globs = parse_globs2(GLOBS2).unwrap();
+    assert_eq!(globs.len(), 9);
+    assert_eq!(globs[2], glob(50, "text/x-c++src", "*.C", true));
+    assert_eq!(globs[3], glob(50, "text/x-c++src", "*.C", false));
+
+    let w = EXTENSION_MAP_WEIGHT;
+    assert_eq!(
+      parse_mime_types(MIME_TYPES, w).unwrap(),
+      [
+        glob(w, "application/pdf", "*.pdf", false),
+        glob(w, "image/png", "*.png", false),
+        glob(w, "image/x-test", "*.tst", false),
+        glob(w, "image/x-test", "*.tsti", false),
+      ]
+    );
+    let nginx = parse_nginx_types(NGINX_TYPES, w).unwrap();
+    let patterns: Vec<_> = nginx
+      .iter()
+      .map(|g| (g.mime_type.as_str(), g.pattern.as_str()))
+      .collect();
+    assert_eq!(
+      patterns,
+      [
+        ("text/html", "*.html"),
+        ("text/html", "*.htm"),
+        ("text/html", "*.shtml"),
+        ("image/avif", "*.avif"),
+        ("application/x-weird", "*.wrd"),
+        ("application/x-weird", "*.wrd2"),
+        ("image/png", "*.png"),
+      ]
+    );
+    // Included snippets have no surrounding block
+    assert_eq!(
+      parse_nginx_types("image/avif avif;", w).unwrap(),
+      [glob(w, "image/avif", "*.avif", false)]
+    );
+  }
+
+  #[test]
+  fn reject_malformed_sources() {
+    let line = |line| Err(GlobsParseError::LineParseError { line });
+    assert_eq!(
+      parse_globs2("50:image/png:*.png\nbad:image/png:*.png"),
+      line(2)
+    );
+    assert_eq!(parse_globs2("50:image/png"), line(1));
+    assert_eq!(parse_mime_types("\npdf application/pdf", 5), line(2));
+    assert_eq!(parse_nginx_types("types {\n  image/png png\n}", 5), line(2));
+    assert_eq!(parse_nginx_types("types {\n  image/png png;\n", 5), line(2));
+    assert_eq!(parse_nginx_types("server {}", 5), line(1));
+  }
+
+  #[test]
+  fn match_filenames() {
+    let db = glob_db();
+    assert_eq!(db.from_filename("Makefile"), Some("text/x-makefile"));
+    assert_eq!(db.from_filename("/src/main.C"), Some("text/x-c++src"));
+    // Case-sensitive globs win over case-insensitive ones
+    assert_eq!(db.from_filename("main.c"), Some("text/x-csrc"));
+    assert_eq!(db.from_filename("MAIN.c"), Some("text/x-csrc"));
+    assert_eq!(db.from_filename("IMAGE.PNG"), Some("image/png"));
+    // Higher weights win over longer patterns
+    assert_eq!(
+      db.from_filename("backup.tar.gz"),
+      Some("application/x-compressed-tar")
+    );
+    assert_eq!(db.from_filename("README.tst"), Some("text/x-readme"));
+    // Extension maps answer for names the shared MIME info globs do not know
+    assert_eq!(db.from_filename("paper.pdf"), Some("application/pdf"));
+    assert_eq!(db.from_filename("index.HTM"), Some("text/html"));
+    assert_eq!(db.from_filename("unknown.xyz"), None);
+
+    let candidates: Vec<_> = db
+      .match_filename("README.tst")
+      .iter()
+      .map(|g| g.mime_type.as_str())
+      .collect();
+    assert_eq!(candidates, ["text/x-readme", "image/x-test"]);
+  }
+
+  #[test]
+  fn wildcards_and_noglobs() {
+    let mut db = GlobDatabase::new();
+    db.add_globs([
+      glob(50, "text/x-log", "*.log.[0-9]", false),
+      glob(50, "text/x-backup", "*~", false),
+      glob(50, "text/x-single", "?.txt", false),
+      glob(50, "text/x-not", "x[!a-c]", false),
+    ]);
+    assert_eq!(db.from_filename("syslog.log.3"), Some("text/x-log"));
+    assert_eq!(db.from_filename("syslog.log.x"), None);
+    assert_eq!(db.from_filename("notes~"), Some("text/x-backup"));
+    assert_eq!(db.from_filename("a.txt"), Some("text/x-single"));
+    assert_eq!(db.from_filename("ab.txt"), None);
+    assert_eq!(db.from_filename("xd"), Some("text/x-not"));
+    assert_eq!(db.from_filename("xb"), None);
+
+    let added = db.add_globs([
+      glob(50, "text/x-backup", "__NOGLOBS__", false),
+      glob(50, "text/x-backup", "*.bak", false),
+    ]);
+    assert_eq!(added, 1);
+    assert_eq!(db.from_filename("notes~"), None);
+    assert_eq!(db.from_filename("notes.bak"), Some("text/x-backup"));
+  }
+
+  #[test]
+  fn reverse_lookup() {
+    let db = glob_db();
+    assert_eq!(db.extensions_for("image/png"), ["png"]);
+    assert_eq!(db.extensions_for("IMAGE/X-TEST"), ["tst", "tsti"]);
+    assert_eq!(db.extensions_for("text/html"), ["html", "htm", "shtml"]);
+    assert_eq!(db.extensions_for("text/x-readme"), Vec::<&str>::new());
+  }
+
+  #[test]
+  fn mime_database_filenames() {
+    let mut provider = provider(&[("application/pdf", b"%PDF-")]);
+    provider.aliases_mut().push(OwnedAlias::new(
+      "application/x-pdf".to_string(),
+      "application/pdf".to_string(),
+    ));
+    let mut db = MimeDatabase::from_provider(&provider);
+    assert_eq!(db.from_filename("paper.pdf"), None);
+    db.globs_mut()
+      .load_mime_types_str("application/x-pdf xpdf\napplication/pdf pdf")
+      .unwrap();
+    assert_eq!(db.from_filename("paper.xpdf"), Some("application/pdf"));
+    assert_eq!(db.extensions_for("application/pdf"), ["pdf", "xpdf"]);
+    assert_eq!(db.extensions_for("application/x-pdf"), ["pdf", "xpdf"]);
+
+    let db = MimeDatabase::from_provider(&provider).with_globs(glob_db());
+    assert_eq!(db.from_filename("paper.pdf"), Some("application/pdf"));
+    assert_eq!(db.globs().globs().len(), 20);
+  }
+
+  fn typed_db() -> MimeDatabase {
+    let mut provider = provider(&[
+      ("text/plain", b"TXT"),
+      ("text/x-log", b"LOG:"),
+      ("application/xml", b"<?xml"),
+      ("image/svg+xml", b"<svg"),
+      ("image/png", b"\x89PNG"),
+      ("application/x-unknown", b"UNK"),
+    ]);
+    provider.subclasses_mut().push(OwnedSubclass::new(
+      "text/x-log".to_string(),
+      "text/plain".to_string(),
+    ));
+    provider.aliases_mut().push(OwnedAlias::new(
+      "image/x-png".to_string(),
+      "image/png".to_string(),
+    ));
+    let mut globs = GlobDatabase::new();
+    globs.add_globs([
+      glob(50, "text/plain", "*.txt", false),
+      glob(50, "application/xml", "*.xml", false),
+      glob(50, "image/svg+xml", "*.svg", false),
+      glob(60, "image/png", "*.png", false),
+      glob(50, "image/x-png", "*.pngx", false),
+      glob(50, "application/octet-stream", "*.bin", false),
+    ]);
+    MimeDatabase::from_provider(&provider).with_globs(globs)
+  }
+
+  #[test]
+  fn preferred_extensions() {
+    let db = typed_db();
+    assert_eq!(db.extensions("image/png"), ["png", "pngx"]);
+    assert_eq!(db.preferred_extension("image/x-png"), Some("png"));
+    // Types without globs fall back to their parents
+    assert_eq!(db.extensions("text/x-log"), ["txt"]);
+    assert_eq!(db.preferred_extension("image/svg+xml"), Some("svg"));
+    // but not to the generic binary type
+    assert_eq!(db.preferred_extension("application/x-unknown"), None);
+  }
+
+  #[test]
+  fn suggest_filenames() {
+    let db = typed_db();
+    assert_eq!(
+      db.suggest_filename("downloads/image.svg", "image/png"),
+      Some("downloads/image.png".to_string())
+    );
+    assert_eq!(
+      db.suggest_filename("image", "image/x-png"),
+      Some("image.png".to_string())
+    );
+    assert_eq!(
+      db.suggest_filename(".hidden", "image/png"),
+      Some(".hidden.png".to_string())
+    );
+    assert_eq!(db.suggest_filename("image.PNGX", "image/png"), None);
+    assert_eq!(db.suggest_filename("server.txt", "text/x-log"), None);
+    // A name of a subtype is more specific than the detected type
+    assert_eq!(db.suggest_filename("drawing.svg", "application/xml"), None);
+    assert_eq!(
+      db.suggest_filename("drawing.xml", "image/svg+xml"),
+      Some("drawing.svg".to_string())
+    );
+    assert_eq!(
+      db.suggest_filename("data.bin", "application/x-unknown"),
+      None
+    );
+    assert_eq!(
+      db.suggest_filename("data.dat", "application/x-unknown"),
+      None
+    );
+  }
+}
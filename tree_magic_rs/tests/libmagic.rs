@@ -0,0 +1,276 @@
+mod common;
+
+mod libmagic {
+  use crate::common::{rule, word_rule};
+  use tree_magic_rs::db::{
+    import_libmagic, write_libmagic_file, LibmagicExportOptions, LibmagicProviderExt,
+    LibmagicSkipReason, MagicRule, OwnedBuildableDb, OwnedSubclass, SkippedLibmagicLine,
+  };
+  use tree_magic_rs::MimeDatabase;
+
+  const MAGIC: &str = r"# Test formats
+0	string		\x89TST\r\n	Test image
+!:mime	image/x-test
+>8	belong&0xffff0000	0x00010000	version 1
+!:mime	image/x-test-v1
+>>12	lelong		-2	with trailer
+>8	byte		x	unknown version
+
+0	string/c	tdoc		Test document
+!:mime	text/x-test
+!:ext	tdoc
+
+0	search/64	MARKER		Marked file
+!:mime	application/x-marked
+
+0	beshort		0xcafe		Coffee
+>2	byte		>4		new
+!:mime	application/x-coffee
+>2	ubyte		=4		old
+!:mime	application/x-coffee-old
+>(4.l)	string				indirect
+!:mime	application/x-indirect
+>>>8	byte		1		orphan
+0	regex		^foo		Regex
+!:mime	text/x-regex
+0	byte		x		Anything
+!:mime	application/x-anything
+0	leshort		0x10000		too large
+";
+
+  #[test]
+  fn translate_rules() {
+    let import = import_libmagic(MAGIC);
+    let rules: Vec<_> = import
+      .magic_rules
+      .iter()
+      .map(|rule| {
+        (
+          rule.priority(),
+          rule.mime_type(),
+          rule.indent_level(),
+          rule.start_off(),
+          rule.val(),
+          rule.mask(),
+          rule.word_len(),
+          rule.region_len(),
+        )
+      })
+      .collect();
+    let no_mask: &[u8] = &[];
+    assert_eq!(
+      rules,
+      [
+        (50, "image/x-test", 0, 0, &b"\x89TST\r\n"[..], no_mask, 1, 0),
+        (51, "image/x-test-v1", 0, 0, b"\x89TST\r\n", no_mask, 1, 0),
+        (
+          51,
+          "image/x-test-v1",
+          1,
+          8,
+          b"\x00\x01\x00\x00",
+          b"\xff\xff\x00\x00",
+          1,
+          0
+        ),
+        (50, "text/x-test", 0, 0, b"TDOC", b"\xdf\xdf\xdf\xdf", 1, 0),
+        (50, "application/x-marked", 0, 0, b"MARKER", no_mask, 1, 64),
+        (
+          51,
+          "application/x-coffee-old",
+          0,
+          0,
+          b"\xca\xfe",
+          no_mask,
+          1,
+          0
+        ),
+        (51, "application/x-coffee-old", 1, 2, b"\x04", no_mask, 1, 0),
+      ]
+    );
+    assert_eq!(
+      import.subclasses,
+      [OwnedSubclass::new(
+        "image/x-test-v1".to_string(),
+        "image/x-test".to_string()
+      )]
+    );
+  }
+
+  #[test]
+  fn report_untranslatable_lines() {
+    let import = import_libmagic(MAGIC);
+    let skipped = |line, reason| SkippedLibmagicLine { line, reason };
+    assert_eq!(
+      import.skipped_lines,
+      [
+        skipped(
+          17,
+          LibmagicSkipReason::UnsupportedTest {
+            test: ">4".to_string()
+          }
+        ),
+        skipped(18, LibmagicSkipReason::UntranslatedEntry { line: 17 }),
+        skipped(
+          21,
+          LibmagicSkipReason::UnsupportedOffset {
+            offset: "(4.l)".to_string()
+          }
+        ),
+        skipped(22, LibmagicSkipReason::UntranslatedEntry { line: 21 }),
+        skipped(23, LibmagicSkipReason::MissingParent),
+        skipped(
+          24,
+          LibmagicSkipReason::UnsupportedType {
+            match_type: "regex".to_string()
+          }
+        ),
+        skipped(25, LibmagicSkipReason::UntranslatedEntry { line: 24 }),
+        skipped(27, LibmagicSkipReason::AlwaysMatches),
+        skipped(
+          28,
+          LibmagicSkipReason::InvalidValue {
+            value: "0x10000".to_string()
+          }
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn detect_imported_types() {
+    let mut db = OwnedBuildableDb::new();
+    let (load_result, skipped) = db.load_libmagic_str(MAGIC);
+    assert_eq!(load_result.magic_rules_num, 7);
+    assert_eq!(load_result.subclasses_num, 1);
+    assert_eq!(skipped.len(), 9);
+    let mime_db = MimeDatabase::from_provider(&db);
+    assert_eq!(mime_db.from_u8(b"\x89TST\r\n\0\0\0\0\0\0"), "image/x-test");
+    assert_eq!(
+      mime_db.from_u8(b"\x89TST\r\n\0\0\0\x01\x12\x34"),
+      "image/x-test-v1"
+    );
+    assert_eq!(mime_db.from_u8(b"tDoC contents"), "text/x-test");
+    // The whole search range has to be present
+    let mut marked = b"\0\x01header MARKER".to_vec();
+    marked.resize(80, 0);
+    assert_eq!(mime_db.from_u8(&marked), "application/x-marked");
+  }
+
+  #[test]
+  fn host_order_values() {
+    let mut db = OwnedBuildableDb::new();
+    db.load_libmagic_str("0\tshort\t0x0102\tHost order\n!:mime\tapplication/x-host\n");
+    let mime_db = MimeDatabase::from_provider(&db);
+    assert!(mime_db.match_u8("application/x-host", &0x0102_u16.to_ne_bytes()));
+    assert!(!mime_db.match_u8("application/x-host", &0x0201_u16.to_ne_bytes()));
+  }
+
+  fn export_db() -> OwnedBuildableDb {
+    let mut db = OwnedBuildableDb::new();
+    db.magic_rules_mut().extend([
+      rule("image/x-test", 0, 0, b"\x89TST \\", &[]),
+      rule("image/x-test", 1, 8, b"\x00\x00", b"\xff\x00"),
+      rule("image/x-test", 1, 8, b"v2", &[]),
+      rule("image/x-test", 0, 0, b">TST", &[]),
+      word_rule(
+        "text/x-test",
+        0,
+        0,
+        b"TDOC!",
+        b"\xdf\xdf\xdf\xdf\xff",
+        1,
+        16,
+      ),
+      word_rule("application/x-host", 0, 4, b"\x12\x34", &[], 2, 0),
+      rule(
+        "application/x-masked",
+        0,
+        2,
+        b"\x01\x02\x03",
+        b"\x0f\x0f\x0f",
+      ),
+    ]);
+    db
+  }
+
+  #[test]
+  fn export_rules() {
+    let mut options = LibmagicExportOptions::default();
+    assert!(options.add_glob("image/x-test", "*.tst"));
+    assert!(options.add_glob("image/x-test", "*.tsti"));
+    assert!(!options.add_glob("image/x-test", "README*"));
+    let mut out = Vec::new();
+    write_libmagic_file(&export_db(), &options, &mut out).unwrap();
+    assert_eq!(
+      String::from_utf8(out).unwrap(),
+      r"
+# application/x-host, priority 50
+4	short	0x1234	application/x-host
+!:mime	application/x-host
+
+# application/x-masked, priority 50
+2	beshort&0x0f0f	0x0102	application/x-masked
+>4	byte&0x0f	0x03
+!:mime	application/x-masked
+
+# image/x-test, priority 50
+0	string	\x89TST\ \\	image/x-test
+>8	beshort&0xff00	0x0000
+!:mime	image/x-test
+!:ext	tst/tsti
+>8	string	v2
+!:mime	image/x-test
+!:ext	tst/tsti
+0	string	=>TST	image/x-test
+!:mime	image/x-test
+!:ext	tst/tsti
+
+# text/x-test, priority 50
+0	search/16/c	tdoc!	text/x-test
+!:mime	text/x-test
+"
+    );
+  }
+
+  #[test]
+  fn export_round_trip() {
+    let db = export_db();
+    let mut out = Vec::new();
+    write_libmagic_file(&db, &LibmagicExportOptions::default(), &mut out).unwrap();
+    let import = import_libmagic(std::str::from_utf8(&out).unwrap());
+    assert!(import.skipped_lines.is_empty());
+
+    let mut imported = OwnedBuildableDb::new();
+    imported.magic_rules_mut().extend(import.magic_rules);
+    let original = MimeDatabase::from_provider(&db);
+    let imported = MimeDatabase::from_provider(&imported);
+    let mut text = b"\0\0xx tDoC!".to_vec();
+    text.resize(32, 0);
+    for sample in [
+      &b"\x89TST \\\0\0\x00\x77"[..],
+      b"\x89TST \\\0\0v2",
+      b"\x89TST \\\0\0v3",
+      b">TST",
+      &text,
+      b"\0\0\0\0\x12\x34",
+      b"\0\0\x31\x32\x33",
+    ] {
+      assert_eq!(
+        original.from_u8(sample),
+        imported.from_u8(sample),
+        "{sample:?}"
+      );
+    }
+    assert_eq!(imported.from_u8(b">TST"), "image/x-test");
+  }
+
+  #[test]
+  fn reject_inexpressible_rules() {
+    let mut db = OwnedBuildableDb::new();
+    db.magic_rules_mut()
+      .push(word_rule("image/x-test", 0, 0, b"ab", b"\x0f\x0f", 1, 8));
+    let options = LibmagicExportOptions::default();
+    assert!(write_libmagic_file(&db, &options, std::io::sink()).is_err());
+  }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+mod lint {
+  use crate::common::rule;
+  use tree_magic_rs::db::{
+    lint_db, GlobDatabase, GlobRule, LintIssue, OwnedAlias, OwnedBuildableDb, OwnedSubclass,
+  };
+
+  fn glob(mime_type: &str, pattern: &str) -> GlobRule {
+    GlobRule {
+      weight: 50,
+      mime_type: mime_type.to_string(),
+      pattern: pattern.to_string(),
+      case_sensitive: false,
+    }
+  }
+
+  fn alias(alias: &str, name: &str) -> OwnedAlias {
+    OwnedAlias::new(alias.to_string(), name.to_string())
+  }
+
+  fn subclass(mime_type: &str, parent: &str) -> OwnedSubclass {
+    OwnedSubclass::new(mime_type.to_string(), parent.to_string())
+  }
+
+  #[test]
+  fn clean_database() {
+    let mut provider = OwnedBuildableDb::new();
+    provider.magic_rules_mut().extend([
+      rule("image/png", 0, 0, b"\x89PNG", b""),
+      rule("application/zip", 0, 0, b"PK", b"\xff\xff"),
+      rule("application/zip", 1, 0, b"\x03\x04", b""),
+    ]);
+    provider
+      .aliases_mut()
+      .push(alias("image/x-png", "image/png"));
+    provider.subclasses_mut().push(subclass(
+      "application/vnd.oasis.opendocument.text",
+      "application/zip",
+    ));
+    let mut globs = GlobDatabase::new();
+    globs.add_globs([glob("image/png", "*.png"), glob("application/zip", "*.zip")]);
+    assert_eq!(lint_db(&provider, &globs), []);
+  }
+
+  #[test]
+  fn report_issues() {
+    let mut provider = OwnedBuildableDb::new();
+    provider.magic_rules_mut().extend([
+      rule("image/png", 0, 0, b"\x89PNG", b"\xff"),
+      rule("image/png", 2, 0, b"IHDR", b""),
+      rule("image/x-empty", 0, 0, b"", b""),
+      rule("image/x-png", 0, 0, b"PNG", b""),
+      rule("image png", 0, 0, b"PNG", b""),
+    ]);
+    provider.aliases_mut().extend([
+      alias("image/x-png", "image/png"),
+      alias("image/x-apng", "image/x-png"),
+      alias("image/x-gone", "image/gone"),
+    ]);
+    provider.subclasses_mut().extend([
+      subclass("text/x-a", "text/x-b"),
+      subclass("text/x-b", "text/x-a"),
+    ]);
+    let mut globs = GlobDatabase::new();
+    globs.add_globs([glob("image/png", "*.png"), glob("image/png", "*.png")]);
+
+    let issues = lint_db(&provider, &globs);
+    let messages: Vec<_> = issues.iter().map(ToString::to_string).collect();
+    assert_eq!(
+      messages,
+      [
+        "\"image png\" is not a valid MIME type",
+        "alias image/x-apng maps to the alias image/x-png",
+        "alias image/x-gone maps to the unknown type image/gone",
+        "alias image/x-png also has magic rules or globs",
+        "text/x-a is a subclass of itself",
+        "text/x-b is a subclass of itself",
+        "magic rule of image/png has a 1 byte mask for a 4 byte value",
+        "magic rule of image/png at level 2 has no parent rule",
+        "magic rule of image/x-empty has an empty value",
+        "glob *.png of image/png is listed more than once",
+      ]
+    );
+    assert_eq!(
+      issues[2],
+      LintIssue::UnknownAliasTarget {
+        alias: "image/x-gone".to_string(),
+        name: "image/gone".to_string(),
+      }
+    );
+  }
+}
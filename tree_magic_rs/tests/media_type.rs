@@ -0,0 +1,67 @@
+mod media_type {
+  use tree_magic_rs as tree_magic;
+  use tree_magic_rs::MediaType;
+
+  #[test]
+  fn parse_parts() {
+    let mime = MediaType::parse("application/vnd.api+json").unwrap();
+    assert_eq!(mime.type_(), "application");
+    assert_eq!(mime.subtype(), "vnd.api+json");
+    assert_eq!(mime.suffix(), Some("json"));
+    assert_eq!(mime.essence(), "application/vnd.api+json");
+    assert_eq!(mime.params().count(), 0);
+  }
+  #[test]
+  fn parse_params() {
+    let mime = MediaType::parse("text/plain ; charset=UTF-8;format=\"flo\\\"wed\"").unwrap();
+    assert_eq!(mime.param("charset"), Some("UTF-8"));
+    assert_eq!(mime.param("FORMAT"), Some("flo\"wed"));
+    assert_eq!(mime.suffix(), None);
+    assert_eq!(
+      mime.to_string(),
+      "text/plain; charset=UTF-8; format=\"flo\\\"wed\""
+    );
+  }
+  #[test]
+  fn parse_invalid() {
+    assert!(MediaType::parse("").is_err());
+    assert!(MediaType::parse("text").is_err());
+    assert!(MediaType::parse("text/").is_err());
+    assert!(MediaType::parse("/plain").is_err());
+    assert!(MediaType::parse("text/plain;charset").is_err());
+    assert_eq!(MediaType::parse("text/pl ain").unwrap_err().offset, 8);
+  }
+  #[test]
+  fn case_insensitive_eq() {
+    let a = MediaType::parse("IMAGE/SVG+XML; Charset=utf-8").unwrap();
+    let b: MediaType = "image/svg+xml; charset=utf-8".parse().unwrap();
+    assert_eq!(a, b);
+    assert_ne!(a, "image/svg+xml; charset=UTF-8");
+    assert_ne!(a, "image/svg+xml");
+  }
+  #[test]
+  fn suffix_parent() {
+    let svg = MediaType::parse("image/svg+xml").unwrap();
+    assert_eq!(svg.suffix_parent(), Some("application/xml"));
+    let xml = MediaType::parse("application/xml").unwrap();
+    assert_eq!(xml.suffix_parent(), None);
+    let unknown = MediaType::parse("application/x-foo+bar").unwrap();
+    assert_eq!(unknown.suffix_parent(), None);
+  }
+
+  #[test]
+  fn match_u8_media_type() {
+    let db = tree_magic::MimeDatabase::new();
+    let gif = MediaType::parse("image/gif; foo=bar").unwrap();
+    assert!(db.match_u8(&gif, include_bytes!("image/gif")));
+    assert!(db.match_u8("Image/GIF", include_bytes!("image/gif")));
+    assert!(!db.match_u8(&gif, include_bytes!("image/png")));
+  }
+  #[test]
+  fn from_u8_media_type() {
+    let db = tree_magic::MimeDatabase::new();
+    let result = db.media_type_from_u8(include_bytes!("image/png"));
+    assert_eq!(result.type_(), "image");
+    assert_eq!(result.subtype(), "png");
+  }
+}
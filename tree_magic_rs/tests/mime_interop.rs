@@ -0,0 +1,53 @@
+#![cfg(feature = "mime")]
+mod mime_interop {
+  use std::path::Path;
+  use tree_magic_rs as tree_magic;
+  use tree_magic_rs::MediaType;
+
+  #[test]
+  fn media_type_to_mime() {
+    let media_type = MediaType::parse("image/svg+xml; charset=utf-8").unwrap();
+    let mime = mime::Mime::try_from(&media_type).unwrap();
+    assert_eq!(mime.essence_str(), "image/svg+xml");
+    assert_eq!(mime.suffix(), Some(mime::XML));
+    assert_eq!(mime.get_param(mime::CHARSET), Some(mime::UTF_8));
+    assert_eq!(MediaType::from(&mime), media_type);
+  }
+  #[test]
+  fn match_u8_mime() {
+    let db = tree_magic::MimeDatabase::new();
+    assert!(db.match_u8(&mime::IMAGE_GIF, include_bytes!("image/gif")));
+    assert!(!db.match_u8(&mime::IMAGE_PNG, include_bytes!("image/gif")));
+  }
+  #[test]
+  fn mime_from_u8() {
+    let db = tree_magic::MimeDatabase::new();
+    assert_eq!(
+      db.mime_from_u8(include_bytes!("image/png")),
+      mime::IMAGE_PNG
+    );
+    assert_eq!(
+      db.mime_from_filepath(Path::new("tests/image/gif")),
+      Some(mime::IMAGE_GIF)
+    );
+  }
+
+  #[cfg(feature = "http")]
+  #[test]
+  fn content_type() {
+    let db = tree_magic::MimeDatabase::new();
+    assert_eq!(
+      db.content_type_from_u8(include_bytes!("text/plain")),
+      "text/plain; charset=utf-8"
+    );
+    assert_eq!(
+      db.content_type_from_u8(include_bytes!("image/png")),
+      "image/png"
+    );
+    assert_eq!(
+      db.content_type_from_filepath(Path::new("tests/text/plain"))
+        .unwrap(),
+      "text/plain; charset=utf-8"
+    );
+  }
+}
@@ -0,0 +1,158 @@
+mod common;
+
+mod ole_storage {
+  use crate::common::tempdir;
+  use tree_magic_rs::MimeDatabase;
+
+  const OLE: &str = "application/x-ole-storage";
+  const NO_STREAM: u32 = 0xffff_ffff;
+  const END_OF_CHAIN: u32 = 0xffff_fffe;
+  const WORD_CLSID: [u8; 16] = [
+    0x06, 0x09, 0x02, 0, 0, 0, 0, 0, 0xc0, 0, 0, 0, 0, 0, 0, 0x46,
+  ];
+
+  fn db() -> MimeDatabase {
+    crate::common::db(&[(OLE, b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1")])
+  }
+
+  /// A version 3 compound file with the allocation table in the first sector, `padding` unused
+  /// sectors and then the directory.
+  ///
+  /// Entries are `(name, type, parent)`, where the parent is the index of a storage plus one,
+  /// or 0 for the root. The children of a storage are linked as right siblings.
+  fn cfb(clsid: [u8; 16], entries: &[(&str, u8, usize)], padding: u32) -> Vec<u8> {
+    let mut directory = vec![entry("Root Entry", 5, clsid)];
+    directory.extend(
+      entries
+        .iter()
+        .map(|&(name, kind, _)| entry(name, kind, [0; 16])),
+    );
+    let mut last_child = vec![None; directory.len()];
+    for (i, &(_, _, parent)) in entries.iter().enumerate() {
+      let id = (i + 1) as u32;
+      let (link, offset) = match last_child[parent] {
+        Some(sibling) => (sibling, 72),
+        None => (parent, 76),
+      };
+      directory[link][offset..offset + 4].copy_from_slice(&id.to_le_bytes());
+      last_child[parent] = Some(id as usize);
+    }
+    let directory: Vec<u8> = directory.concat();
+    let directory_sectors = directory.len().div_ceil(512) as u32;
+    let first_directory_sector = 1 + padding;
+
+    let mut header = Vec::new();
+    header.extend(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1");
+    header.extend([0; 16]);
+    header.extend([0x3e, 0, 3, 0, 0xfe, 0xff, 9, 0, 6, 0]);
+    header.extend([0; 10]); // reserved, directory sector count
+    header.extend(1u32.to_le_bytes());
+    header.extend(first_directory_sector.to_le_bytes());
+    header.extend([0; 4]);
+    header.extend(4096u32.to_le_bytes());
+    header.extend(END_OF_CHAIN.to_le_bytes());
+    header.extend([0; 4]);
+    header.extend(END_OF_CHAIN.to_le_bytes());
+    header.extend([0; 4]);
+    header.extend(0u32.to_le_bytes());
+    header.resize(512, 0xff);
+
+    let mut fat = vec![NO_STREAM; 128];
+    fat[0] = 0xffff_fffd;
+    for sector in first_directory_sector..first_directory_sector + directory_sectors {
+      fat[sector as usize] = sector + 1;
+    }
+    fat[(first_directory_sector + directory_sectors - 1) as usize] = END_OF_CHAIN;
+
+    let mut out = header;
+    out.extend(fat.iter().flat_map(|sector| sector.to_le_bytes()));
+    out.resize(out.len() + padding as usize * 512, 0);
+    out.extend(&directory);
+    out.resize(out.len().next_multiple_of(512), 0);
+    out
+  }
+
+  fn entry(name: &str, kind: u8, clsid: [u8; 16]) -> Vec<u8> {
+    let mut entry: Vec<u8> = name
+      .encode_utf16()
+      .chain([0])
+      .flat_map(u16::to_le_bytes)
+      .collect();
+    let name_len = entry.len() as u16;
+    entry.resize(64, 0);
+    entry.extend(name_len.to_le_bytes());
+    entry.extend([kind, 1]);
+    entry.extend([NO_STREAM; 3].iter().flat_map(|id| id.to_le_bytes()));
+    entry.extend(clsid);
+    entry.resize(128, 0);
+    entry
+  }
+
+  #[test]
+  fn root_clsid() {
+    let db = db();
+    let doc = cfb(WORD_CLSID, &[("Data", 2, 0)], 0);
+    assert_eq!(db.from_u8(&doc), "application/msword");
+    assert!(db.match_u8("application/msword", &doc));
+    let mut excel = WORD_CLSID;
+    excel[..2].copy_from_slice(&[0x20, 0x08]);
+    assert_eq!(db.from_u8(&cfb(excel, &[], 0)), "application/vnd.ms-excel");
+    assert_eq!(db.from_u8(&cfb([0; 16], &[], 0)), OLE);
+  }
+
+  #[test]
+  fn stream_names() {
+    let db = db();
+    for (name, mimetype) in [
+      ("WordDocument", "application/msword"),
+      ("Workbook", "application/vnd.ms-excel"),
+      ("Book", "application/vnd.ms-excel"),
+      ("PowerPoint Document", "application/vnd.ms-powerpoint"),
+    ] {
+      let document = cfb([0; 16], &[("\u{1}CompObj", 2, 0), (name, 2, 0)], 0);
+      assert_eq!(db.from_u8(&document), mimetype);
+      assert!(db.match_u8(mimetype, &document));
+      assert!(db.match_u8(OLE, &document));
+    }
+    let installer = cfb([0; 16], &[("\u{4840}\u{3f3f}\u{4577}\u{446c}", 2, 0)], 0);
+    assert_eq!(db.from_u8(&installer), "application/x-msi");
+  }
+
+  #[test]
+  fn nested_streams() {
+    let db = db();
+    // The document is an attachment of the message
+    let message = cfb(
+      [0; 16],
+      &[
+        ("__attach_version1.0_#00000000", 1, 0),
+        ("WordDocument", 2, 1),
+        ("__substg1.0_0037001F", 2, 0),
+      ],
+      0,
+    );
+    assert_eq!(db.from_u8(&message), "application/vnd.ms-outlook");
+    let storage = cfb([0; 16], &[("Objects", 1, 0), ("WordDocument", 2, 1)], 0);
+    assert_eq!(db.from_u8(&storage), OLE);
+  }
+
+  #[test]
+  fn truncated() {
+    let db = db();
+    let doc = cfb([0; 16], &[("WordDocument", 2, 0)], 8);
+    assert_eq!(db.from_u8(&doc), "application/msword");
+    assert_eq!(db.from_u8(&doc[..2048]), OLE);
+  }
+
+  #[test]
+  fn from_filepath() {
+    let dir = tempdir();
+    // The directory is past the start of the file that is read for magic
+    let path = dir.join("book");
+    std::fs::write(&path, cfb([0; 16], &[("Workbook", 2, 0)], 8)).unwrap();
+    let db = db();
+    assert_eq!(db.from_filepath(&path), Some("application/vnd.ms-excel"));
+    assert!(db.match_filepath("application/vnd.ms-excel", &path));
+    assert!(!db.match_filepath("application/msword", &path));
+  }
+}
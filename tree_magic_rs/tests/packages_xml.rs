@@ -0,0 +1,226 @@
+#![cfg(feature = "xml")]
+mod common;
+
+mod packages_xml {
+  use crate::common::tempdir;
+  use tree_magic_rs::db::{
+    parse_packages_xml, LoadResult, MagicRule, OwnedBuildableDb, PackageComment, PackageGlob,
+    PackageRootXml, PackagesXmlError, PackagesXmlProviderExt,
+  };
+  use tree_magic_rs::MimeDatabase;
+
+  const PACKAGE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">
+  <mime-type type="image/x-test">
+    <comment>Test image</comment>
+    <comment xml:lang="de">Testbild</comment>
+    <generic-icon name="image-x-generic"/>
+    <glob pattern="*.tst"/>
+    <glob pattern="*.TST" weight="60" case-sensitive="true"/>
+    <magic priority="60">
+      <match type="string" value="\x89TST\r\n\0" offset="0">
+        <match type="big16" value="0x0102" offset="8:15" mask="0xff00"/>
+        <match type="little32" value="258" offset="8">
+          <match type="byte" value="010" offset="12"/>
+        </match>
+      </match>
+      <match type="host16" value="0x0102" offset="0"/>
+      <match type="string" value="a\\b" mask="0xffdfff" offset="4"/>
+    </magic>
+    <alias type="image/x-test-alias"/>
+    <sub-class-of type="image/x-test-parent"/>
+  </mime-type>
+  <mime-type type="application/x-test+xml">
+    <comment>Test document</comment>
+    <root-XML namespaceURI="http://example.com/test" localName="test"/>
+    <sub-class-of type="application/xml"/>
+  </mime-type>
+</mime-info>
+"#;
+
+  #[test]
+  fn parse_mime_types() {
+    let mime_types = parse_packages_xml(PACKAGE).unwrap();
+    assert_eq!(mime_types.len(), 2);
+    let image = &mime_types[0];
+    assert_eq!(image.mime_type, "image/x-test");
+    assert_eq!(
+      image.comments,
+      [
+        PackageComment {
+          lang: None,
+          text: "Test image".to_string(),
+        },
+        PackageComment {
+          lang: Some("de".to_string()),
+          text: "Testbild".to_string(),
+        },
+      ]
+    );
+    assert_eq!(image.generic_icon.as_deref(), Some("image-x-generic"));
+    assert_eq!(
+      image.globs,
+      [
+        PackageGlob {
+          pattern: "*.tst".to_string(),
+          weight: 50,
+          case_sensitive: false,
+        },
+        PackageGlob {
+          pattern: "*.TST".to_string(),
+          weight: 60,
+          case_sensitive: true,
+        },
+      ]
+    );
+    assert_eq!(image.aliases, ["image/x-test-alias"]);
+    assert_eq!(image.sub_class_of, ["image/x-test-parent"]);
+    assert_eq!(
+      mime_types[1].root_xml,
+      [PackageRootXml {
+        namespace_uri: "http://example.com/test".to_string(),
+        local_name: "test".to_string(),
+      }]
+    );
+  }
+
+  #[test]
+  fn translate_matches() {
+    let mime_types = parse_packages_xml(PACKAGE).unwrap();
+    let rules: Vec<_> = mime_types[0]
+      .magic_rules
+      .iter()
+      .map(|rule| {
+        (
+          rule.priority(),
+          rule.indent_level(),
+          rule.start_off(),
+          rule.val().to_vec(),
+          rule.mask().to_vec(),
+          rule.word_len(),
+          rule.region_len(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      rules,
+      [
+        (60, 0, 0, b"\x89TST\r\n\0".to_vec(), vec![], 1, 0),
+        (60, 1, 8, vec![1, 2], vec![0xff, 0], 1, 7),
+        (60, 1, 8, vec![2, 1, 0, 0], vec![], 1, 0),
+        (60, 2, 12, vec![8], vec![], 1, 0),
+        (60, 0, 0, vec![1, 2], vec![], 2, 0),
+        (60, 0, 4, b"a\\b".to_vec(), vec![0xff, 0xdf, 0xff], 1, 0),
+      ]
+    );
+  }
+
+  #[test]
+  fn invalid_matches() {
+    let invalid = |attributes: &str| {
+      let xml = format!(
+        r#"<mime-info><mime-type type="a/b"><magic><match {attributes}/></magic></mime-type></mime-info>"#
+      );
+      parse_packages_xml(&xml).unwrap_err()
+    };
+    for attributes in [
+      r#"type="byte" value="256" offset="0""#,
+      r#"type="big16" value="x" offset="0""#,
+      r#"type="string" value="ab" mask="0xff" offset="0""#,
+      r#"type="string" value="ab" mask="0x€0" offset="0""#,
+      r#"type="string" value="ab" offset="2:1""#,
+      r#"type="float" value="1" offset="0""#,
+      r#"type="string" value="ab""#,
+    ] {
+      assert!(
+        matches!(
+          invalid(attributes),
+          PackagesXmlError::InvalidElement { ref mime_type, .. } if mime_type == "a/b"
+        ),
+        "{attributes}"
+      );
+    }
+    assert!(matches!(
+      parse_packages_xml("<mime-info>").unwrap_err(),
+      PackagesXmlError::XmlError(_)
+    ));
+  }
+
+  #[test]
+  fn load_into_db() {
+    let mut db = OwnedBuildableDb::new();
+    let load_result = db.load_packages_xml_str(PACKAGE).unwrap();
+    assert_eq!(
+      load_result,
+      LoadResult {
+        magic_rules_num: 6,
+        aliases_num: 1,
+        subclasses_num: 2,
+      }
+    );
+
+    let override_package = r#"<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">
+  <mime-type type="image/x-test">
+    <magic-deleteall/>
+    <magic><match type="string" value="TEST" offset="0"/></magic>
+  </mime-type>
+</mime-info>"#;
+    db.load_packages_xml_str(override_package).unwrap();
+    let database = MimeDatabase::from_provider(&db);
+    assert_eq!(database.from_u8(b"TEST image data"), "image/x-test");
+    assert!(database.match_u8("image/x-test-alias", b"TEST image data"));
+  }
+
+  #[test]
+  fn offset_range() {
+    let mut db = OwnedBuildableDb::new();
+    db.load_packages_xml_str(
+      r#"<mime-info><mime-type type="text/x-test"><magic>
+  <match type="string" value="TEST" offset="2:4"/>
+</magic></mime-type></mime-info>"#,
+    )
+    .unwrap();
+    let database = MimeDatabase::from_provider(&db);
+    // The end of the range is the last offset the value may start at
+    assert!(!database.match_u8("text/x-test", b"_TEST____"));
+    assert!(database.match_u8("text/x-test", b"__TEST___"));
+    assert!(database.match_u8("text/x-test", b"____TEST_"));
+    assert!(!database.match_u8("text/x-test", b"_____TEST"));
+  }
+
+  #[test]
+  fn host_order_values() {
+    let mut db = OwnedBuildableDb::new();
+    db.load_packages_xml_str(
+      r#"<mime-info><mime-type type="application/x-host"><magic>
+  <match type="host16" value="0x0102" offset="0">
+    <match type="host32" value="0x03040506" offset="2"/>
+  </match>
+</magic></mime-type></mime-info>"#,
+    )
+    .unwrap();
+    let database = MimeDatabase::from_provider(&db);
+    let little: &[u8] = b"\x02\x01\x06\x05\x04\x03";
+    let big: &[u8] = b"\x01\x02\x03\x04\x05\x06";
+    let (host, other) = if cfg!(target_endian = "little") {
+      (little, big)
+    } else {
+      (big, little)
+    };
+    assert!(database.match_u8("application/x-host", host));
+    assert!(!database.match_u8("application/x-host", other));
+  }
+
+  #[test]
+  fn load_dir() {
+    let dir = tempdir();
+    std::fs::write(dir.join("test.xml"), PACKAGE).unwrap();
+    std::fs::write(dir.join("broken.xml"), "<mime-info>").unwrap();
+    std::fs::write(dir.join("ignored.txt"), "<mime-info>").unwrap();
+
+    let mut db = OwnedBuildableDb::new();
+    let (load_result, errs) = db.load_packages_dir(&dir).unwrap_err();
+    assert_eq!(load_result.magic_rules_num, 6);
+    assert_eq!(errs.len(), 1);
+  }
+}
@@ -0,0 +1,236 @@
+mod common;
+
+mod policy {
+  use crate::common::provider;
+  use tree_magic_rs::db::{OwnedAlias, OwnedSubclass};
+  use tree_magic_rs::{MimeDatabase, Policy, PolicyAction, PolicyError, PolicyRule, Selector};
+
+  fn db() -> MimeDatabase {
+    let mut provider = provider(&[
+      ("image/png", b"\x89PNG"),
+      ("image/svg+xml", b"<svg"),
+      ("application/pdf", b"%PDF-"),
+      ("application/x-pdf-portfolio", b"%PDF-PORTFOLIO"),
+      ("application/x-executable", b"\x7fELF"),
+      ("application/x-shellscript", b"#!/bin/sh"),
+      ("application/x-ms-dos-executable", b"MZ"),
+      ("image/x-exe-icon", b"MZICON"),
+    ]);
+    provider.subclasses_mut().extend([
+      OwnedSubclass::new(
+        "application/x-pdf-portfolio".to_string(),
+        "application/pdf".to_string(),
+      ),
+      OwnedSubclass::new(
+        "application/x-shellscript".to_string(),
+        "application/x-executable".to_string(),
+      ),
+      OwnedSubclass::new(
+        "application/x-shellscript".to_string(),
+        "text/plain".to_string(),
+      ),
+      OwnedSubclass::new(
+        "image/x-exe-icon".to_string(),
+        "application/x-ms-dos-executable".to_string(),
+      ),
+    ]);
+    provider.aliases_mut().push(OwnedAlias::new(
+      "application/x-msdos-program".to_string(),
+      "application/x-ms-dos-executable".to_string(),
+    ));
+    MimeDatabase::from_provider(&provider)
+  }
+
+  fn selector(s: &str) -> Selector {
+    s.parse().unwrap()
+  }
+
+  fn upload_policy() -> Policy {
+    Policy::new(PolicyAction::Deny)
+      .with_rule(PolicyRule::deny(Selector::Executable))
+      .with_rule(PolicyRule::allow(selector("image/*")).except(selector("image/svg+xml")))
+      .with_rule(PolicyRule::allow(selector("descendants:application/pdf")))
+      .with_max_sniff_len(64 * 1024)
+  }
+
+  #[test]
+  fn parse_selectors() {
+    assert_eq!(selector("*/*"), Selector::Any);
+    assert_eq!(selector("image/*"), Selector::TopLevel("image".to_string()));
+    assert_eq!(
+      selector("descendants:application/pdf"),
+      Selector::DescendantsOf("application/pdf".to_string())
+    );
+    assert_eq!(selector("executable"), Selector::Executable);
+    for s in [
+      "*/*",
+      "image/*",
+      "image/png",
+      "descendants:application/pdf",
+      "executable",
+    ] {
+      assert_eq!(selector(s).to_string(), s);
+    }
+    for s in [
+      "image",
+      "image/*/x",
+      "*/png",
+      "descendants:image/*",
+      "text/ plain",
+    ] {
+      assert!(
+        matches!(
+          s.parse::<Selector>(),
+          Err(PolicyError::InvalidSelector { .. })
+        ),
+        "{s}"
+      );
+    }
+  }
+
+  #[test]
+  fn evaluate_uploads() {
+    let db = db();
+    let policy = upload_policy();
+    let rules = policy.rules();
+
+    let png = policy.evaluate(&db, b"\x89PNG\r\n");
+    assert!(png.is_allowed());
+    assert_eq!(png.mime_type, "image/png");
+    assert_eq!(png.clause, Some(&rules[1]));
+
+    // The exception falls through to the default action
+    let svg = policy.evaluate(&db, b"<svg/>");
+    assert_eq!(svg.action, PolicyAction::Deny);
+    assert_eq!(svg.clause, None);
+
+    let portfolio = policy.evaluate(&db, b"%PDF-PORTFOLIO");
+    assert_eq!(portfolio.mime_type, "application/x-pdf-portfolio");
+    assert!(portfolio.is_allowed());
+    assert_eq!(portfolio.clause, Some(&rules[2]));
+
+    // Executables are denied before the image rule is reached
+    for sample in [&b"\x7fELF"[..], b"#!/bin/sh\n", b"MZ", b"MZICON"] {
+      let decision = policy.evaluate(&db, sample);
+      assert_eq!(decision.action, PolicyAction::Deny, "{sample:?}");
+      assert_eq!(decision.clause, Some(&rules[0]), "{sample:?}");
+    }
+    assert_eq!(
+      policy
+        .evaluate_type(&db, "application/x-msdos-program")
+        .clause,
+      Some(&rules[0])
+    );
+  }
+
+  #[test]
+  fn limit_sniffed_bytes() {
+    let db = db();
+    let policy = Policy::new(PolicyAction::Deny)
+      .with_rule(PolicyRule::allow(selector("application/x-pdf-portfolio")))
+      .with_max_sniff_len(8);
+    assert_eq!(
+      policy.evaluate(&db, b"%PDF-PORTFOLIO").mime_type,
+      "application/pdf"
+    );
+    assert!(!policy.evaluate(&db, b"%PDF-PORTFOLIO").is_allowed());
+  }
+
+  #[test]
+  fn is_a() {
+    let db = db();
+    assert!(db.is_a("application/x-pdf-portfolio", "application/pdf"));
+    assert!(db.is_a("Application/PDF", "application/pdf"));
+    assert!(db.is_a("image/x-exe-icon", "application/x-msdos-program"));
+    assert!(db.is_a("image/png", "application/octet-stream"));
+    assert!(!db.is_a("application/pdf", "application/x-pdf-portfolio"));
+  }
+
+  #[cfg(feature = "toml")]
+  #[test]
+  fn read_policy_file() {
+    let policy = Policy::from_toml_str(
+      r#"
+default = "deny"
+max_sniff = 65536
+
+[[rule]]
+action = "deny"
+match = "executable"
+
+[[rule]]
+action = "allow"
+match = "image/*"
+except = ["image/svg+xml"]
+
+[[rule]]
+action = "allow"
+match = "descendants:application/pdf"
+"#,
+    )
+    .unwrap();
+    assert_eq!(policy, upload_policy());
+    assert_eq!(
+      policy.rules()[1].to_string(),
+      "allow image/* except image/svg+xml"
+    );
+
+    let allow_all = Policy::from_toml_str("default = \"allow\"").unwrap();
+    assert_eq!(allow_all.default_action(), PolicyAction::Allow);
+    assert_eq!(allow_all.max_sniff_len(), None);
+  }
+
+  #[cfg(feature = "toml")]
+  #[test]
+  fn policy_file_without_max_sniff() {
+    let policy = Policy::from_toml_str(
+      r#"
+[[rule]]
+action = "allow"
+match = "text/plain"
+"#,
+    )
+    .unwrap();
+    assert_eq!(policy.max_sniff_len(), None);
+    // Binary data after the default sniff length
+    let mut bytes = vec![b'a'; Policy::DEFAULT_SNIFF_LEN];
+    bytes.extend([0; 64]);
+    let db = db();
+    assert!(!policy.evaluate(&db, &bytes).is_allowed());
+
+    let dir = crate::common::tempdir();
+    let path = dir.join("upload");
+    std::fs::write(&path, &bytes).unwrap();
+    let decision = policy.evaluate_filepath(&db, &path).unwrap();
+    assert_eq!(decision.mime_type, "text/plain");
+    assert!(decision.is_allowed());
+  }
+
+  #[cfg(feature = "toml")]
+  #[test]
+  fn reject_invalid_policy_files() {
+    let invalid_value = |text: &str| match Policy::from_toml_str(text) {
+      Err(PolicyError::InvalidValue { key }) => key,
+      other => panic!("{other:?}"),
+    };
+    assert_eq!(invalid_value("default = \"maybe\""), "default");
+    assert_eq!(invalid_value("max_sniff = -1"), "max_sniff");
+    assert_eq!(invalid_value("unknown = 1"), "unknown");
+    assert_eq!(
+      invalid_value("[[rule]]\nmatch = \"image/*\""),
+      "rule.action"
+    );
+    assert_eq!(
+      invalid_value("[[rule]]\naction = \"allow\"\nmatch = \"image/*\"\ndescendants = true"),
+      "rule.descendants"
+    );
+    assert!(matches!(
+      Policy::from_toml_str("[[rule]]\naction = \"allow\"\nmatch = \"image\""),
+      Err(PolicyError::InvalidSelector { .. })
+    ));
+    assert!(matches!(
+      Policy::from_toml_str("default ="),
+      Err(PolicyError::TomlError(_))
+    ));
+  }
+}
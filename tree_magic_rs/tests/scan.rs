@@ -0,0 +1,145 @@
+mod common;
+
+mod scan {
+  use crate::common::{tempdir, TempDir};
+  use std::num::NonZeroUsize;
+  use std::path::Path;
+  use std::sync::Arc;
+  use tree_magic_rs::{MimeDatabase, ScanError, ScanOptions, SymlinkPolicy, TypeTotals};
+
+  fn db() -> Arc<MimeDatabase> {
+    Arc::new(crate::common::db(&[("image/png", b"\x89PNG")]))
+  }
+
+  /// `a.png`, `notes.txt`, `.hidden.txt`, `scratch.tmp` and `sub/deeper/b.png`
+  fn tree() -> TempDir {
+    let root = tempdir();
+    let write = |path: &str, contents: &[u8]| {
+      let path = root.join(path);
+      std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+      std::fs::write(path, contents).unwrap();
+    };
+    write("a.png", b"\x89PNG\r\n\x1a\n");
+    write("notes.txt", b"hello");
+    write(".hidden.txt", b"secret");
+    write("scratch.tmp", b"\x00\x01");
+    write("sub/deeper/b.png", b"\x89PNG\r\n\x1a\n\x00\x00");
+    root
+  }
+
+  fn relative_paths(root: &Path, options: &ScanOptions) -> Vec<String> {
+    let mut paths: Vec<String> = db()
+      .scan(root, options)
+      .map(|result| {
+        let path = result.unwrap().path;
+        path
+          .strip_prefix(root)
+          .unwrap()
+          .to_string_lossy()
+          .into_owned()
+      })
+      .collect();
+    paths.sort();
+    paths
+  }
+
+  #[test]
+  fn summarize_tree() {
+    let root = tree();
+    let options = ScanOptions::new().with_threads(NonZeroUsize::new(3).unwrap());
+    let summary = db().scan(&root, &options).summary();
+    assert_eq!(summary.errors, 0);
+    assert_eq!(
+      summary.types["image/png"],
+      TypeTotals {
+        files: 2,
+        bytes: 18
+      }
+    );
+    assert_eq!(summary.types["text/plain"].files, 1);
+    assert_eq!(summary.types["application/octet-stream"].files, 1);
+    assert_eq!(
+      summary.total,
+      TypeTotals {
+        files: 4,
+        bytes: 25
+      }
+    );
+  }
+
+  #[test]
+  fn filter_entries() {
+    let root = tree();
+    assert_eq!(
+      relative_paths(&root, &ScanOptions::new().with_hidden(true)),
+      [
+        ".hidden.txt",
+        "a.png",
+        "notes.txt",
+        "scratch.tmp",
+        "sub/deeper/b.png"
+      ]
+    );
+    assert_eq!(
+      relative_paths(
+        &root,
+        &ScanOptions::new().with_max_depth(1).with_exclude("*.tmp")
+      ),
+      ["a.png", "notes.txt"]
+    );
+    assert_eq!(
+      relative_paths(&root, &ScanOptions::new().with_exclude("sub/deeper")),
+      ["a.png", "notes.txt", "scratch.tmp"]
+    );
+    // A file as root yields just that file
+    let file = root.join("a.png");
+    let entries: Vec<_> = db().scan(&file, &ScanOptions::new()).collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].as_ref().unwrap().mime_type, "image/png");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn symlinks() {
+    use std::os::unix::fs::symlink;
+    let root = tree();
+    symlink(root.join("a.png"), root.join("link.png")).unwrap();
+    symlink(root.join("sub"), root.join("linked-sub")).unwrap();
+    // A cycle back to the root
+    symlink(&root, root.join("sub/loop")).unwrap();
+
+    let options = ScanOptions::new();
+    assert_eq!(
+      relative_paths(&root, &options.clone().with_symlinks(SymlinkPolicy::Skip)),
+      ["a.png", "notes.txt", "scratch.tmp", "sub/deeper/b.png"]
+    );
+    assert_eq!(
+      relative_paths(
+        &root,
+        &options.clone().with_symlinks(SymlinkPolicy::FollowFiles)
+      ),
+      [
+        "a.png",
+        "link.png",
+        "notes.txt",
+        "scratch.tmp",
+        "sub/deeper/b.png"
+      ]
+    );
+    // Each directory is only walked once
+    let followed = relative_paths(&root, &options.with_symlinks(SymlinkPolicy::Follow));
+    assert_eq!(followed.len(), 5);
+    assert!(followed.contains(&"link.png".to_string()));
+  }
+
+  #[test]
+  fn report_errors() {
+    let dir = tempdir();
+    let root = dir.join("missing");
+    let results: Vec<_> = db().scan(&root, &ScanOptions::new()).collect();
+    assert!(matches!(
+      results.as_slice(),
+      [Err(ScanError::IoError { path, .. })] if *path == root
+    ));
+  }
+}
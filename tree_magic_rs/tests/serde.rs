@@ -0,0 +1,54 @@
+#![cfg(feature = "serde")]
+mod common;
+
+mod serde {
+  use crate::common::test_provider;
+  use tree_magic_rs::db::{LoadResult, OwnedBuildableDb};
+  use tree_magic_rs::{MediaType, MimeDatabase};
+
+  #[test]
+  fn buildable_db_round_trip() {
+    let db = test_provider();
+    let json = serde_json::to_string(&db).unwrap();
+    let deserialized: OwnedBuildableDb = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, db);
+
+    let original = MimeDatabase::from_provider(&db);
+    let rebuilt = MimeDatabase::from_provider(&deserialized);
+    let samples: [&[u8]; 3] = [
+      include_bytes!("image/png"),
+      include_bytes!("image/gif"),
+      include_bytes!("text/plain"),
+    ];
+    for sample in samples {
+      assert_eq!(rebuilt.from_u8(sample), original.from_u8(sample));
+    }
+    assert_eq!(rebuilt.from_u8(samples[0]), "image/x-test-png");
+    assert_eq!(rebuilt.from_u8(samples[1]), "image/gif");
+    assert!(rebuilt.match_u8("image/x-png", samples[0]));
+  }
+  #[test]
+  fn load_result() {
+    let load_result = LoadResult {
+      magic_rules_num: 3,
+      aliases_num: 2,
+      subclasses_num: 1,
+    };
+    let json = serde_json::to_string(&load_result).unwrap();
+    assert_eq!(
+      serde_json::from_str::<LoadResult>(&json).unwrap(),
+      load_result
+    );
+  }
+  #[test]
+  fn media_type() {
+    let media_type = MediaType::parse("text/plain; charset=utf-8").unwrap();
+    let json = serde_json::to_string(&media_type).unwrap();
+    assert_eq!(json, "\"text/plain; charset=utf-8\"");
+    assert_eq!(
+      serde_json::from_str::<MediaType>(&json).unwrap(),
+      media_type
+    );
+    assert!(serde_json::from_str::<MediaType>("\"text\"").is_err());
+  }
+}
@@ -0,0 +1,181 @@
+mod common;
+
+mod shared_mime_db {
+  use crate::common::tempdir;
+  use proptest::prelude::*;
+  use std::collections::BTreeMap;
+  use tree_magic_rs::db::{
+    write_aliases_file, write_magic_file, write_subclasses_file, write_xdg_shared_magic_dir, Alias,
+    DbProvider, MagicRule, OwnedAlias, OwnedBuildableDb, OwnedMagicRule, OwnedSubclass,
+    SharedMimeDbProviderExt, Subclass,
+  };
+
+  fn mime_type() -> impl Strategy<Value = String> {
+    "[a-z]{1,8}/[a-z0-9.+-]{1,12}"
+  }
+
+  fn magic_rule(mime_type: String, priority: u32) -> impl Strategy<Value = OwnedMagicRule> {
+    (
+      0..4u32,
+      any::<u32>(),
+      prop::collection::vec(any::<u8>(), 1..16),
+      any::<bool>(),
+      prop::sample::select(vec![1u32, 2, 4]),
+      prop::option::weighted(0.3, 1..100u32),
+    )
+      .prop_flat_map(
+        move |(indent_level, start_off, val, masked, word_len, region)| {
+          let mask_len = if masked { val.len() } else { 0 };
+          let mime_type = mime_type.clone();
+          prop::collection::vec(any::<u8>(), mask_len).prop_map(move |mask| {
+            OwnedMagicRule::new(
+              priority,
+              mime_type.clone(),
+              indent_level,
+              start_off,
+              val.clone().into(),
+              mask.into(),
+              word_len,
+              region.unwrap_or(0),
+            )
+          })
+        },
+      )
+  }
+
+  fn section() -> impl Strategy<Value = Vec<OwnedMagicRule>> {
+    (mime_type(), 0..100u32).prop_flat_map(|(mime_type, priority)| {
+      prop::collection::vec(magic_rule(mime_type, priority), 1..8)
+    })
+  }
+
+  fn db() -> impl Strategy<Value = OwnedBuildableDb> {
+    (
+      prop::collection::vec(section(), 0..8),
+      prop::collection::vec((mime_type(), mime_type()), 0..8),
+      prop::collection::vec((mime_type(), mime_type()), 0..8),
+    )
+      .prop_map(|(sections, aliases, subclasses)| {
+        let mut db = OwnedBuildableDb::new();
+        db.magic_rules_mut().extend(sections.into_iter().flatten());
+        db.aliases_mut().extend(
+          aliases
+            .into_iter()
+            .map(|(alias, name)| OwnedAlias::new(alias, name)),
+        );
+        db.subclasses_mut().extend(
+          subclasses
+            .into_iter()
+            .map(|(mime_type, parent)| OwnedSubclass::new(mime_type, parent)),
+        );
+        db
+      })
+  }
+
+  /// The rules of each section in order, which is what the writer preserves
+  fn sections(db: &OwnedBuildableDb) -> BTreeMap<(u32, String), Vec<OwnedMagicRule>> {
+    let mut sections = BTreeMap::<_, Vec<_>>::new();
+    for rule in db.iter_magic_rules() {
+      sections
+        .entry((rule.priority(), rule.mime_type().to_string()))
+        .or_default()
+        .push(rule.clone());
+    }
+    sections
+  }
+
+  fn sorted_pairs<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> Vec<(&'a str, &'a str)> {
+    let mut pairs: Vec<_> = pairs.collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+  }
+
+  proptest! {
+    #[test]
+    fn round_trip(db in db()) {
+      let dir = tempdir();
+      write_xdg_shared_magic_dir(&db, &dir).unwrap();
+      let mut parsed = OwnedBuildableDb::new();
+      parsed.load_from_xdg_shared_magic_dir(&dir).unwrap();
+
+      prop_assert_eq!(sections(&parsed), sections(&db));
+      prop_assert_eq!(
+        sorted_pairs(parsed.iter_aliases().map(|a| (a.alias(), a.name()))),
+        sorted_pairs(db.iter_aliases().map(|a| (a.alias(), a.name())))
+      );
+      prop_assert_eq!(
+        sorted_pairs(parsed.iter_subclasses().map(|s| (s.mime_type(), s.parent()))),
+        sorted_pairs(db.iter_subclasses().map(|s| (s.mime_type(), s.parent())))
+      );
+
+      // Writing the parsed database again gives the same files
+      let mut magic = Vec::new();
+      write_magic_file(&db, &mut magic).unwrap();
+      let mut reparsed_magic = Vec::new();
+      write_magic_file(&parsed, &mut reparsed_magic).unwrap();
+      prop_assert_eq!(magic, reparsed_magic);
+    }
+  }
+
+  #[test]
+  fn magic_file_layout() {
+    let mut db = OwnedBuildableDb::new();
+    db.magic_rules_mut().extend([
+      OwnedMagicRule::new(
+        50,
+        "image/png".to_string(),
+        0,
+        0,
+        b"\x89PNG".as_slice().into(),
+        Box::new([]),
+        1,
+        0,
+      ),
+      OwnedMagicRule::new(
+        80,
+        "text/x-test".to_string(),
+        1,
+        4,
+        b"ab".as_slice().into(),
+        b"\xff\xdf".as_slice().into(),
+        2,
+        10,
+      ),
+    ]);
+    let mut magic = Vec::new();
+    write_magic_file(&db, &mut magic).unwrap();
+    assert_eq!(
+      magic,
+      b"MIME-Magic\0\n[80:text/x-test]\n1>4=\0\x02ab&\xff\xdf~2+10\n[50:image/png]\n>0=\0\x04\x89PNG\n"
+    );
+
+    db.aliases_mut().extend([
+      OwnedAlias::new("image/x-png".to_string(), "image/png".to_string()),
+      OwnedAlias::new("image/apng".to_string(), "image/png".to_string()),
+    ]);
+    let mut aliases = Vec::new();
+    write_aliases_file(&db, &mut aliases).unwrap();
+    assert_eq!(aliases, b"image/apng image/png\nimage/x-png image/png\n");
+
+    let mut subclasses = Vec::new();
+    write_subclasses_file(&db, &mut subclasses).unwrap();
+    assert!(subclasses.is_empty());
+  }
+
+  #[test]
+  fn reject_unencodable_rules() {
+    let mut db = OwnedBuildableDb::new();
+    db.magic_rules_mut().push(OwnedMagicRule::new(
+      50,
+      "image/png".to_string(),
+      0,
+      0,
+      b"\x89PNG".as_slice().into(),
+      b"\xff".as_slice().into(),
+      1,
+      0,
+    ));
+    assert!(write_magic_file(&db, std::io::sink()).is_err());
+  }
+}
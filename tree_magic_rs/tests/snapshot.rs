@@ -0,0 +1,94 @@
+mod common;
+
+mod snapshot {
+  use crate::common::{tempdir, test_provider};
+  use tree_magic_rs::db::{
+    read_snapshot, write_snapshot, write_snapshot_file, OwnedBuildableDb, Snapshot, SnapshotError,
+  };
+  use tree_magic_rs::MimeDatabase;
+
+  fn snapshot_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_snapshot(&test_provider(), &mut bytes).unwrap();
+    bytes
+  }
+
+  #[test]
+  fn round_trip() {
+    let bytes = snapshot_bytes();
+    let provider = read_snapshot(&bytes).unwrap();
+    assert_eq!(OwnedBuildableDb::from_provider(&provider), test_provider());
+  }
+
+  #[test]
+  fn detect_from_snapshot() {
+    let snapshot = Snapshot::from_vec(snapshot_bytes()).unwrap();
+    let db = MimeDatabase::from_provider(&snapshot.provider().unwrap());
+    assert_eq!(db.from_u8(include_bytes!("image/png")), "image/x-test-png");
+    assert_eq!(db.from_u8(include_bytes!("image/gif")), "image/gif");
+    assert!(db.match_u8("image/x-png", include_bytes!("image/png")));
+  }
+
+  #[test]
+  fn fingerprint() {
+    let snapshot = Snapshot::from_vec(snapshot_bytes()).unwrap();
+    let db = MimeDatabase::from_provider(&snapshot.provider().unwrap());
+    assert_eq!(db.fingerprint(), snapshot.checksum());
+    assert_eq!(
+      MimeDatabase::from_provider(&test_provider()).fingerprint(),
+      snapshot.checksum()
+    );
+    assert_ne!(
+      MimeDatabase::from_provider(&OwnedBuildableDb::new()).fingerprint(),
+      snapshot.checksum()
+    );
+  }
+
+  #[test]
+  fn reject_corrupted() {
+    let bytes = snapshot_bytes();
+
+    let mut corrupted = bytes.clone();
+    *corrupted.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+      read_snapshot(&corrupted),
+      Err(SnapshotError::ChecksumMismatch)
+    ));
+
+    let mut future = bytes.clone();
+    future[8] = 99;
+    assert!(matches!(
+      Snapshot::from_vec(future),
+      Err(SnapshotError::UnsupportedVersion { version: 99 })
+    ));
+
+    assert!(matches!(
+      read_snapshot(&bytes[..bytes.len() - 1]),
+      Err(SnapshotError::Malformed { .. })
+    ));
+    assert!(matches!(
+      read_snapshot(b"not a snapshot"),
+      Err(SnapshotError::BadMagic)
+    ));
+  }
+
+  #[test]
+  fn read_file() {
+    let dir = tempdir();
+    let path = dir.join("db.snap");
+    write_snapshot_file(&test_provider(), &path).unwrap();
+
+    let snapshot = Snapshot::read(&path).unwrap();
+    assert_eq!(snapshot.as_bytes(), snapshot_bytes());
+    #[cfg(feature = "mmap")]
+    {
+      // SAFETY: The file is not modified while mapped.
+      let mapped = unsafe { Snapshot::map(&path) }.unwrap();
+      assert_eq!(mapped.checksum(), snapshot.checksum());
+      assert_eq!(
+        OwnedBuildableDb::from_provider(&mapped.provider().unwrap()),
+        test_provider()
+      );
+    }
+  }
+}
@@ -0,0 +1,116 @@
+mod text {
+  use tree_magic_rs::db::OwnedBuildableDb;
+  use tree_magic_rs::{is_text, MimeDatabase, TextClassifier, TextEncoding, TextVerdict};
+
+  fn text(encoding: TextEncoding, bom: bool) -> TextVerdict {
+    TextVerdict::Text { encoding, bom }
+  }
+
+  fn utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+  }
+
+  fn utf16be(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+  }
+
+  #[test]
+  fn eight_bit() {
+    assert_eq!(is_text(b""), text(TextEncoding::Ascii, false));
+    assert_eq!(
+      is_text(b"plain\ttext\r\n"),
+      text(TextEncoding::Ascii, false)
+    );
+    assert_eq!(
+      is_text("naïve café\n".as_bytes()),
+      text(TextEncoding::Utf8, false)
+    );
+    // Cut off in the middle of `é`
+    assert_eq!(
+      is_text(&"café".as_bytes()[..4]),
+      text(TextEncoding::Utf8, false)
+    );
+    // ISO-8859-1
+    assert_eq!(
+      is_text(b"na\xefve caf\xe9\n"),
+      text(TextEncoding::Unknown8Bit, false)
+    );
+    assert_eq!(
+      is_text(b"\x1b[1mbold\x1b[0m\n"),
+      text(TextEncoding::Ascii, false)
+    );
+  }
+
+  #[test]
+  fn byte_order_marks() {
+    assert_eq!(
+      is_text(b"\xef\xbb\xbfhello"),
+      text(TextEncoding::Utf8, true)
+    );
+    let mut bytes = b"\xff\xfe".to_vec();
+    bytes.extend(utf16le("grüße"));
+    assert_eq!(is_text(&bytes), text(TextEncoding::Utf16Le, true));
+    let mut bytes = b"\xfe\xff".to_vec();
+    bytes.extend(utf16be("grüße"));
+    assert_eq!(is_text(&bytes), text(TextEncoding::Utf16Be, true));
+    let bytes: Vec<u8> = "\u{feff}hi"
+      .chars()
+      .flat_map(|char| u32::from(char).to_le_bytes())
+      .collect();
+    assert_eq!(is_text(&bytes), text(TextEncoding::Utf32Le, true));
+    let bytes: Vec<u8> = "\u{feff}hi"
+      .chars()
+      .flat_map(|char| u32::from(char).to_be_bytes())
+      .collect();
+    assert_eq!(is_text(&bytes), text(TextEncoding::Utf32Be, true));
+  }
+
+  #[test]
+  fn utf16_without_bom() {
+    assert_eq!(
+      is_text(&utf16le("Hello, world!\n")),
+      text(TextEncoding::Utf16Le, false)
+    );
+    assert_eq!(
+      is_text(&utf16be("Hello, world!\n")),
+      text(TextEncoding::Utf16Be, false)
+    );
+  }
+
+  #[test]
+  fn binary() {
+    assert_eq!(is_text(b"text\0with a NUL"), TextVerdict::Binary);
+    // Pseudo-random bytes without zeros
+    let mut state = 0x2545_f491_u32;
+    let bytes: Vec<u8> = std::iter::repeat_with(|| {
+      state ^= state << 13;
+      state ^= state >> 17;
+      state ^= state << 5;
+      state.to_le_bytes()[0].max(1)
+    })
+    .take(512)
+    .collect();
+    assert!(!bytes.contains(&0));
+    assert_eq!(is_text(&bytes), TextVerdict::Binary);
+    // UTF-16 that decodes to control characters
+    assert_eq!(is_text(&[1, 0, 2, 0, 3, 0, 4, 0]), TextVerdict::Binary);
+  }
+
+  #[test]
+  fn control_ratio() {
+    let bell = b"ding\x07dong\x07";
+    assert_eq!(is_text(bell), TextVerdict::Binary);
+    let lenient = TextClassifier::new().with_max_control_ratio(0.25);
+    assert_eq!(lenient.classify(bell), text(TextEncoding::Ascii, false));
+    assert!((lenient.max_control_ratio() - 0.25).abs() < f32::EPSILON);
+  }
+
+  #[test]
+  fn database() {
+    let db = MimeDatabase::from_provider(&OwnedBuildableDb::new());
+    assert_eq!(db.from_u8(&utf16le("Hello, world!\n")), "text/plain");
+    assert_eq!(db.from_u8(b"\x01\x02\x03\x04"), "application/octet-stream");
+    let db = db.with_text_classifier(TextClassifier::new().with_max_control_ratio(1.0));
+    assert_eq!(db.from_u8(b"\x01\x02\x03\x04"), "text/plain");
+  }
+}
@@ -0,0 +1,155 @@
+#![cfg(feature = "xml")]
+mod tika {
+  use tree_magic_rs::db::{
+    parse_tika_mimetypes, Alias, DbProvider, MagicRule, OwnedBuildableDb, Subclass,
+    TikaMimetypesError, TikaProviderExt, TikaUnsupported,
+  };
+  use tree_magic_rs::MimeDatabase;
+
+  const MIMETYPES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE mime-info [
+  <!ELEMENT mime-info (mime-type)+>
+]>
+<mime-info xmlns:tika="https://tika.apache.org/">
+  <mime-type type="application/pdf">
+    <alias type="application/x-pdf"/>
+    <acronym>PDF</acronym>
+    <_comment>Portable Document Format</_comment>
+    <tika:link>http://en.wikipedia.org/wiki/PDF</tika:link>
+    <magic priority="50">
+      <match value="%PDF-" type="string" offset="0"/>
+      <match value="\xef\xbb\xbf%PDF-" type="string" offset="0:8"/>
+    </magic>
+    <glob pattern="*.pdf"/>
+  </mime-type>
+
+  <mime-type type="image/x-test">
+    <magic priority="60">
+      <match value="0x54455354" type="string" offset="0">
+        <match value="0x0100" type="big16" offset="4" mask="0xff00"/>
+        <match value="^v[0-9]" type="regex" offset="4"/>
+      </match>
+      <match value="tst!" type="stringignorecase" offset="0"/>
+      <match value="TST" type="unicodeLE" offset="2"/>
+      <match value="X" type="string" offset="0">
+        <match value="^[a-z]+" type="regex" offset="1"/>
+      </match>
+      <match value="Y" type="string" offset="0" minShouldMatch="2"/>
+    </magic>
+    <glob pattern="*.tst"/>
+    <glob pattern="^test[0-9]+$" isregex="true"/>
+    <sub-class-of type="application/octet-stream"/>
+  </mime-type>
+</mime-info>
+"#;
+
+  fn unsupported(reason: &str) -> TikaUnsupported {
+    TikaUnsupported {
+      mime_type: "image/x-test".to_string(),
+      element: "match".to_string(),
+      reason: reason.to_string(),
+    }
+  }
+
+  #[test]
+  fn parse_mime_types() {
+    let import = parse_tika_mimetypes(MIMETYPES).unwrap();
+    assert_eq!(import.mime_types.len(), 2);
+    let pdf = &import.mime_types[0];
+    assert_eq!(pdf.mime_type, "application/pdf");
+    assert_eq!(pdf.aliases, ["application/x-pdf"]);
+    assert_eq!(pdf.globs, ["*.pdf"]);
+    let rules: Vec<_> = pdf
+      .magic_rules
+      .iter()
+      .map(|rule| (rule.start_off(), rule.val(), rule.region_len()))
+      .collect();
+    assert_eq!(rules, [(0, &b"%PDF-"[..], 0), (0, b"\xef\xbb\xbf%PDF-", 8)]);
+
+    let test = &import.mime_types[1];
+    assert_eq!(test.globs, ["*.tst"]);
+    assert_eq!(test.sub_class_of, ["application/octet-stream"]);
+    let rules: Vec<_> = test
+      .magic_rules
+      .iter()
+      .map(|rule| {
+        (
+          rule.priority(),
+          rule.indent_level(),
+          rule.start_off(),
+          rule.val(),
+          rule.mask(),
+        )
+      })
+      .collect();
+    let no_mask: &[u8] = &[];
+    assert_eq!(
+      rules,
+      [
+        (60, 0, 0, &b"TEST"[..], no_mask),
+        (60, 1, 4, b"\x01\x00", b"\xff\x00"),
+        (60, 0, 0, b"TST!", b"\xdf\xdf\xdf\xff"),
+        (60, 0, 2, b"T\0S\0T\0", no_mask),
+      ]
+    );
+    assert_eq!(
+      import.unsupported,
+      [
+        unsupported("regular expressions are not supported"),
+        unsupported("regular expressions are not supported"),
+        unsupported("minShouldMatch is not supported"),
+        TikaUnsupported {
+          mime_type: "image/x-test".to_string(),
+          element: "glob".to_string(),
+          reason: "regular expressions are not supported".to_string(),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn load_mime_types() {
+    let mut db = OwnedBuildableDb::new();
+    let (load_result, unsupported) = db.load_tika_mimetypes_str(MIMETYPES).unwrap();
+    assert_eq!(load_result.magic_rules_num, 6);
+    assert_eq!(load_result.aliases_num, 1);
+    assert_eq!(load_result.subclasses_num, 1);
+    assert_eq!(unsupported.len(), 4);
+    assert!(db
+      .iter_aliases()
+      .any(|a| a.alias() == "application/x-pdf" && a.name() == "application/pdf"));
+    assert!(db
+      .iter_subclasses()
+      .any(|s| s.mime_type() == "image/x-test"));
+
+    let mime_db = MimeDatabase::from_provider(&db);
+    assert_eq!(mime_db.from_u8(b"%PDF-1.7\n"), "application/pdf");
+    assert_eq!(mime_db.from_u8(b"TEST\x01\x77"), "image/x-test");
+    assert_eq!(mime_db.from_u8(b"tSt!"), "image/x-test");
+  }
+
+  #[test]
+  fn non_ascii_hex_values() {
+    let import = parse_tika_mimetypes(
+      r#"<mime-info><mime-type type="image/x-test"><magic>
+        <match value="0x€0" type="string" offset="0"/>
+        <match value="ab" mask="0x€0" type="string" offset="0"/>
+      </magic></mime-type></mime-info>"#,
+    )
+    .unwrap();
+    assert_eq!(import.unsupported.len(), 2);
+    assert!(import.unsupported.iter().all(|u| u.element == "match"));
+  }
+
+  #[test]
+  fn reject_malformed_documents() {
+    assert!(matches!(
+      parse_tika_mimetypes("<mime-info><mime-type/></mime-info>"),
+      Err(TikaMimetypesError::MissingType)
+    ));
+    assert!(matches!(
+      parse_tika_mimetypes("<mime-info>"),
+      Err(TikaMimetypesError::XmlError(_))
+    ));
+  }
+}
@@ -0,0 +1,221 @@
+#![cfg(feature = "cli")]
+mod common;
+
+mod tmagic {
+  use crate::common::{magic_rule, tempdir, test_provider, TempDir};
+  use std::path::Path;
+  use std::process::{Command, Output};
+  use tree_magic_rs::db::{write_xdg_shared_magic_dir, OwnedAlias, OwnedSubclass};
+
+  const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+  /// A shared MIME info directory with the test database and globs for PNG and GIF
+  fn mime_dir() -> TempDir {
+    let dir = tempdir();
+    write_xdg_shared_magic_dir(&test_provider(), &dir).unwrap();
+    std::fs::write(
+      dir.join("globs2"),
+      "50:image/png:*.png\n50:image/gif:*.gif\n",
+    )
+    .unwrap();
+    dir
+  }
+
+  fn tmagic(mime_dir: &Path, args: &[&str]) -> (String, u8) {
+    let Output { status, stdout, .. } = Command::new(env!("CARGO_BIN_EXE_tmagic"))
+      .arg("--mime-dir")
+      .arg(mime_dir)
+      .args(args)
+      .output()
+      .unwrap();
+    let code = status.code().unwrap().try_into().unwrap();
+    (String::from_utf8(stdout).unwrap(), code)
+  }
+
+  #[test]
+  fn detect() {
+    let mime_dir = mime_dir();
+    let files = tempdir();
+    std::fs::write(files.join("picture.png"), PNG).unwrap();
+    std::fs::write(files.join("picture.gif"), PNG).unwrap();
+    let png = files.join("picture.png");
+    let png = png.to_str().unwrap();
+    let gif = files.join("picture.gif");
+    let gif = gif.to_str().unwrap();
+
+    assert_eq!(
+      tmagic(&mime_dir, &["detect", png]),
+      (
+        format!("{png}: image/png (name: image/png, consistent)\n"),
+        0
+      )
+    );
+    assert_eq!(
+      tmagic(&mime_dir, &["detect", "--by", "content", gif]),
+      (format!("{gif}: image/png\n"), 0)
+    );
+    assert_eq!(
+      tmagic(&mime_dir, &["detect", "--by", "name", gif]),
+      (format!("{gif}: image/gif\n"), 0)
+    );
+    // A name that does not fit the contents
+    let (out, code) = tmagic(&mime_dir, &["detect", gif]);
+    assert_eq!(
+      out,
+      format!("{gif}: image/png (name: image/gif, mismatched)\n")
+    );
+    assert_eq!(code, 1);
+
+    let (out, code) = tmagic(&mime_dir, &["--format", "json", "detect", png]);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json[0]["content_type"], "image/png");
+    assert_eq!(json[0]["verdict"], "Consistent");
+    assert_eq!(code, 0);
+
+    let missing = files.join("missing");
+    assert_eq!(
+      tmagic(&mime_dir, &["detect", missing.to_str().unwrap()]),
+      (String::new(), 2)
+    );
+  }
+
+  #[test]
+  fn xml_root_elements() {
+    let mut provider = test_provider();
+    provider
+      .magic_rules_mut()
+      .push(magic_rule("application/xml", b"<?xml"));
+    provider.subclasses_mut().push(OwnedSubclass::new(
+      "application/xml".to_string(),
+      "text/plain".to_string(),
+    ));
+    let mime_dir = tempdir();
+    write_xdg_shared_magic_dir(&provider, &mime_dir).unwrap();
+    std::fs::write(
+      mime_dir.join("XMLnamespaces"),
+      "http://www.w3.org/2000/svg svg image/svg+xml\n",
+    )
+    .unwrap();
+    let files = tempdir();
+    let svg = files.join("picture");
+    std::fs::write(
+      &svg,
+      r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg"/>"#,
+    )
+    .unwrap();
+    let svg = svg.to_str().unwrap();
+
+    assert_eq!(
+      tmagic(&mime_dir, &["detect", "--by", "content", svg]),
+      (format!("{svg}: image/svg+xml\n"), 0)
+    );
+  }
+
+  #[test]
+  fn match_files() {
+    let mime_dir = mime_dir();
+    let files = tempdir();
+    let png = files.join("picture");
+    std::fs::write(&png, PNG).unwrap();
+    let png = png.to_str().unwrap();
+
+    assert_eq!(
+      tmagic(&mime_dir, &["match", "image/x-png", png]),
+      (format!("{png}: image/x-png\n"), 0)
+    );
+    assert_eq!(
+      tmagic(&mime_dir, &["match", "image/gif", png]),
+      (format!("{png}: not image/gif\n"), 1)
+    );
+  }
+
+  #[test]
+  fn explain() {
+    let mime_dir = mime_dir();
+    let files = tempdir();
+    let gif = files.join("picture.gif");
+    std::fs::write(&gif, PNG).unwrap();
+    let gif = gif.to_str().unwrap();
+
+    let (out, code) = tmagic(&mime_dir, &["explain", gif]);
+    assert_eq!(
+      out,
+      format!(
+        "{gif}: image/png
+  magic: all/allfiles > application/octet-stream > image/png
+  glob: *.gif image/gif (weight 50)
+  name: mismatched
+  supertypes: application/octet-stream, all/allfiles, all/all
+"
+      )
+    );
+    assert_eq!(code, 1);
+  }
+
+  #[test]
+  fn tree() {
+    let mime_dir = mime_dir();
+    assert_eq!(
+      tmagic(&mime_dir, &["tree", "image/x-png"]),
+      (
+        "image/x-png
+  supertypes:
+    application/octet-stream
+      all/allfiles
+        all/all
+  subtypes:
+    image/x-test-png
+"
+        .to_string(),
+        0
+      )
+    );
+    assert_eq!(
+      tmagic(&mime_dir, &["tree", "image/x-test-png"]).0,
+      "image/x-test-png
+  supertypes:
+    image/png
+      application/octet-stream
+        all/allfiles
+          all/all
+"
+    );
+
+    let (out, _) = tmagic(&mime_dir, &["--format", "json", "tree", "image/png"]);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(json["parents"][0]["mime_type"], "application/octet-stream");
+    assert_eq!(json["children"][0]["mime_type"], "image/x-test-png");
+  }
+
+  #[test]
+  fn db_commands() {
+    let mime_dir = mime_dir();
+    let (out, code) = tmagic(&mime_dir, &["db", "stats"]);
+    assert_eq!(
+      out.lines().last(),
+      Some("total: 5 magic rules, 1 aliases, 1 subclasses, 2 globs")
+    );
+    assert_eq!(code, 0);
+
+    let (out, code) = tmagic(&mime_dir, &["db", "dump"]);
+    assert!(out.contains("alias image/x-png image/png\n"));
+    assert!(out.contains("subclass image/x-test-png image/png\n"));
+    assert!(out.contains("glob 50 image/gif *.gif\n"));
+    assert_eq!(code, 0);
+
+    assert_eq!(tmagic(&mime_dir, &["db", "lint"]), (String::new(), 0));
+    let mut provider = test_provider();
+    provider.aliases_mut().push(OwnedAlias::new(
+      "image/x-icon".to_string(),
+      "image/vnd.microsoft.icon".to_string(),
+    ));
+    write_xdg_shared_magic_dir(&provider, &mime_dir).unwrap();
+    assert_eq!(
+      tmagic(&mime_dir, &["db", "lint"]),
+      (
+        "alias image/x-icon maps to the unknown type image/vnd.microsoft.icon\n".to_string(),
+        1
+      )
+    );
+  }
+}
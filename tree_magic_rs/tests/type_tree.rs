@@ -0,0 +1,54 @@
+mod common;
+
+mod type_tree {
+  use crate::common::provider;
+  use tree_magic_rs::db::{OwnedAlias, OwnedSubclass};
+  use tree_magic_rs::MimeDatabase;
+
+  const ODT: &str = "application/vnd.oasis.opendocument.text";
+  const ODT_TEMPLATE: &str = "application/vnd.oasis.opendocument.text-template";
+
+  fn db() -> MimeDatabase {
+    let mut provider = provider(&[("application/zip", b"PK\x03\x04"), (ODT, b"PK\x03\x04ODT")]);
+    provider.subclasses_mut().extend([
+      OwnedSubclass::new(ODT.to_string(), "application/zip".to_string()),
+      OwnedSubclass::new(ODT_TEMPLATE.to_string(), ODT.to_string()),
+    ]);
+    provider.aliases_mut().push(OwnedAlias::new(
+      "application/x-zip-compressed".to_string(),
+      "application/zip".to_string(),
+    ));
+    MimeDatabase::from_provider(&provider)
+  }
+
+  #[test]
+  fn explain_detection() {
+    let db = db();
+    let odt = b"PK\x03\x04ODT";
+    let path = db.path_from_u8(odt);
+    assert_eq!(path.last(), Some(&db.from_u8(odt)));
+    assert_eq!(
+      path[path.len() - 3..],
+      ["application/octet-stream", "application/zip", ODT]
+    );
+    assert_eq!(db.parents(ODT), ["application/zip"]);
+    assert_eq!(db.parents("application/zip"), ["application/octet-stream"]);
+    assert_eq!(db.parents("text/x-unknown"), ["text/plain"]);
+    assert!(db.parents("all/all").is_empty());
+  }
+
+  #[test]
+  fn children() {
+    let db = db();
+    // The zip checker adds the formats it tells apart
+    let zip_children = db.children("application/x-zip-compressed");
+    assert!(zip_children.contains(&ODT));
+    assert!(zip_children.is_sorted());
+    // Types without magic are included
+    assert_eq!(db.children(ODT), [ODT_TEMPLATE]);
+    assert!(db.children(ODT_TEMPLATE).is_empty());
+    assert!(db
+      .children("application/octet-stream")
+      .contains(&"application/zip"));
+  }
+}
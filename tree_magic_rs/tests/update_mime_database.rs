@@ -0,0 +1,210 @@
+#![cfg(feature = "xml")]
+mod common;
+
+mod update_mime_database {
+  use crate::common::{tempdir, TempDir};
+  use std::path::Path;
+  use tree_magic_rs::db::{
+    merge_package_mime_types, parse_packages_xml, update_mime_database, write_mime_database,
+    OwnedBuildableDb, PackageMimeType, PackageRootXml, PackagesXmlError, PackagesXmlProviderError,
+    SharedMimeDbProviderExt,
+  };
+  use tree_magic_rs::MimeDatabase;
+
+  const PACKAGE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">
+  <mime-type type="image/x-test">
+    <comment>Test image</comment>
+    <comment xml:lang="de">Testbild</comment>
+    <acronym>TST</acronym>
+    <generic-icon name="image-x-generic"/>
+    <glob pattern="*.tst"/>
+    <magic priority="60">
+      <match type="string" value="TEST" offset="0"/>
+    </magic>
+    <alias type="image/x-test-alias"/>
+  </mime-type>
+  <mime-type type="application/x-test+xml">
+    <comment>Test &amp; document</comment>
+    <icon name="test-icon"/>
+    <glob pattern="*.testxml" weight="80"/>
+    <root-XML namespaceURI="http://example.com/test" localName="test"/>
+    <sub-class-of type="application/xml"/>
+  </mime-type>
+</mime-info>
+"#;
+
+  const OVERRIDE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">
+  <mime-type type="image/x-test">
+    <comment>Overridden test image</comment>
+    <glob-deleteall/>
+    <glob pattern="*.TST" case-sensitive="true"/>
+    <magic-deleteall/>
+    <magic priority="70">
+      <match type="string" value="OVERRIDE" offset="0"/>
+    </magic>
+  </mime-type>
+</mime-info>
+"#;
+
+  /// A temporary directory with an empty `packages` directory
+  fn mime_dir() -> TempDir {
+    let dir = tempdir();
+    std::fs::create_dir(dir.join("packages")).unwrap();
+    dir
+  }
+
+  fn read(dir: &Path, name: &str) -> String {
+    std::fs::read_to_string(dir.join(name)).unwrap()
+  }
+
+  #[test]
+  fn merge_overrides() {
+    let mut mime_types = parse_packages_xml(PACKAGE).unwrap();
+    mime_types.extend(parse_packages_xml(OVERRIDE).unwrap());
+    let merged = merge_package_mime_types(mime_types);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].mime_type, "application/x-test+xml");
+    let image = &merged[1];
+    assert_eq!(image.comments.len(), 2);
+    assert_eq!(image.comments[1].text, "Overridden test image");
+    assert_eq!(image.acronym.as_deref(), Some("TST"));
+    assert_eq!(image.globs.len(), 1);
+    assert_eq!(image.globs[0].pattern, "*.TST");
+    assert_eq!(image.magic_rules.len(), 1);
+    assert_eq!(image.aliases, ["image/x-test-alias"]);
+  }
+
+  #[test]
+  fn compile_database() {
+    let dir = mime_dir();
+    // Override.xml is applied last, even though it sorts first
+    std::fs::write(dir.join("packages/test.xml"), PACKAGE).unwrap();
+    std::fs::write(dir.join("packages/Override.xml"), OVERRIDE).unwrap();
+    std::fs::write(dir.join("packages/README"), "not a package").unwrap();
+
+    assert_eq!(update_mime_database(&dir).unwrap(), 2);
+
+    assert_eq!(
+      std::fs::read(dir.join("magic")).unwrap(),
+      b"MIME-Magic\0\n[70:image/x-test]\n>0=\0\x08OVERRIDE\n"
+    );
+    let header =
+      "# This file was automatically generated by the\n# update-mime-database command. DO NOT EDIT!\n";
+    assert_eq!(
+      read(&dir, "globs2"),
+      format!("{header}80:application/x-test+xml:*.testxml\n50:image/x-test:*.TST:cs\n")
+    );
+    assert_eq!(
+      read(&dir, "globs"),
+      format!("{header}application/x-test+xml:*.testxml\nimage/x-test:*.TST\n")
+    );
+    assert_eq!(read(&dir, "aliases"), "image/x-test-alias image/x-test\n");
+    assert_eq!(
+      read(&dir, "subclasses"),
+      "application/x-test+xml application/xml\n"
+    );
+    assert_eq!(
+      read(&dir, "types"),
+      "application/x-test+xml\nimage/x-test\n"
+    );
+    assert_eq!(read(&dir, "icons"), "application/x-test+xml:test-icon\n");
+    assert_eq!(
+      read(&dir, "generic-icons"),
+      "image/x-test:image-x-generic\n"
+    );
+    assert_eq!(
+      read(&dir, "XMLnamespaces"),
+      "http://example.com/test test application/x-test+xml\n"
+    );
+    assert_eq!(
+      read(&dir, "application/x-test+xml.xml"),
+      r#"<?xml version="1.0" encoding="utf-8"?>
+<mime-type xmlns="http://www.freedesktop.org/standards/shared-mime-info" type="application/x-test+xml">
+  <!--Created automatically by update-mime-database. DO NOT EDIT!-->
+  <comment>Test &amp; document</comment>
+  <sub-class-of type="application/xml"/>
+  <icon name="test-icon"/>
+  <glob pattern="*.testxml" weight="80"/>
+</mime-type>
+"#
+    );
+    let image_xml = read(&dir, "image/x-test.xml");
+    assert!(image_xml.contains("<comment>Overridden test image</comment>"));
+    assert!(image_xml.contains(r#"<comment xml:lang="de">Testbild</comment>"#));
+    assert!(image_xml.contains("<acronym>TST</acronym>"));
+    assert!(image_xml.contains(r#"<glob pattern="*.TST" case-sensitive="true"/>"#));
+
+    // The compiled database can be loaded again
+    let mut db = OwnedBuildableDb::new();
+    db.load_from_xdg_shared_magic_dir(&dir).unwrap();
+    let mime_db = MimeDatabase::from_provider(&db);
+    assert_eq!(mime_db.from_u8(b"OVERRIDE data"), "image/x-test");
+    assert_ne!(mime_db.from_u8(b"TEST data"), "image/x-test");
+  }
+
+  #[test]
+  fn hostile_type_names() {
+    let package = |mime_type: &str| {
+      format!(
+        r#"<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">{mime_type}</mime-info>"#
+      )
+    };
+    for mime_type in [
+      r#"<mime-type type="../../escaped"/>"#,
+      r#"<mime-type type="a//tmp/abs"/>"#,
+      r#"<mime-type type="a/.."/>"#,
+      r#"<mime-type type="a/b c"/>"#,
+      r#"<mime-type type="a/b; charset=utf-8"/>"#,
+      r#"<mime-type type="a/b"><alias type="../c"/></mime-type>"#,
+      r#"<mime-type type="a/b"><sub-class-of type="a/b/c"/></mime-type>"#,
+      r#"<mime-type type="a/b"><root-XML namespaceURI="urn:a b" localName="c"/></mime-type>"#,
+    ] {
+      assert!(
+        matches!(
+          parse_packages_xml(&package(mime_type)),
+          Err(PackagesXmlError::InvalidElement { .. })
+        ),
+        "{mime_type}"
+      );
+    }
+
+    let dir = mime_dir();
+    let mime_dir = dir.join("mime");
+    let hostile = [
+      PackageMimeType {
+        mime_type: "../../escaped".to_string(),
+        ..PackageMimeType::default()
+      },
+      PackageMimeType {
+        mime_type: "a//tmp/abs".to_string(),
+        ..PackageMimeType::default()
+      },
+      PackageMimeType {
+        mime_type: "a/b".to_string(),
+        root_xml: vec![PackageRootXml {
+          namespace_uri: String::new(),
+          local_name: "c\na/d x".to_string(),
+        }],
+        ..PackageMimeType::default()
+      },
+    ];
+    for mime in hostile {
+      assert!(matches!(
+        write_mime_database(std::slice::from_ref(&mime), &mime_dir),
+        Err(PackagesXmlProviderError::InvalidMimeType { ref mime_type, .. })
+          if *mime_type == mime.mime_type
+      ));
+    }
+    // Nothing is written, inside the directory or out of it
+    assert!(!mime_dir.exists());
+    assert!(!dir.join("escaped.xml").exists());
+  }
+
+  #[test]
+  fn missing_packages_dir() {
+    let dir = tempdir();
+    assert!(update_mime_database(&dir).is_err());
+  }
+}
@@ -0,0 +1,114 @@
+mod common;
+
+mod validate {
+  use crate::common::provider;
+  use std::path::Path;
+  use tree_magic_rs::db::{GlobDatabase, OwnedSubclass};
+  use tree_magic_rs::{MimeDatabase, Verdict, VerdictKind};
+
+  fn db() -> MimeDatabase {
+    let mut provider = provider(&[
+      ("image/jpeg", b"\xff\xd8\xff"),
+      ("application/x-msdownload", b"MZ"),
+      ("application/zip", b"PK\x03\x04"),
+      ("application/vnd.oasis.opendocument.text", b"PK\x03\x04ODT"),
+    ]);
+    provider.subclasses_mut().extend([
+      OwnedSubclass::new(
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document".to_string(),
+        "application/zip".to_string(),
+      ),
+      OwnedSubclass::new(
+        "application/vnd.oasis.opendocument.text".to_string(),
+        "application/zip".to_string(),
+      ),
+    ]);
+    let mut globs = GlobDatabase::new();
+    globs
+      .load_mime_types_str(
+        "image/jpeg jpg jpeg
+application/x-msdownload exe
+application/zip zip
+application/vnd.openxmlformats-officedocument.wordprocessingml.document docx
+application/vnd.oasis.opendocument.text odt
+video/mp2t ts
+text/vnd.trolltech.linguist ts
+",
+      )
+      .unwrap();
+    MimeDatabase::from_provider(&provider).with_globs(globs)
+  }
+
+  fn verdict<'a>(
+    kind: VerdictKind,
+    name_type: Option<&'a str>,
+    content_type: &'a str,
+  ) -> Verdict<'a> {
+    Verdict {
+      kind,
+      name_type,
+      content_type,
+    }
+  }
+
+  #[test]
+  fn classify_uploads() {
+    let db = db();
+    let jpeg = b"\xff\xd8\xff\xe0 JFIF";
+    assert_eq!(
+      db.validate("photo.JPG", jpeg),
+      verdict(VerdictKind::Consistent, Some("image/jpeg"), "image/jpeg")
+    );
+    let renamed = db.validate(Path::new("/uploads/photo.jpg"), b"MZ\x90\x00");
+    assert_eq!(
+      renamed,
+      verdict(
+        VerdictKind::Mismatched,
+        Some("image/jpeg"),
+        "application/x-msdownload"
+      )
+    );
+    assert!(!renamed.is_acceptable());
+
+    // Generic ZIP detection for a more specific name, and the other way around
+    let docx = db.validate("report.docx", b"PK\x03\x04word/");
+    assert_eq!(docx.kind, VerdictKind::Compatible);
+    assert!(docx.is_acceptable());
+    assert_eq!(
+      db.validate("report.zip", b"PK\x03\x04ODT").kind,
+      VerdictKind::Compatible
+    );
+    // Siblings are unrelated
+    assert_eq!(
+      db.validate("report.docx", b"PK\x03\x04ODT").kind,
+      VerdictKind::Mismatched
+    );
+  }
+
+  #[test]
+  fn undecidable_uploads() {
+    let db = db();
+    assert_eq!(
+      db.validate("README", b"\xff\xd8\xff"),
+      verdict(VerdictKind::Undecidable, None, "image/jpeg")
+    );
+    assert_eq!(
+      db.validate("photo.jpg", b"\x00\x01\x02\x03"),
+      verdict(
+        VerdictKind::Undecidable,
+        Some("image/jpeg"),
+        "application/octet-stream"
+      )
+    );
+  }
+
+  #[test]
+  fn any_matching_glob_counts() {
+    let db = db();
+    // `.ts` is ambiguous, the glob agreeing with the contents is reported
+    let verdict = db.validate("strings.ts", b"<?xml version=\"1.0\"?><TS/>");
+    assert_eq!(verdict.content_type, "text/plain");
+    assert_eq!(verdict.kind, VerdictKind::Compatible);
+    assert_eq!(verdict.name_type, Some("text/vnd.trolltech.linguist"));
+  }
+}
@@ -0,0 +1,211 @@
+mod common;
+
+mod xml_namespaces {
+  use crate::common::{ranged_rule, tempdir};
+  use std::path::PathBuf;
+  use tree_magic_rs::db::{
+    parse_xml_namespaces, OwnedBuildableDb, OwnedSubclass, XmlNamespaceDatabase, XmlNamespaceRule,
+    XmlNamespacesParseError,
+  };
+  use tree_magic_rs::{MimeDatabase, TextClassifier};
+
+  const XML_NAMESPACES: &str = "\
+http://www.w3.org/2000/svg svg image/svg+xml
+http://www.w3.org/2005/Atom feed application/atom+xml
+ rss application/rss+xml
+";
+
+  fn provider() -> OwnedBuildableDb {
+    let mut provider = OwnedBuildableDb::new();
+    // The declaration may follow a UTF-8 byte order mark
+    provider.magic_rules_mut().extend([
+      ranged_rule("application/xml", b"<?xml", 3),
+      ranged_rule("application/xml", b"<\0?\0x\0m\0l\0", 2),
+    ]);
+    provider.subclasses_mut().push(OwnedSubclass::new(
+      "application/xml".to_string(),
+      "text/plain".to_string(),
+    ));
+    provider
+  }
+
+  fn xml_namespaces() -> XmlNamespaceDatabase {
+    let mut xml_namespaces = XmlNamespaceDatabase::new();
+    xml_namespaces
+      .load_xml_namespaces_str(XML_NAMESPACES)
+      .unwrap();
+    xml_namespaces
+  }
+
+  fn db() -> MimeDatabase {
+    MimeDatabase::from_provider(&provider()).with_xml_namespaces(xml_namespaces())
+  }
+
+  #[test]
+  fn parse() {
+    let rules = parse_xml_namespaces(XML_NAMESPACES).unwrap();
+    assert_eq!(rules.len(), 3);
+    assert_eq!(
+      rules[2],
+      XmlNamespaceRule {
+        namespace_uri: String::new(),
+        local_name: "rss".to_string(),
+        mime_type: "application/rss+xml".to_string(),
+      }
+    );
+    assert_eq!(
+      parse_xml_namespaces("urn:a a text/x-a\nurn:b b\n"),
+      Err(XmlNamespacesParseError::LineParseError { line: 2 })
+    );
+    assert_eq!(
+      parse_xml_namespaces("urn:a a not-a-type"),
+      Err(XmlNamespacesParseError::LineParseError { line: 1 })
+    );
+  }
+
+  #[test]
+  fn match_root() {
+    let mut xml_namespaces = xml_namespaces();
+    assert_eq!(
+      xml_namespaces.match_root("http://www.w3.org/2000/svg", "svg"),
+      Some("image/svg+xml")
+    );
+    assert_eq!(
+      xml_namespaces.match_root("", "rss"),
+      Some("application/rss+xml")
+    );
+    assert_eq!(xml_namespaces.match_root("", "svg"), None);
+    xml_namespaces
+      .load_xml_namespaces_str("http://www.w3.org/2000/svg svg image/x-svg")
+      .unwrap();
+    assert_eq!(
+      xml_namespaces.match_root("http://www.w3.org/2000/svg", "svg"),
+      Some("image/x-svg")
+    );
+  }
+
+  #[test]
+  fn root_elements() {
+    let db = db();
+    let detect = |text: &str| db.from_u8(text.as_bytes());
+    assert_eq!(
+      detect("<?xml version=\"1.0\"?><svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+      "image/svg+xml"
+    );
+    assert_eq!(
+      detect("<?xml version='1.0'?>\n<a:feed\n  xmlns:a='http://www.w3.org/2005/Atom'>\n<title>"),
+      "application/atom+xml"
+    );
+    assert_eq!(
+      detect("<?xml version=\"1.0\"?><rss version=\"2.0\"><channel>"),
+      "application/rss+xml"
+    );
+    assert_eq!(
+      detect(concat!(
+        "\u{feff}<?xml version=\"1.0\"?>\n",
+        "<!-- <rss> -->\n",
+        "<?xml-stylesheet href=\"a.css\"?>\n",
+        "<!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \"svg11.dtd\" [\n",
+        "  <!ENTITY a \"<b>\">\n",
+        "]>\n",
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1\">",
+      )),
+      "image/svg+xml"
+    );
+    // Other namespaces, undeclared prefixes and cut off start tags stay generic XML
+    assert_eq!(
+      detect("<?xml version=\"1.0\"?><svg xmlns=\"urn:other\"/>"),
+      "application/xml"
+    );
+    assert_eq!(
+      detect("<?xml version=\"1.0\"?><a:feed><title>"),
+      "application/xml"
+    );
+    assert_eq!(
+      detect("<?xml version=\"1.0\"?><svg xmlns=\"http://www.w3.org/2000/svg"),
+      "application/xml"
+    );
+    // Documents that are not XML are not looked at
+    assert_eq!(detect("<rss version=\"2.0\">"), "text/plain");
+  }
+
+  #[test]
+  fn utf16() {
+    let db = db();
+    let text = "<?xml version=\"1.0\" encoding=\"UTF-16\"?><rss>";
+    let mut le = vec![0xff, 0xfe];
+    le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    assert_eq!(db.from_u8(&le), "application/rss+xml");
+    let le = &le[2..];
+    assert_eq!(db.from_u8(le), "application/rss+xml");
+  }
+
+  #[test]
+  fn type_graph() {
+    let db = db();
+    assert!(db.is_a("application/rss+xml", "application/xml"));
+    assert!(db.is_a("image/svg+xml", "text/plain"));
+    assert_eq!(db.parents("application/atom+xml"), ["application/xml"]);
+    assert!(db.match_u8(
+      "application/rss+xml",
+      b"<?xml version=\"1.0\"?><rss version=\"2.0\">"
+    ));
+    assert_eq!(
+      db.path_from_u8(b"<?xml version=\"1.0\"?><rss>"),
+      [
+        "all/allfiles",
+        "application/octet-stream",
+        "text/plain",
+        "application/xml",
+        "application/rss+xml"
+      ]
+    );
+    // Replacing the text classifier keeps the XML types
+    let db = db.with_text_classifier(TextClassifier::new().with_max_control_ratio(0.2));
+    assert_eq!(
+      db.from_u8(b"<?xml version=\"1.0\"?><rss>"),
+      "application/rss+xml"
+    );
+  }
+
+  #[test]
+  fn from_filepath() {
+    let dir = tempdir();
+    let path: PathBuf = dir.join("drawing");
+    std::fs::write(
+      &path,
+      "<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>\n",
+    )
+    .unwrap();
+    assert_eq!(db().from_filepath(&path), Some("image/svg+xml"));
+    assert!(db().match_filepath("image/svg+xml", &path));
+  }
+
+  #[test]
+  fn read_len() {
+    let svg = |padding: usize| {
+      format!(
+        "<?xml version=\"1.0\"?><!--{}-->\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>",
+        " ".repeat(padding)
+      )
+    };
+    assert_eq!(db().from_u8(svg(3900).as_bytes()), "image/svg+xml");
+    // Root elements after the first 4096 bytes are not looked for
+    assert_eq!(db().from_u8(svg(4100).as_bytes()), "application/xml");
+  }
+
+  #[test]
+  fn fingerprint() {
+    let plain = MimeDatabase::from_provider(&provider());
+    let xml = db();
+    assert_ne!(plain.fingerprint(), xml.fingerprint());
+    assert_eq!(xml.fingerprint(), db().fingerprint());
+    assert_eq!(
+      plain
+        .with_xml_namespaces(XmlNamespaceDatabase::new())
+        .fingerprint(),
+      MimeDatabase::from_provider(&provider()).fingerprint()
+    );
+    assert_eq!(xml.xml_namespaces().rules().len(), 3);
+  }
+}
@@ -0,0 +1,169 @@
+mod common;
+
+mod zip_container {
+  use crate::common::tempdir;
+  use tree_magic_rs::MimeDatabase;
+
+  const DOCX: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+  const XLSX: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+  const PPTX: &str = "application/vnd.openxmlformats-officedocument.presentationml.presentation";
+  const ODT: &str = "application/vnd.oasis.opendocument.text";
+
+  fn db() -> MimeDatabase {
+    crate::common::db(&[("application/zip", b"PK\x03\x04")])
+  }
+
+  /// A zip file with stored entries, a central directory and an end record.
+  ///
+  /// Entries with a data descriptor only have their sizes in the central directory.
+  fn zip(entries: &[(&str, &[u8], bool)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+    for &(name, data, descriptor) in entries {
+      let offset = out.len() as u32;
+      let size = data.len() as u32;
+      let flags: u16 = if descriptor { 0x08 } else { 0 };
+      let local_size = if descriptor { 0 } else { size };
+      out.extend(b"PK\x03\x04\x14\0");
+      out.extend(flags.to_le_bytes());
+      out.extend([0; 10]); // method, time, date, crc
+      out.extend(local_size.to_le_bytes());
+      out.extend(local_size.to_le_bytes());
+      out.extend((name.len() as u16).to_le_bytes());
+      out.extend([0, 0]);
+      out.extend(name.as_bytes());
+      out.extend(data);
+      if descriptor {
+        out.extend(b"PK\x07\x08\0\0\0\0");
+        out.extend(size.to_le_bytes());
+        out.extend(size.to_le_bytes());
+      }
+
+      directory.extend(b"PK\x01\x02\x14\0\x14\0");
+      directory.extend(flags.to_le_bytes());
+      directory.extend([0; 10]);
+      directory.extend(size.to_le_bytes());
+      directory.extend(size.to_le_bytes());
+      directory.extend((name.len() as u16).to_le_bytes());
+      directory.extend([0; 12]); // extra, comment, disk, attributes
+      directory.extend(offset.to_le_bytes());
+      directory.extend(name.as_bytes());
+    }
+    let offset = out.len() as u32;
+    out.extend(&directory);
+    out.extend(b"PK\x05\x06\0\0\0\0");
+    out.extend((entries.len() as u16).to_le_bytes());
+    out.extend((entries.len() as u16).to_le_bytes());
+    out.extend((directory.len() as u32).to_le_bytes());
+    out.extend(offset.to_le_bytes());
+    out.extend([0, 0]);
+    out
+  }
+
+  #[test]
+  fn mimetype_entry() {
+    let db = db();
+    let odt = zip(&[
+      ("mimetype", ODT.as_bytes(), false),
+      ("content.xml", b"<office:document-content/>", false),
+      ("META-INF/manifest.xml", b"<manifest:manifest/>", false),
+    ]);
+    assert_eq!(db.from_u8(&odt), ODT);
+    assert_eq!(
+      db.path_from_u8(&odt),
+      [
+        "all/allfiles",
+        "application/octet-stream",
+        "application/zip",
+        ODT
+      ]
+    );
+    let epub = zip(&[
+      ("mimetype", b"application/epub+zip", false),
+      ("META-INF/container.xml", b"<container/>", false),
+    ]);
+    assert_eq!(db.from_u8(&epub), "application/epub+zip");
+    // The prefix of a file is enough
+    assert_eq!(db.from_u8(&odt[..80]), ODT);
+    // Only the first entry names the type
+    let late = zip(&[
+      ("content.xml", b"", false),
+      ("mimetype", ODT.as_bytes(), false),
+    ]);
+    assert_eq!(db.from_u8(&late), "application/zip");
+  }
+
+  #[test]
+  fn office_open_xml() {
+    let db = db();
+    for (part, mimetype) in [
+      ("word/document.xml", DOCX),
+      ("xl/workbook.xml", XLSX),
+      ("ppt/presentation.xml", PPTX),
+    ] {
+      let document = zip(&[
+        ("[Content_Types].xml", b"<Types/>", false),
+        ("_rels/.rels", b"<Relationships/>", false),
+        (part, b"<document/>", false),
+      ]);
+      assert_eq!(db.from_u8(&document), mimetype);
+      assert!(db.match_u8(mimetype, &document));
+    }
+    let parts_only = zip(&[("word/document.xml", b"<document/>", false)]);
+    assert_eq!(db.from_u8(&parts_only), "application/zip");
+  }
+
+  #[test]
+  fn java_archives() {
+    let db = db();
+    let jar = zip(&[
+      ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n", false),
+      ("Main.class", b"\xca\xfe\xba\xbe", false),
+    ]);
+    assert_eq!(db.from_u8(&jar), "application/x-java-archive");
+    let apk = zip(&[
+      ("AndroidManifest.xml", b"\x03\x00\x08\x00", false),
+      ("classes.dex", b"dex\n035\0", false),
+      ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n", false),
+    ]);
+    assert_eq!(db.from_u8(&apk), "application/vnd.android.package-archive");
+    assert!(!db.match_u8("application/x-java-archive", &apk));
+  }
+
+  #[test]
+  fn central_directory() {
+    let db = db();
+    // The local headers cannot be walked past an entry with a data descriptor
+    let jar = zip(&[
+      ("Main.class", b"\xca\xfe\xba\xbe", true),
+      ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n", true),
+    ]);
+    assert_eq!(db.from_u8(&jar), "application/x-java-archive");
+    assert_eq!(
+      db.from_u8(&zip(&[("plain", b"text", false)])),
+      "application/zip"
+    );
+  }
+
+  #[test]
+  fn from_filepath() {
+    let dir = tempdir();
+    // The manifest is only found by seeking to the central directory
+    let padding = vec![b'x'; 100_000];
+    let path = dir.join("app");
+    std::fs::write(
+      &path,
+      zip(&[
+        ("classes.dex", &padding, false),
+        ("AndroidManifest.xml", b"\x03\x00\x08\x00", false),
+      ]),
+    )
+    .unwrap();
+    let db = db();
+    assert_eq!(
+      db.from_filepath(&path),
+      Some("application/vnd.android.package-archive")
+    );
+    assert!(db.match_filepath("application/vnd.android.package-archive", &path));
+  }
+}